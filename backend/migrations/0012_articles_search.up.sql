@@ -0,0 +1,11 @@
+-- Full-text search over articles. Title matches are ranked higher than matches
+-- in description, which in their turn are ranked higher than matches in body.
+--
+-- See: https://www.postgresql.org/docs/current/textsearch-controls.html
+ALTER TABLE "articles" ADD COLUMN search TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', title), 'A') ||
+    setweight(to_tsvector('english', description), 'B') ||
+    setweight(to_tsvector('english', body), 'C')
+) STORED;
+
+CREATE INDEX articles_search_gin ON "articles" USING gin(search);
@@ -0,0 +1,3 @@
+-- Supporting keyset (a.k.a. cursor) pagination over articles list,
+-- which is sorted by creation date (with identifier as a tie-breaker).
+CREATE INDEX articles_created_at_idx ON "articles" (created_at DESC, article_id DESC);
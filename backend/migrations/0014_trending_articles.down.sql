@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS comments_created_at_idx;
+
+DROP INDEX IF EXISTS favorites_created_at_idx;
+
+DROP TABLE IF EXISTS "trending_articles";
@@ -0,0 +1,19 @@
+-- Articles scores as of the latest trending refresh, which is happening
+-- periodically in a background workflow (see `temporal` module).
+CREATE TABLE IF NOT EXISTS "trending_articles" (
+    period          TEXT NOT NULL CHECK ( period IN ('day', 'week', 'month') ),
+    article_id      UUID NOT NULL REFERENCES "articles" (article_id) ON DELETE CASCADE,
+    score           DOUBLE PRECISION NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ,
+
+    PRIMARY KEY (period, article_id)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('trending_articles');
+
+CREATE INDEX trending_articles_score_idx ON "trending_articles" (period, score DESC);
+
+CREATE INDEX favorites_created_at_idx ON "favorites" (created_at);
+
+CREATE INDEX comments_created_at_idx ON "comments" (created_at);
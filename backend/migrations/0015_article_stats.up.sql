@@ -0,0 +1,14 @@
+-- Daily article views and read-throughs, which are counted in Redis
+-- and flushed here periodically (see `services::analytics` module).
+CREATE TABLE IF NOT EXISTS "article_stats" (
+    article_id      UUID NOT NULL REFERENCES "articles" (article_id) ON DELETE CASCADE,
+    day             DATE NOT NULL,
+    views           BIGINT NOT NULL DEFAULT 0,
+    reads           BIGINT NOT NULL DEFAULT 0,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ,
+
+    PRIMARY KEY (article_id, day)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('article_stats');
@@ -0,0 +1,17 @@
+DROP TRIGGER IF EXISTS count_article_comments ON "comments";
+DROP FUNCTION IF EXISTS count_article_comments;
+
+DROP TRIGGER IF EXISTS count_article_favorites ON "favorites";
+DROP FUNCTION IF EXISTS count_article_favorites;
+
+-- NB: we cannot use `put_creation_mutation_timestamps_guard_on` here,
+-- since the table now contains a generated column (see migration 0012)
+DROP TRIGGER guard_creation_mutation_timestamps ON "articles";
+CREATE TRIGGER guard_creation_mutation_timestamps
+    BEFORE UPDATE ON "articles"
+    FOR EACH ROW
+    EXECUTE FUNCTION guard_creation_mutation_timestamps();
+
+ALTER TABLE "articles"
+    DROP COLUMN favorites_count,
+    DROP COLUMN comments_count;
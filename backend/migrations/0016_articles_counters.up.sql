@@ -0,0 +1,62 @@
+ALTER TABLE "articles"
+    ADD COLUMN favorites_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN comments_count INTEGER NOT NULL DEFAULT 0;
+
+-- counters are maintained by the triggers below and changes to them should
+-- not be considered the article's modification, hence re-creating the guard
+-- (see migration 0002) to only react to the article's contents changes;
+-- NB: a whole-row comparison is not allowed here due to the generated column
+DROP TRIGGER guard_creation_mutation_timestamps ON "articles";
+CREATE TRIGGER guard_creation_mutation_timestamps
+    BEFORE UPDATE ON "articles"
+    FOR EACH ROW
+    WHEN (
+        (OLD.user_id, OLD.slug, OLD.title, OLD.description, OLD.body, OLD.tags, OLD.created_at)
+        IS DISTINCT FROM
+        (NEW.user_id, NEW.slug, NEW.title, NEW.description, NEW.body, NEW.tags, NEW.created_at)
+    )
+    EXECUTE FUNCTION guard_creation_mutation_timestamps();
+
+UPDATE "articles" article SET
+    favorites_count = (SELECT count(*) FROM favorites WHERE article_id = article.article_id),
+    comments_count = (SELECT count(*) FROM comments WHERE article_id = article.article_id);
+
+CREATE OR REPLACE FUNCTION count_article_favorites() RETURNS TRIGGER AS
+$$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE articles SET favorites_count = favorites_count + 1
+        WHERE article_id = NEW.article_id;
+    ELSE
+        UPDATE articles SET favorites_count = favorites_count - 1
+        WHERE article_id = OLD.article_id;
+    END IF;
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER count_article_favorites
+    AFTER INSERT OR DELETE ON "favorites"
+    FOR EACH ROW
+    EXECUTE FUNCTION count_article_favorites();
+
+CREATE OR REPLACE FUNCTION count_article_comments() RETURNS TRIGGER AS
+$$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE articles SET comments_count = comments_count + 1
+        WHERE article_id = NEW.article_id;
+    ELSE
+        UPDATE articles SET comments_count = comments_count - 1
+        WHERE article_id = OLD.article_id;
+    END IF;
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER count_article_comments
+    AFTER INSERT OR DELETE ON "comments"
+    FOR EACH ROW
+    EXECUTE FUNCTION count_article_comments();
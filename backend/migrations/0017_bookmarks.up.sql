@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS "bookmarks" (
+    user_id         UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    article_id      UUID NOT NULL REFERENCES "articles" (article_id) ON DELETE CASCADE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ,
+
+    PRIMARY KEY (user_id, article_id)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('bookmarks');
+
+-- Supporting keyset pagination over the user's reading list, which is
+-- sorted by the bookmark's creation date (with article as a tie-breaker).
+CREATE INDEX bookmarks_user_id_created_at_idx
+    ON "bookmarks" (user_id, created_at DESC, article_id DESC);
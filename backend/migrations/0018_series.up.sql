@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS "series" (
+    series_id       UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id         UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    slug            TEXT UNIQUE NOT NULL,
+    title           TEXT NOT NULL,
+    description     TEXT NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('series');
+
+-- An article can be part of at most one series, while its position is only
+-- meaningful relative to other entries of the series (there can be gaps
+-- after an article is deleted).
+CREATE TABLE IF NOT EXISTS "series_articles" (
+    series_id       UUID NOT NULL REFERENCES "series" (series_id) ON DELETE CASCADE,
+    article_id      UUID UNIQUE NOT NULL REFERENCES "articles" (article_id) ON DELETE CASCADE,
+    position        INTEGER NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ,
+
+    PRIMARY KEY (series_id, article_id)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('series_articles');
+
+CREATE INDEX series_articles_position_idx ON "series_articles" (series_id, position);
@@ -0,0 +1,4 @@
+DROP FUNCTION IF EXISTS article_authors_json(UUID, UUID);
+DROP TRIGGER IF EXISTS add_article_owner ON "articles";
+DROP FUNCTION IF EXISTS add_article_owner();
+DROP TABLE IF EXISTS "article_authors";
@@ -0,0 +1,59 @@
+-- Article's authors: the owner (who is also `articles.user_id`) and editors
+-- who have been invited by the owner; an invitation is pending as long as
+-- `accepted_at` is not set.
+CREATE TABLE IF NOT EXISTS "article_authors" (
+    article_id      UUID NOT NULL REFERENCES "articles" (article_id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    role            TEXT NOT NULL CHECK (role IN ('owner', 'editor')),
+    accepted_at     TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ,
+
+    PRIMARY KEY (article_id, user_id)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('article_authors');
+
+CREATE INDEX article_authors_user_id_idx ON "article_authors" (user_id);
+
+INSERT INTO "article_authors" (article_id, user_id, role, accepted_at)
+SELECT article_id, user_id, 'owner', created_at FROM "articles";
+
+CREATE OR REPLACE FUNCTION add_article_owner() RETURNS TRIGGER AS
+$$
+BEGIN
+    INSERT INTO article_authors (article_id, user_id, role, accepted_at)
+    VALUES (NEW.article_id, NEW.user_id, 'owner', NEW.created_at);
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER add_article_owner
+    AFTER INSERT ON "articles"
+    FOR EACH ROW
+    EXECUTE FUNCTION add_article_owner();
+
+-- Article's authors (the owner first) as seen by the viewer (if any).
+CREATE OR REPLACE FUNCTION article_authors_json(UUID, UUID) RETURNS JSONB AS
+$$
+    SELECT coalesce(
+        jsonb_agg(
+            jsonb_build_object(
+                'username', author.username,
+                'bio', author.bio,
+                'image', author.image,
+                'role', coauthor.role,
+                'following', $2 IS NOT NULL AND EXISTS(
+                    SELECT 1 FROM follows
+                    WHERE followed_user_id = author.user_id AND following_user_id = $2
+                )
+            )
+            ORDER BY coauthor.role = 'owner' DESC, coauthor.accepted_at
+        ),
+        '[]'::JSONB
+    )
+    FROM article_authors coauthor JOIN users author USING (user_id)
+    WHERE coauthor.article_id = $1 AND coauthor.accepted_at IS NOT NULL
+$$
+LANGUAGE sql STABLE;
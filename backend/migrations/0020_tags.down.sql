@@ -0,0 +1,5 @@
+DROP TRIGGER IF EXISTS register_article_tags ON "articles";
+DROP FUNCTION IF EXISTS register_article_tags();
+DROP TABLE IF EXISTS "tag_aliases";
+DROP TABLE IF EXISTS "tags";
+ALTER TABLE "users" DROP COLUMN IF EXISTS is_admin;
@@ -0,0 +1,61 @@
+ALTER TABLE "users" ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Registry of the tags used across articles; tags are normalized (trimmed,
+-- lowercased, and with the inner whitespace collapsed) before being written,
+-- and any new tag is registered here by the trigger below.
+CREATE TABLE IF NOT EXISTS "tags" (
+    name            TEXT PRIMARY KEY,
+    description     TEXT NOT NULL DEFAULT '',
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('tags');
+
+-- Admin-managed alternative spellings of tags: an alias is replaced with
+-- the tag it points to whenever articles are written or filtered by tags.
+CREATE TABLE IF NOT EXISTS "tag_aliases" (
+    alias           TEXT PRIMARY KEY,
+    tag             TEXT NOT NULL REFERENCES "tags" (name) ON DELETE CASCADE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('tag_aliases');
+
+CREATE INDEX tag_aliases_tag_idx ON "tag_aliases" (tag);
+
+-- normalizing the tags of the existing articles, which is not an edit
+-- of the articles' contents, hence disabling the guard (see migration 0016)
+ALTER TABLE "articles" DISABLE TRIGGER guard_creation_mutation_timestamps;
+
+UPDATE "articles" SET tags = normalized.tags
+FROM (
+    SELECT
+        article_id,
+        ARRAY(
+            SELECT DISTINCT lower(regexp_replace(btrim(tag), '\s+', ' ', 'g'))
+            FROM unnest(tags) tag
+            ORDER BY 1
+        ) AS tags
+    FROM "articles"
+) normalized
+WHERE articles.article_id = normalized.article_id AND articles.tags != normalized.tags;
+
+ALTER TABLE "articles" ENABLE TRIGGER guard_creation_mutation_timestamps;
+
+INSERT INTO "tags" (name) SELECT DISTINCT unnest(tags) FROM "articles";
+
+CREATE OR REPLACE FUNCTION register_article_tags() RETURNS TRIGGER AS
+$$
+BEGIN
+    INSERT INTO tags (name) SELECT unnest(NEW.tags) ON CONFLICT DO NOTHING;
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER register_article_tags
+    AFTER INSERT OR UPDATE OF tags ON "articles"
+    FOR EACH ROW
+    EXECUTE FUNCTION register_article_tags();
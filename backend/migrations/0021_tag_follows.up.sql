@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS "tag_follows" (
+    user_id         UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    tag             TEXT NOT NULL REFERENCES "tags" (name) ON DELETE CASCADE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ,
+
+    PRIMARY KEY (user_id, tag)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('tag_follows');
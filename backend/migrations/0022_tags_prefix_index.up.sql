@@ -0,0 +1,3 @@
+-- the primary key's index can only serve prefix searches (`LIKE 'prefix%'`)
+-- under the "C" collation, and so adding a collation-agnostic one
+CREATE INDEX tags_name_pattern_idx ON "tags" (name text_pattern_ops);
@@ -0,0 +1,32 @@
+DROP TRIGGER IF EXISTS collect_deleted_comments ON "comments";
+DROP FUNCTION IF EXISTS collect_deleted_comments;
+
+-- placeholders cannot be told from regular comments without the threads,
+-- and so removing them (along with their replies, see the cascade)
+DELETE FROM "comments" WHERE deleted_at IS NOT NULL;
+
+DROP TRIGGER count_article_comments ON "comments";
+CREATE OR REPLACE FUNCTION count_article_comments() RETURNS TRIGGER AS
+$$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE articles SET comments_count = comments_count + 1
+        WHERE article_id = NEW.article_id;
+    ELSE
+        UPDATE articles SET comments_count = comments_count - 1
+        WHERE article_id = OLD.article_id;
+    END IF;
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+CREATE TRIGGER count_article_comments
+    AFTER INSERT OR DELETE ON "comments"
+    FOR EACH ROW
+    EXECUTE FUNCTION count_article_comments();
+
+DROP INDEX IF EXISTS comments_parent_comment_id_idx;
+ALTER TABLE "comments"
+    DROP COLUMN parent_comment_id,
+    DROP COLUMN depth,
+    DROP COLUMN deleted_at;
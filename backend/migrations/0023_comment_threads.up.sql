@@ -0,0 +1,60 @@
+-- replies reference the comment they are replying to, while top-level
+-- comments have no parent; since a reply never changes its parent, we are
+-- also storing how deep in the thread the comment is (0 for the top-level)
+ALTER TABLE "comments"
+    ADD COLUMN parent_comment_id UUID REFERENCES "comments" (comment_id) ON DELETE CASCADE,
+    ADD COLUMN depth INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX comments_parent_comment_id_idx ON "comments" (parent_comment_id);
+
+-- a comment having replies is not removed but rather turned into a "deleted"
+-- placeholder (its `deleted_at` is set), and so it should no longer count
+-- towards the article's comments
+CREATE OR REPLACE FUNCTION count_article_comments() RETURNS TRIGGER AS
+$$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE articles SET comments_count = comments_count + 1
+        WHERE article_id = NEW.article_id;
+    ELSIF TG_OP = 'UPDATE' THEN
+        IF OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+            UPDATE articles SET comments_count = comments_count - 1
+            WHERE article_id = OLD.article_id;
+        END IF;
+    ELSIF OLD.deleted_at IS NULL THEN
+        UPDATE articles SET comments_count = comments_count - 1
+        WHERE article_id = OLD.article_id;
+    END IF;
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+DROP TRIGGER count_article_comments ON "comments";
+CREATE TRIGGER count_article_comments
+    AFTER INSERT OR DELETE OR UPDATE OF deleted_at ON "comments"
+    FOR EACH ROW
+    EXECUTE FUNCTION count_article_comments();
+
+-- once the last reply to a "deleted" placeholder is gone, there is no reason
+-- to keep the placeholder around; NB: this will in turn fire for the parent
+-- of the placeholder, and so on up the thread
+CREATE OR REPLACE FUNCTION collect_deleted_comments() RETURNS TRIGGER AS
+$$
+BEGIN
+    DELETE FROM comments
+    WHERE
+        comment_id = OLD.parent_comment_id AND
+        deleted_at IS NOT NULL AND
+        NOT EXISTS(SELECT 1 FROM comments WHERE parent_comment_id = OLD.parent_comment_id);
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER collect_deleted_comments
+    AFTER DELETE ON "comments"
+    FOR EACH ROW
+    WHEN (OLD.parent_comment_id IS NOT NULL)
+    EXECUTE FUNCTION collect_deleted_comments();
@@ -0,0 +1,12 @@
+-- prior versions of the comments' bodies, one row per edit
+CREATE TABLE IF NOT EXISTS "comment_edits" (
+    edit_id         UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    comment_id      UUID NOT NULL REFERENCES "comments" (comment_id) ON DELETE CASCADE,
+    body            TEXT NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ
+);
+
+CREATE INDEX comment_edits_comment_id_idx ON "comment_edits" (comment_id);
+
+SELECT put_creation_mutation_timestamps_guard_on('comment_edits');
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS comments_article_id_created_at_idx;
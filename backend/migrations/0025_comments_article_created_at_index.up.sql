@@ -0,0 +1,2 @@
+-- comments are listed per article, most often in chronological order
+CREATE INDEX comments_article_id_created_at_idx ON "comments" (article_id, created_at);
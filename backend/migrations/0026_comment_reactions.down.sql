@@ -0,0 +1,3 @@
+DROP FUNCTION IF EXISTS comment_user_reactions_json;
+DROP FUNCTION IF EXISTS comment_reactions_json;
+DROP TABLE IF EXISTS "comment_reactions";
@@ -0,0 +1,36 @@
+-- a user can react to a comment in a few different ways, but only once
+-- with each of the reactions
+CREATE TABLE IF NOT EXISTS "comment_reactions" (
+    comment_id      UUID NOT NULL REFERENCES "comments" (comment_id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    reaction        TEXT NOT NULL CHECK (
+        reaction IN ('upvote', 'heart', 'laugh', 'hooray', 'confused', 'eyes', 'rocket')
+    ),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ,
+
+    PRIMARY KEY (comment_id, user_id, reaction)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('comment_reactions');
+
+-- reaction counts of the comment (`$1`), e.g. `{"upvote": 2, "eyes": 1}`
+CREATE OR REPLACE FUNCTION comment_reactions_json(UUID) RETURNS JSONB AS
+$$
+    SELECT coalesce(jsonb_object_agg(reaction, reactions), '{}'::JSONB)
+    FROM (
+        SELECT reaction, count(*) AS reactions FROM comment_reactions
+        WHERE comment_id = $1
+        GROUP BY reaction
+    ) counts
+$$
+LANGUAGE sql STABLE;
+
+-- reactions of the user (`$2`, if any) to the comment (`$1`)
+CREATE OR REPLACE FUNCTION comment_user_reactions_json(UUID, UUID) RETURNS JSONB AS
+$$
+    SELECT coalesce(jsonb_agg(reaction ORDER BY reaction), '[]'::JSONB)
+    FROM comment_reactions
+    WHERE comment_id = $1 AND user_id = $2
+$$
+LANGUAGE sql STABLE;
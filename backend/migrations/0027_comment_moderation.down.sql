@@ -0,0 +1,8 @@
+DROP TRIGGER guard_creation_mutation_timestamps ON "comments";
+SELECT put_creation_mutation_timestamps_guard_on('comments');
+
+DROP INDEX IF EXISTS comments_pinned_idx;
+ALTER TABLE "comments"
+    DROP COLUMN hidden_at,
+    DROP COLUMN pinned_at;
+ALTER TABLE "articles" DROP COLUMN comments_locked;
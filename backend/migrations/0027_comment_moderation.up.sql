@@ -0,0 +1,22 @@
+-- the article's authors can close the discussion, ...
+ALTER TABLE "articles" ADD COLUMN comments_locked BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- ... hide comments from the readers, and pin a (top-level) comment
+ALTER TABLE "comments"
+    ADD COLUMN hidden_at TIMESTAMPTZ,
+    ADD COLUMN pinned_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX comments_pinned_idx ON "comments" (article_id) WHERE pinned_at IS NOT NULL;
+
+-- moderation is not the comment's modification, hence re-creating the guard
+-- (see migration 0002) to ignore changes to the moderation timestamps
+DROP TRIGGER guard_creation_mutation_timestamps ON "comments";
+CREATE TRIGGER guard_creation_mutation_timestamps
+    BEFORE UPDATE ON "comments"
+    FOR EACH ROW
+    WHEN (
+        (OLD.user_id, OLD.article_id, OLD.body, OLD.parent_comment_id, OLD.depth, OLD.deleted_at, OLD.created_at)
+        IS DISTINCT FROM
+        (NEW.user_id, NEW.article_id, NEW.body, NEW.parent_comment_id, NEW.depth, NEW.deleted_at, NEW.created_at)
+    )
+    EXECUTE FUNCTION guard_creation_mutation_timestamps();
@@ -0,0 +1,34 @@
+-- users mentioned in articles' (`comment_id IS NULL`) and comments' bodies
+CREATE TABLE IF NOT EXISTS "mentions" (
+    mention_id      UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id         UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    article_id      UUID NOT NULL REFERENCES "articles" (article_id) ON DELETE CASCADE,
+    comment_id      UUID REFERENCES "comments" (comment_id) ON DELETE CASCADE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX mentions_article_id_comment_id_user_id_idx
+    ON "mentions" (article_id, comment_id, user_id) NULLS NOT DISTINCT;
+CREATE INDEX mentions_user_id_idx ON "mentions" (user_id);
+
+SELECT put_creation_mutation_timestamps_guard_on('mentions');
+
+-- things that happened and the user (`user_id`) should be made aware of,
+-- caused by another user (`actor_id`)
+CREATE TABLE IF NOT EXISTS "notifications" (
+    notification_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id         UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    actor_id        UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    kind            TEXT NOT NULL CHECK (kind IN ('mention')),
+    article_id      UUID REFERENCES "articles" (article_id) ON DELETE CASCADE,
+    comment_id      UUID REFERENCES "comments" (comment_id) ON DELETE CASCADE,
+    read_at         TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ
+);
+
+CREATE INDEX notifications_user_id_created_at_idx
+    ON "notifications" (user_id, created_at DESC, notification_id DESC);
+
+SELECT put_creation_mutation_timestamps_guard_on('notifications');
@@ -0,0 +1,29 @@
+DROP TRIGGER IF EXISTS notify_on_comment ON "comments";
+DROP FUNCTION IF EXISTS notify_on_comment;
+DROP TRIGGER IF EXISTS notify_on_favorite ON "favorites";
+DROP FUNCTION IF EXISTS notify_on_favorite;
+DROP TRIGGER IF EXISTS notify_on_follow ON "follows";
+DROP FUNCTION IF EXISTS notify_on_follow;
+DROP FUNCTION IF EXISTS notify;
+
+DROP INDEX IF EXISTS notifications_unread_idx;
+DROP INDEX IF EXISTS notifications_user_id_notified_at_idx;
+CREATE INDEX notifications_user_id_created_at_idx
+    ON "notifications" (user_id, created_at DESC, notification_id DESC);
+
+-- aggregated notifications are attributed to their first actor
+ALTER TABLE "notifications" ADD COLUMN actor_id UUID REFERENCES "users" (user_id) ON DELETE CASCADE;
+UPDATE "notifications" notification SET actor_id = (
+    SELECT user_id FROM notification_actors
+    WHERE notification_id = notification.notification_id
+    ORDER BY created_at
+    LIMIT 1
+);
+DELETE FROM "notifications" WHERE kind <> 'mention' OR actor_id IS NULL;
+ALTER TABLE "notifications"
+    ALTER COLUMN actor_id SET NOT NULL,
+    DROP COLUMN notified_at;
+DROP TABLE IF EXISTS "notification_actors";
+
+ALTER TABLE "notifications" DROP CONSTRAINT notifications_kind_check;
+ALTER TABLE "notifications" ADD CONSTRAINT notifications_kind_check CHECK (kind IN ('mention'));
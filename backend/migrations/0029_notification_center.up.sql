@@ -0,0 +1,115 @@
+ALTER TABLE "notifications" DROP CONSTRAINT notifications_kind_check;
+ALTER TABLE "notifications" ADD CONSTRAINT notifications_kind_check CHECK (
+    kind IN ('follow', 'favorite', 'comment', 'reply', 'mention')
+);
+
+-- similar notifications are aggregated for as long as they are unread,
+-- e.g. "5 people favorited your article", and so a notification may have
+-- a few actors, and the time of the latest one is `notified_at`
+CREATE TABLE IF NOT EXISTS "notification_actors" (
+    notification_id UUID NOT NULL REFERENCES "notifications" (notification_id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ,
+
+    PRIMARY KEY (notification_id, user_id)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('notification_actors');
+
+INSERT INTO "notification_actors" (notification_id, user_id, created_at)
+SELECT notification_id, actor_id, created_at FROM "notifications";
+
+ALTER TABLE "notifications"
+    DROP COLUMN actor_id,
+    ADD COLUMN notified_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+UPDATE "notifications" SET notified_at = created_at;
+
+DROP INDEX notifications_user_id_created_at_idx;
+CREATE INDEX notifications_user_id_notified_at_idx
+    ON "notifications" (user_id, notified_at DESC, notification_id DESC);
+CREATE UNIQUE INDEX notifications_unread_idx
+    ON "notifications" (user_id, kind, article_id, comment_id) NULLS NOT DISTINCT
+    WHERE read_at IS NULL;
+
+-- notify user (`$1`) about the actor's (`$2`) action of the given kind (`$3`)
+-- regarding the article (`$4`) or comment (`$5`), if any, aggregating it with
+-- an unread notification of the same kind regarding the same thing; users are
+-- not notified about their own actions
+CREATE OR REPLACE FUNCTION notify(UUID, UUID, TEXT, UUID, UUID) RETURNS UUID AS
+$$
+    WITH
+        notification AS (
+            INSERT INTO notifications (user_id, kind, article_id, comment_id)
+            SELECT $1, $3, $4, $5 WHERE $1 <> $2
+            ON CONFLICT (user_id, kind, article_id, comment_id) WHERE read_at IS NULL
+            DO UPDATE SET notified_at = NOW()
+            RETURNING notification_id
+        ),
+        _actor AS (
+            INSERT INTO notification_actors (notification_id, user_id)
+            SELECT notification_id, $2 FROM notification
+            ON CONFLICT DO NOTHING
+        )
+    SELECT notification_id FROM notification
+$$
+LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION notify_on_follow() RETURNS TRIGGER AS
+$$
+BEGIN
+    PERFORM notify(NEW.followed_user_id, NEW.following_user_id, 'follow', NULL, NULL);
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_on_follow
+    AFTER INSERT ON "follows"
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_on_follow();
+
+CREATE OR REPLACE FUNCTION notify_on_favorite() RETURNS TRIGGER AS
+$$
+BEGIN
+    PERFORM notify(user_id, NEW.user_id, 'favorite', NEW.article_id, NULL)
+    FROM article_authors
+    WHERE article_id = NEW.article_id AND accepted_at IS NOT NULL;
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_on_favorite
+    AFTER INSERT ON "favorites"
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_on_favorite();
+
+-- the article's authors are notified about comments under the article, while
+-- the parent comment's author is notified about the reply (and only once,
+-- even if they are one of the article's authors)
+CREATE OR REPLACE FUNCTION notify_on_comment() RETURNS TRIGGER AS
+$$
+DECLARE
+    parent_author_id UUID;
+BEGIN
+    IF NEW.parent_comment_id IS NOT NULL THEN
+        SELECT user_id INTO parent_author_id
+        FROM comments WHERE comment_id = NEW.parent_comment_id;
+        PERFORM notify(parent_author_id, NEW.user_id, 'reply', NEW.article_id, NEW.parent_comment_id);
+    END IF;
+    PERFORM notify(user_id, NEW.user_id, 'comment', NEW.article_id, NULL)
+    FROM article_authors
+    WHERE
+        article_id = NEW.article_id AND
+        accepted_at IS NOT NULL AND
+        user_id IS DISTINCT FROM parent_author_id;
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_on_comment
+    AFTER INSERT ON "comments"
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_on_comment();
@@ -0,0 +1,3 @@
+DROP FUNCTION IF EXISTS email_delivery(UUID, TEXT);
+DROP TABLE IF EXISTS "email_digests";
+DROP TABLE IF EXISTS "email_preferences";
@@ -0,0 +1,40 @@
+-- how the user wants to be emailed about each kind of event: right away,
+-- in a daily or weekly digest, or not at all; besides the kinds of in-app
+-- notifications, there are new articles of the authors the user is following
+CREATE TABLE IF NOT EXISTS "email_preferences" (
+    user_id    UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    kind       TEXT NOT NULL CHECK (
+        kind IN ('follow', 'favorite', 'comment', 'reply', 'mention', 'article')
+    ),
+    delivery   TEXT NOT NULL CHECK (delivery IN ('immediate', 'daily', 'weekly', 'off')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ,
+
+    PRIMARY KEY (user_id, kind)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('email_preferences');
+
+-- when the latest digest of each frequency has been sent to the user
+CREATE TABLE IF NOT EXISTS "email_digests" (
+    user_id    UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    delivery   TEXT NOT NULL CHECK (delivery IN ('daily', 'weekly')),
+    sent_at    TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ,
+
+    PRIMARY KEY (user_id, delivery)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('email_digests');
+
+-- user's (`$1`) preferred delivery of emails of the given kind (`$2`), with
+-- everything going to the daily digest unless the user has said otherwise
+CREATE OR REPLACE FUNCTION email_delivery(UUID, TEXT) RETURNS TEXT AS
+$$
+    SELECT COALESCE(
+        (SELECT delivery FROM email_preferences WHERE user_id = $1 AND kind = $2),
+        'daily'
+    )
+$$
+LANGUAGE sql STABLE;
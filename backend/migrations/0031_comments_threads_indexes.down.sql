@@ -0,0 +1,4 @@
+DROP INDEX comments_parent_comment_id_idx;
+CREATE INDEX comments_parent_comment_id_idx ON "comments" (parent_comment_id);
+
+DROP INDEX comments_roots_idx;
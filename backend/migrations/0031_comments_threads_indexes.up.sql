@@ -0,0 +1,7 @@
+-- top-level comments are paginated per article by (created_at, comment_id),
+-- while replies are paginated per the comment they are replying to
+CREATE INDEX comments_roots_idx ON "comments" (article_id, created_at, comment_id)
+    WHERE parent_comment_id IS NULL;
+
+DROP INDEX comments_parent_comment_id_idx;
+CREATE INDEX comments_parent_comment_id_idx ON "comments" (parent_comment_id, created_at, comment_id);
@@ -0,0 +1,20 @@
+CREATE OR REPLACE FUNCTION notify(UUID, UUID, TEXT, UUID, UUID) RETURNS UUID AS
+$$
+    WITH
+        notification AS (
+            INSERT INTO notifications (user_id, kind, article_id, comment_id)
+            SELECT $1, $3, $4, $5 WHERE $1 <> $2
+            ON CONFLICT (user_id, kind, article_id, comment_id) WHERE read_at IS NULL
+            DO UPDATE SET notified_at = NOW()
+            RETURNING notification_id
+        ),
+        _actor AS (
+            INSERT INTO notification_actors (notification_id, user_id)
+            SELECT notification_id, $2 FROM notification
+            ON CONFLICT DO NOTHING
+        )
+    SELECT notification_id FROM notification
+$$
+LANGUAGE sql;
+
+DROP TABLE IF EXISTS "notification_deliveries";
@@ -0,0 +1,47 @@
+-- notifications which have got a new actor and are yet to be delivered (i.e.
+-- streamed and emailed) to their recipients, see `notifications::publish`;
+-- these are claimed by the actor's request once its action is committed
+CREATE TABLE IF NOT EXISTS "notification_deliveries" (
+    notification_id UUID NOT NULL REFERENCES "notifications" (notification_id) ON DELETE CASCADE,
+    actor_id        UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (notification_id, actor_id)
+);
+
+CREATE INDEX notification_deliveries_actor_id_idx ON "notification_deliveries" (actor_id);
+
+-- same as before, but an actor repeating their action (e.g. following the user
+-- again after unfollowing them) neither bumps the notification nor gets it
+-- delivered once more, and so the notification's ID is only returned (and
+-- the delivery is only queued) if the actor is new to the notification
+CREATE OR REPLACE FUNCTION notify(UUID, UUID, TEXT, UUID, UUID) RETURNS UUID AS
+$$
+DECLARE
+    notified_id UUID;
+BEGIN
+    IF $1 IS NULL OR $1 = $2 THEN
+        RETURN NULL;
+    END IF;
+    INSERT INTO notifications (user_id, kind, article_id, comment_id)
+    VALUES ($1, $3, $4, $5)
+    ON CONFLICT (user_id, kind, article_id, comment_id) WHERE read_at IS NULL
+    -- a no-op, so that the existing notification is returned
+    DO UPDATE SET user_id = EXCLUDED.user_id
+    RETURNING notification_id INTO notified_id;
+
+    INSERT INTO notification_actors (notification_id, user_id)
+    VALUES (notified_id, $2)
+    ON CONFLICT DO NOTHING;
+    IF NOT FOUND THEN
+        RETURN NULL;
+    END IF;
+
+    UPDATE notifications SET notified_at = NOW() WHERE notification_id = notified_id;
+    INSERT INTO notification_deliveries (notification_id, actor_id)
+    VALUES (notified_id, $2)
+    ON CONFLICT DO NOTHING;
+    RETURN notified_id;
+END
+$$
+LANGUAGE plpgsql;
@@ -0,0 +1,8 @@
+CREATE OR REPLACE FUNCTION email_delivery(UUID, TEXT) RETURNS TEXT AS
+$$
+    SELECT COALESCE(
+        (SELECT delivery FROM email_preferences WHERE user_id = $1 AND kind = $2),
+        'daily'
+    )
+$$
+LANGUAGE sql STABLE;
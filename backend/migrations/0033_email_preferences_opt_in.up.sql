@@ -0,0 +1,12 @@
+-- users have not been asked about their email preferences (neither the ones
+-- who were there before the preferences were introduced, nor the ones signing
+-- up now), and so they are not emailed about a topic until they opt in to it;
+-- the choices made so far are kept, and so there is nothing to backfill
+CREATE OR REPLACE FUNCTION email_delivery(UUID, TEXT) RETURNS TEXT AS
+$$
+    SELECT COALESCE(
+        (SELECT delivery FROM email_preferences WHERE user_id = $1 AND kind = $2),
+        'off'
+    )
+$$
+LANGUAGE sql STABLE;
@@ -0,0 +1,3 @@
+DROP FUNCTION IF EXISTS listed_articles(
+    TEXT[], TEXT[], BOOLEAN, TEXT, TEXT, TEXT[], TEXT[], TIMESTAMPTZ, TIMESTAMPTZ, UUID, TEXT
+);
@@ -0,0 +1,66 @@
+-- articles matching the filters of the articles lists (see `ListQuery`), which
+-- all of the lists, their counts, and the search are selecting from, so that
+-- the filters are only ever defined here; if `feed_of` is provided, only
+-- the articles in that user's personal feed (see `FeedMode`) are matching;
+--
+-- the function is a single `SELECT` in SQL and is not `STRICT`, so that
+-- the planner inlines it into the calling query and can still seek the
+-- indexes when ordering and paginating the matching articles
+CREATE OR REPLACE FUNCTION listed_articles(
+    by_authors      TEXT[],
+    with_tags       TEXT[],
+    any_tag         BOOLEAN,
+    favorited_by    TEXT,
+    in_series       TEXT,
+    without_tags    TEXT[],
+    without_authors TEXT[],
+    published_since TIMESTAMPTZ,
+    published_until TIMESTAMPTZ,
+    feed_of         UUID,
+    feed_mode       TEXT
+) RETURNS SETOF "articles" AS
+$$
+    SELECT article.*
+    FROM "articles" article JOIN "users" author USING (user_id)
+    WHERE
+        (
+            feed_of IS NULL OR
+            (
+                feed_mode <> 'tags' AND
+                EXISTS(
+                    SELECT 1 FROM follows
+                    WHERE followed_user_id = article.user_id AND following_user_id = feed_of
+                )
+            ) OR
+            (
+                feed_mode <> 'authors' AND
+                article.user_id <> feed_of AND
+                article.tags && ARRAY(SELECT tag FROM tag_follows WHERE user_id = feed_of)
+            )
+        ) AND
+        (cardinality(by_authors) = 0 OR author.username = ANY(by_authors)) AND
+        (
+            cardinality(with_tags) = 0 OR
+            (NOT any_tag AND article.tags @> with_tags) OR
+            (any_tag AND article.tags && with_tags)
+        ) AND
+        (
+            favorited_by IS NULL OR
+            EXISTS(
+                SELECT 1 FROM favorites fav JOIN users USING (user_id)
+                WHERE fav.article_id = article.article_id AND username = favorited_by
+            )
+        ) AND
+        (
+            in_series IS NULL OR
+            EXISTS(
+                SELECT 1 FROM series_articles JOIN series USING (series_id)
+                WHERE article_id = article.article_id AND series.slug = in_series
+            )
+        ) AND
+        NOT article.tags && without_tags AND
+        author.username <> ALL(without_authors) AND
+        (published_since IS NULL OR article.created_at >= published_since) AND
+        (published_until IS NULL OR article.created_at < published_until)
+$$
+LANGUAGE sql STABLE;
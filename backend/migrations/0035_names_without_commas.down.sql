@@ -0,0 +1,4 @@
+-- the rewritten usernames and tags are not restored
+ALTER TABLE "users" DROP CONSTRAINT IF EXISTS users_username_check;
+ALTER TABLE "tag_aliases" DROP CONSTRAINT IF EXISTS tag_aliases_alias_check;
+ALTER TABLE "tags" DROP CONSTRAINT IF EXISTS tags_name_check;
@@ -0,0 +1,67 @@
+-- usernames and tags are matched by the comma-separated filters of the articles
+-- lists (see `ListQuery`), and so those cannot contain commas; the existing ones
+-- that do are rewritten with the commas (and the whitespace around them) replaced
+-- with dashes, e.g. "rust, go" becomes "rust-go", and the constraints below
+-- make sure no commas make it to the database from now on
+
+-- a rewritten tag might already exist, in which case the two are merged
+CREATE TEMPORARY TABLE renamed_tags ON COMMIT DROP AS
+SELECT name AS old_name, regexp_replace(name, '\s*,\s*', '-', 'g') AS new_name
+FROM "tags"
+WHERE strpos(name, ',') > 0;
+
+INSERT INTO "tags" (name, description, created_at)
+SELECT new_name, description, created_at
+FROM "tags" JOIN renamed_tags ON name = old_name
+ON CONFLICT DO NOTHING;
+
+-- re-tagging is not an edit of the articles' contents, see migration 0020
+ALTER TABLE "articles" DISABLE TRIGGER guard_creation_mutation_timestamps;
+
+UPDATE "articles" SET tags = ARRAY(
+    SELECT DISTINCT coalesce(new_name, tag)
+    FROM unnest(tags) tag LEFT JOIN renamed_tags ON tag = old_name
+    ORDER BY 1
+)
+WHERE tags && ARRAY(SELECT old_name FROM renamed_tags);
+
+ALTER TABLE "articles" ENABLE TRIGGER guard_creation_mutation_timestamps;
+
+INSERT INTO "tag_follows" (user_id, tag, created_at)
+SELECT user_id, new_name, created_at
+FROM "tag_follows" JOIN renamed_tags ON tag = old_name
+ON CONFLICT DO NOTHING;
+
+UPDATE "tag_aliases" SET tag = new_name
+FROM renamed_tags
+WHERE tag = old_name;
+
+-- aliases are only ever looked up by the filters and so the ones with commas
+-- have never been reachable anyway
+DELETE FROM "tag_aliases" WHERE strpos(alias, ',') > 0;
+
+-- the follows of the old tags are gone with those
+DELETE FROM "tags" WHERE name IN (SELECT old_name FROM renamed_tags);
+
+-- a rewritten username might already be taken, in which case the beginning
+-- of the user's ID is appended to it
+WITH renamed_users AS (
+    SELECT
+        user_id,
+        regexp_replace(username COLLATE "C", '\s*,\s*', '-', 'g') COLLATE case_insensitive AS username
+    FROM "users"
+    WHERE strpos(username COLLATE "C", ',') > 0
+)
+UPDATE "users" SET username = CASE
+    WHEN
+        EXISTS(SELECT 1 FROM "users" taken WHERE taken.username = renamed.username) OR
+        (SELECT count(*) FROM renamed_users other WHERE other.username = renamed.username) > 1
+    THEN renamed.username || '-' || left(renamed.user_id::TEXT, 8)
+    ELSE renamed.username
+END
+FROM renamed_users renamed
+WHERE users.user_id = renamed.user_id;
+
+ALTER TABLE "tags" ADD CONSTRAINT tags_name_check CHECK (strpos(name, ',') = 0);
+ALTER TABLE "tag_aliases" ADD CONSTRAINT tag_aliases_alias_check CHECK (strpos(alias, ',') = 0);
+ALTER TABLE "users" ADD CONSTRAINT users_username_check CHECK (strpos(username COLLATE "C", ',') = 0);
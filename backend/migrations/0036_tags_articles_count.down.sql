@@ -0,0 +1,22 @@
+DROP TRIGGER register_article_tags ON "articles";
+
+CREATE OR REPLACE FUNCTION register_article_tags() RETURNS TRIGGER AS
+$$
+BEGIN
+    INSERT INTO tags (name) SELECT unnest(NEW.tags) ON CONFLICT DO NOTHING;
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER register_article_tags
+    AFTER INSERT OR UPDATE OF tags ON "articles"
+    FOR EACH ROW
+    EXECUTE FUNCTION register_article_tags();
+
+DROP INDEX tags_articles_count_idx;
+
+DROP TRIGGER guard_creation_mutation_timestamps ON "tags";
+SELECT put_creation_mutation_timestamps_guard_on('tags');
+
+ALTER TABLE "tags" DROP COLUMN articles_count;
@@ -0,0 +1,56 @@
+ALTER TABLE "tags" ADD COLUMN articles_count INTEGER NOT NULL DEFAULT 0;
+
+-- the counter is maintained by the trigger below and changes to it should not
+-- be considered the tag's modification, see migration 0016 for the articles
+DROP TRIGGER guard_creation_mutation_timestamps ON "tags";
+CREATE TRIGGER guard_creation_mutation_timestamps
+    BEFORE UPDATE ON "tags"
+    FOR EACH ROW
+    WHEN (
+        (OLD.name, OLD.description, OLD.created_at)
+        IS DISTINCT FROM
+        (NEW.name, NEW.description, NEW.created_at)
+    )
+    EXECUTE FUNCTION guard_creation_mutation_timestamps();
+
+UPDATE "tags" SET articles_count = usage.articles_count
+FROM (
+    SELECT tag, count(DISTINCT article_id) AS articles_count
+    FROM "articles", unnest(tags) tag
+    GROUP BY tag
+) usage
+WHERE name = usage.tag;
+
+-- the most popular tags are listed first, see `list_tags`
+CREATE INDEX tags_articles_count_idx ON "tags" (articles_count DESC, name);
+
+-- same as before, but also counting the articles having the tag, i.e. for the
+-- tags the article has got (or has lost) the counter goes up (or down)
+CREATE OR REPLACE FUNCTION register_article_tags() RETURNS TRIGGER AS
+$$
+BEGIN
+    IF TG_OP <> 'DELETE' THEN
+        INSERT INTO tags (name) SELECT unnest(NEW.tags) ON CONFLICT DO NOTHING;
+    END IF;
+    UPDATE tags SET articles_count = articles_count + change.delta
+    FROM (
+        SELECT tag, sum(delta) AS delta
+        FROM (
+            SELECT DISTINCT unnest(NEW.tags), 1
+            UNION ALL
+            SELECT DISTINCT unnest(OLD.tags), -1
+        ) changes (tag, delta)
+        GROUP BY tag
+        HAVING sum(delta) <> 0
+    ) change
+    WHERE name = change.tag;
+    RETURN NULL;
+END
+$$
+LANGUAGE plpgsql;
+
+DROP TRIGGER register_article_tags ON "articles";
+CREATE TRIGGER register_article_tags
+    AFTER INSERT OR UPDATE OF tags OR DELETE ON "articles"
+    FOR EACH ROW
+    EXECUTE FUNCTION register_article_tags();
@@ -0,0 +1,14 @@
+-- users who do not want to hear from certain other users; for now, this is
+-- only about not being notified when mentioned by them
+CREATE TABLE IF NOT EXISTS "blocks" (
+    blocking_user_id UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    blocked_user_id  UUID NOT NULL REFERENCES "users" (user_id) ON DELETE CASCADE,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at       TIMESTAMPTZ,
+
+    CONSTRAINT "blocks_no_self_block" CHECK (blocked_user_id != blocking_user_id),
+
+    PRIMARY KEY (blocking_user_id, blocked_user_id)
+);
+
+SELECT put_creation_mutation_timestamps_guard_on('blocks');
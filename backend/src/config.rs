@@ -28,11 +28,19 @@ pub struct Config {
     pub temporal_url: Option<Url>,
     pub migrate: Option<bool>,
     pub frontend_url: Url,
+
+    /// Public URL of this API, which we are embedding into the emails
+    /// (e.g. the unsubscribe links) where those should lead to the back-end.
+    pub backend_url: Url,
     pub allowed_origins: Vec<String>,
     pub ip: IpAddr,
     pub port: u16,
     pub docs_ui_path: Option<String>,
 
+    /// How often (in seconds) article views buffered in Redis are written
+    /// to the database. Defaults to 30 seconds.
+    pub analytics_flush_interval: Option<u64>,
+
     /// Skip email and/or captcha verification logic and/or content moderation.
     ///
     /// This is something we only need to satisfy Realdworld project's
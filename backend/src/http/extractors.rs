@@ -1,18 +1,34 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
 use std::ops::Deref;
 use std::sync::Arc;
 
 use crate::AppContext;
 use crate::http::errors::Error;
-use crate::http::jwt::verify_token;
-use axum::extract::{FromRef, FromRequestParts};
+use crate::http::utils::forwarded_for;
+use axum::extract::{ConnectInfo, FromRef, FromRequestParts};
 use axum::http::request::Parts;
 use uuid::Uuid;
 
+/// Subprotocol to be followed by the token, when authenticating a WebSocket
+/// connection, see [`SocketUserID`].
+pub(in crate::http) const ACCESS_TOKEN_PROTOCOL: &str = "access_token";
+
 #[derive(Debug)]
 pub(in crate::http) struct UserID(pub Uuid);
 #[derive(Debug)]
 pub(in crate::http) struct MaybeUserID(pub Option<UserID>);
 
+/// User authenticated to open a WebSocket connection.
+///
+/// Browsers' WebSocket API cannot set the `Authorization` header, and so the
+/// token can instead be offered as a subprotocol following the `access_token`
+/// one, i.e. `new WebSocket(url, ["access_token", token])`. The handler should
+/// then accept [`ACCESS_TOKEN_PROTOCOL`], since browsers are failing
+/// the connection otherwise.
+#[derive(Debug)]
+pub(in crate::http) struct SocketUserID(pub Uuid);
+
 impl Deref for UserID {
     type Target = Uuid;
     fn deref(&self) -> &Self::Target {
@@ -33,12 +49,25 @@ where
             return Err(Error::Unauthorized);
         };
         let ctx = Arc::<AppContext>::from_ref(state);
-        verify_token(token, &ctx.dec_key)
-            .map_err(|e| {
-                warn!("Authentication failed: {}", e);
-                Error::Unauthorized
-            })
-            .map(UserID)
+        utils::authenticate(&ctx, token)
+    }
+}
+
+impl<S> FromRequestParts<S> for SocketUserID
+where
+    Arc<AppContext>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Some(token) = utils::maybe_token(&parts.headers)
+            .or_else(|| utils::maybe_protocol_token(&parts.headers))
+        else {
+            return Err(Error::Unauthorized);
+        };
+        let ctx = Arc::<AppContext>::from_ref(state);
+        utils::authenticate(&ctx, token).map(|UserID(uid)| Self(uid))
     }
 }
 
@@ -53,19 +82,65 @@ where
             return Ok(Self(None));
         };
         let ctx = Arc::<AppContext>::from_ref(state);
-        let uid = verify_token(token, &ctx.dec_key)
-            .map_err(|e| {
-                warn!("Authentication failed: {}", e);
-                Error::Unauthorized
-            })
-            .map(UserID)?;
+        let uid = utils::authenticate(&ctx, token)?;
         Ok(Self(Some(uid)))
     }
 }
 
+/// Address of the client the request is coming from.
+///
+/// This is the address forwarded by the reverse proxy (see
+/// [`forwarded_for`]) or - if there is no proxy in front of the app,
+/// e.g. when developing locally - the peer's address.
+#[derive(Debug, Clone, Copy)]
+pub(in crate::http) struct ClientIp(pub Option<IpAddr>);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let ip = forwarded_for(&parts.headers)
+            .and_then(|ip| ip.parse().ok())
+            .or_else(|| {
+                parts
+                    .extensions
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|ConnectInfo(addr)| addr.ip())
+            });
+        Ok(Self(ip))
+    }
+}
+
 mod utils {
+    use super::{ACCESS_TOKEN_PROTOCOL, UserID};
+    use crate::AppContext;
+    use crate::http::errors::Error;
+    use crate::http::jwt::verify_token;
     use axum::http::HeaderMap;
 
+    pub fn authenticate(ctx: &AppContext, token: &str) -> Result<UserID, Error> {
+        verify_token(token, &ctx.dec_key)
+            .map_err(|e| {
+                warn!("Authentication failed: {}", e);
+                Error::Unauthorized
+            })
+            .map(UserID)
+    }
+
+    /// Token offered as a WebSocket subprotocol, see [`super::SocketUserID`].
+    pub fn maybe_protocol_token(headers: &HeaderMap) -> Option<&str> {
+        let mut protocols = headers
+            .get("Sec-WebSocket-Protocol")
+            .and_then(|header| header.to_str().ok())?
+            .split(',')
+            .map(str::trim);
+        protocols.find(|protocol| *protocol == ACCESS_TOKEN_PROTOCOL)?;
+        protocols.next()
+    }
+
     pub fn maybe_token(headers: &HeaderMap) -> Option<&str> {
         headers
             .get("Authorization")
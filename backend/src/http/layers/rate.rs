@@ -1,3 +1,4 @@
+use crate::http::utils::forwarded_for;
 use axum::body::Body;
 use axum::http::{Method, Request, StatusCode, header};
 use axum::response::AppendHeaders;
@@ -5,7 +6,6 @@ use axum::response::IntoResponse as _;
 use axum::response::Response;
 use deadpool_redis::Pool as RedisPool;
 use tower_redis_cell::Error as RateLimitError;
-use tower_redis_cell::ProvideRuleError;
 use tower_redis_cell::RateLimitConfig;
 use tower_redis_cell::deadpool::RateLimitLayer;
 use tower_redis_cell::redis_cell::{Key, Policy};
@@ -34,9 +34,9 @@ impl<T> ProvideRule<Request<T>> for RuleProvider {
 
         let (path, method) = (req.uri().path(), req.method());
 
-        // the app is sitting behind `Kamal-Proxy` which sets 'x-forwarded-for' for us:
-        // https://kamal-deploy.org/docs/configuration/proxy/#forward-headers
-        let ip = match req.headers().get("x-forwarded-for") {
+        // the app is sitting behind `Kamal-Proxy` which sets 'x-forwarded-for' for us,
+        // see `forwarded_for` on which address we can trust
+        let ip = match forwarded_for(req.headers()) {
             None => {
                 // we are developing locally w/o reverse-proxy
                 if cfg!(debug_assertions) {
@@ -50,9 +50,7 @@ impl<T> ProvideRule<Request<T>> for RuleProvider {
                     return Err("'x-forwarded-for' header is missing".into());
                 }
             }
-            Some(ip_header) => ip_header
-                .to_str()
-                .map_err(|e| ProvideRuleError::default().detail(e.to_string()))?,
+            Some(ip) => ip,
         };
 
         // writing and updating articles is a fairly expensive operation due to
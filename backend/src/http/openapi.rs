@@ -30,7 +30,9 @@ impl Modify for SecurityAddon {
       (name = "Users", description = "User management endpoints."),
       (name = "Profiles", description = "Profiles endpoints."),
       (name = "Articles", description = "Articles and feed endpoints."),
+      (name = "Series", description = "Article series endpoints."),
       (name = "Tags", description = "Content tags endpoints."),
+      (name = "Stream", description = "Real-time events endpoints."),
     ),
     modifiers(&SecurityAddon)
     )]
@@ -0,0 +1,344 @@
+use super::crud::db;
+use super::{Article, ArticleLink, ArticlePayload, AuthorRole};
+use crate::http::errors::{Error, Validation};
+use crate::http::extractors::UserID;
+use crate::state::AppContext;
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use utoipa::ToSchema;
+use validator::Validate;
+use validator_derive::Validate;
+
+/// Invitation to co-author an article.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Invitation {
+    /// Article the user is invited to co-author.
+    article: ArticleLink,
+
+    /// Invited user's name.
+    #[schema(example = "rob.pike1984")]
+    username: String,
+
+    /// Name of the user who sent the invitation (the article's owner).
+    #[schema(example = "timClicks")]
+    invited_by: String,
+
+    /// Role the user is invited for.
+    role: AuthorRole,
+
+    /// When the invitation was sent.
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub(crate) struct InvitationPayload<U> {
+    invitation: U,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct InvitationsList {
+    /// List of pending invitations, the most recent ones first.
+    invitations: Vec<Invitation>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct InvitationCreate {
+    /// Name of the user to invite.
+    #[schema(example = "rob.pike1984", min_length = 1)]
+    #[validate(length(min = 1, message = "username should be at least 1 character long"))]
+    username: String,
+}
+
+/// Invite co-author.
+///
+/// Invite a user to co-author the article as an editor. Once the user accepts
+/// the invitation (see `accept_invitation`), they will be listed among the
+/// article's `authors` and will be able to update (but not delete) the article.
+///
+/// Only the article's owner can invite co-authors.
+#[utoipa::path(
+    post,
+    path = "/{slug}/authors",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "how-to-design-a-programming-language"
+        ),
+    ),
+    responses(
+        (status = 201, description = "Invitation successfully sent", body = InvitationPayload<Invitation>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 403, description = "User is not the owner of this article."),
+        (status = 404, description = "Article not found"),
+        (status = 422, description = "Missing or invalid invitation attributes", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "INVITE AUTHOR", skip(ctx, input))]
+pub async fn invite_author(
+    ctx: State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    uid: UserID,
+    input: Result<Json<InvitationPayload<InvitationCreate>>, JsonRejection>,
+) -> Result<(StatusCode, Json<InvitationPayload<Invitation>>), Error> {
+    let InvitationPayload { invitation } = input?.0;
+    invitation.validate()?;
+    let article = sqlx::query!(
+        r#"
+        SELECT article.article_id, article.user_id, article.slug, article.title, owner.username
+        FROM articles article JOIN users owner USING (user_id)
+        WHERE slug = $1
+        "#,
+        slug
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+    if article.user_id != *uid {
+        warn!("user tried to invite co-author w/o proper permissions");
+        return Err(Error::Forbidden);
+    }
+
+    let details = sqlx::query!(
+        r#"
+        WITH
+            invitee AS (SELECT user_id, username FROM users WHERE username = $2),
+            invited AS (
+                INSERT INTO article_authors (article_id, user_id, role)
+                SELECT $1, user_id, $3 FROM invitee
+                ON CONFLICT DO NOTHING
+                RETURNING created_at
+            )
+        SELECT
+            (SELECT username FROM invitee) AS "username?",
+            (SELECT created_at FROM invited) AS "created_at?"
+        "#,
+        article.article_id,
+        invitation.username,
+        AuthorRole::Editor.as_str(),
+    )
+    .fetch_one(&ctx.db)
+    .await?;
+    let Some(username) = details.username else {
+        return Err(Error::unprocessable_entity([(
+            "username",
+            "user not found",
+        )]));
+    };
+    let Some(created_at) = details.created_at else {
+        return Err(Error::unprocessable_entity([(
+            "username",
+            "user is already an author of this article or has been invited",
+        )]));
+    };
+
+    let invitation = Invitation {
+        article: ArticleLink {
+            slug: article.slug,
+            title: article.title,
+        },
+        username,
+        invited_by: article.username,
+        role: AuthorRole::Editor,
+        created_at,
+    };
+    Ok((StatusCode::CREATED, Json(InvitationPayload { invitation })))
+}
+
+/// Accept invitation.
+///
+/// Accept the invitation to co-author the article, see `invite_author`.
+/// This operation is idempotent: if the invitation has already been accepted,
+/// a successful response will be returned.
+#[utoipa::path(
+    post,
+    path = "/{slug}/authors/accept",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "how-to-design-a-programming-language"
+        ),
+    ),
+    responses(
+        (status = 200, description = "Invitation successfully accepted", body = ArticlePayload<Article>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 404, description = "Article or invitation not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "ACCEPT INVITATION", skip(ctx))]
+pub async fn accept_invitation(
+    ctx: State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    uid: UserID,
+) -> Result<Json<ArticlePayload<Article>>, Error> {
+    let _article_id = sqlx::query_scalar!(
+        r#"
+        UPDATE article_authors
+        SET accepted_at = coalesce(accepted_at, NOW())
+        WHERE
+            article_id = (SELECT article_id FROM articles WHERE slug = $1) AND
+            user_id = $2
+        RETURNING article_id
+        "#,
+        slug,
+        *uid
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+    let (_, article) = db::read_article(&ctx, &slug, Some(&uid)).await?;
+    Ok(Json(ArticlePayload { article }))
+}
+
+/// Remove co-author.
+///
+/// Remove an editor from the article's authors (or revoke a pending invitation).
+/// The article's owner can remove any editor, while editors and invited users
+/// can only remove themselves (i.e. step down or decline the invitation).
+/// The owner themselves cannot be removed.
+#[utoipa::path(
+    delete,
+    path = "/{slug}/authors/{username}",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "how-to-design-a-programming-language"
+        ),
+        (
+            "username" = String, Path,
+            description = "Co-author's name.",
+            example = "rob.pike1984"
+        ),
+    ),
+    responses(
+        (status = 204, description = "Co-author successfully removed."),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 403, description = "User does not have permissions to remove this co-author."),
+        (status = 404, description = "Article or co-author not found"),
+        (status = 422, description = "Article's owner cannot be removed", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "REMOVE AUTHOR", skip(ctx))]
+pub async fn remove_author(
+    ctx: State<Arc<AppContext>>,
+    Path((slug, username)): Path<(String, String)>,
+    uid: UserID,
+) -> Result<StatusCode, Error> {
+    let details = sqlx::query!(
+        r#"
+        SELECT
+            article.article_id,
+            article.user_id AS owner_id,
+            coauthor.user_id AS "coauthor_id?",
+            coauthor.role AS "role?"
+        FROM
+            articles article
+                LEFT JOIN users target ON target.username = $2
+                LEFT JOIN article_authors coauthor
+                    ON coauthor.article_id = article.article_id AND coauthor.user_id = target.user_id
+        WHERE article.slug = $1
+        "#,
+        slug,
+        username,
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+    let (Some(coauthor_id), Some(role)) = (details.coauthor_id, details.role) else {
+        return Err(Error::NotFound);
+    };
+    if details.owner_id != *uid && coauthor_id != *uid {
+        warn!("user tried to remove co-author w/o proper permissions");
+        return Err(Error::Forbidden);
+    }
+    if role == AuthorRole::Owner.as_str() {
+        return Err(Error::unprocessable_entity([(
+            "username",
+            "article's owner cannot be removed",
+        )]));
+    }
+    sqlx::query!(
+        "DELETE FROM article_authors WHERE article_id = $1 AND user_id = $2",
+        details.article_id,
+        coauthor_id,
+    )
+    .execute(&ctx.db)
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List invitations.
+///
+/// Pending invitations to co-author articles sent to the current user.
+#[utoipa::path(
+    get,
+    path = "/invitations",
+    tags = ["Users"],
+    responses(
+        (status = 200, description = "Invitations list successfully retrieved", body = InvitationsList),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "LIST INVITATIONS", skip_all)]
+pub async fn list_invitations(
+    ctx: State<Arc<AppContext>>,
+    uid: UserID,
+) -> Result<Json<InvitationsList>, Error> {
+    let invitations = sqlx::query!(
+        r#"
+        SELECT
+            article.slug,
+            article.title,
+            invitee.username,
+            owner.username AS invited_by,
+            coauthor.created_at
+        FROM
+            article_authors coauthor
+                JOIN articles article USING (article_id)
+                JOIN users invitee ON invitee.user_id = coauthor.user_id
+                JOIN users owner ON owner.user_id = article.user_id
+        WHERE coauthor.user_id = $1 AND coauthor.accepted_at IS NULL
+        ORDER BY coauthor.created_at DESC
+        "#,
+        *uid
+    )
+    .fetch_all(&ctx.db)
+    .await?;
+    Ok(Json(InvitationsList {
+        invitations: invitations
+            .into_iter()
+            .map(|row| Invitation {
+                article: ArticleLink {
+                    slug: row.slug,
+                    title: row.title,
+                },
+                username: row.username,
+                invited_by: row.invited_by,
+                // owners do not need to be invited
+                role: AuthorRole::Editor,
+                created_at: row.created_at,
+            })
+            .collect(),
+    }))
+}
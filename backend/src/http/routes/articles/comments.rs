@@ -1,17 +1,34 @@
-use super::Author;
+use super::{Author, mentions};
 use crate::http::errors::{Error, ResultExt as _, Validation};
 use crate::http::extractors::{MaybeUserID, UserID};
+use crate::http::routes::users::notifications;
 use crate::http::routes::users::utils::parse_image_url;
 use crate::http::utils;
+use crate::services::events::Topic;
 use crate::state::AppContext;
-use axum::extract::{Json, Path, State};
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Json, Path, Query, State};
 use chrono::{DateTime, Utc};
 use reqwest::StatusCode;
+use sqlx::types::Json as SqlxJson;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::time::Duration;
+use utoipa::IntoParams;
 use uuid::Uuid;
 use validator::Validate;
 use validator_derive::Validate;
 
+/// For how long after the comment's creation its author can edit it.
+const EDIT_WINDOW: Duration = Duration::from_secs(60 * 15);
+
+const DEFAULT_LIMIT: usize = 20;
+
+/// How many replies are listed at most per thread.
+///
+/// The rest of the replies can be listed with `parentId`, see `list_comments`.
+const MAX_REPLIES: usize = 50;
+
 // ----------------------------- CREATE COMMENT -------------------------------
 /// Container for comment creation endpoint.
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -19,7 +36,18 @@ pub(crate) struct CommentPayload<U> {
     comment: U,
 }
 
+/// Payload of the `comment` event, see `stream_events`.
+#[derive(Debug, Serialize)]
+struct CommentEvent<'a> {
+    /// Article's slug.
+    article: &'a str,
+
+    /// The new comment.
+    comment: &'a Comment,
+}
+
 #[derive(Debug, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct CommentCreate {
     /// Comment's text.
     #[schema(
@@ -33,6 +61,13 @@ pub(crate) struct CommentCreate {
         message = "comment should be at least 1 and at max 500 characters long"
     ))]
     body: String,
+
+    /// Identifier of the comment to reply to.
+    ///
+    /// The comment should belong to the same article. Top-level comment
+    /// will be created if not provided.
+    #[schema(examples("123e4567-e89b-12d3-a456-426614174000"))]
+    parent_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -47,15 +82,76 @@ pub(crate) struct Comment {
     /// When this comment was last update.
     updated_at: DateTime<Utc>,
 
+    /// Whether this comment has been edited by its author.
+    edited: bool,
+
     /// Comment's text.
+    ///
+    /// Empty for deleted (and hidden) comments.
     body: String,
 
     /// Details of the comment's author
-    author: Author,
+    ///
+    /// Not provided for deleted comments.
+    author: Option<Author>,
+
+    /// Whether the article's authors pinned this comment.
+    ///
+    /// The pinned comment is listed before any other comments.
+    pinned: bool,
+
+    /// Whether the article's authors hid this comment.
+    ///
+    /// The hidden comment's text and author are only provided to the article's
+    /// authors and the comment's author, just like with deleted comments.
+    hidden: bool,
+
+    /// Identifier of the comment this one is replying to.
+    ///
+    /// Not provided for top-level comments.
+    parent_id: Option<Uuid>,
+
+    /// How deep in the thread this comment is, `0` for top-level comments.
+    depth: u32,
+
+    /// Whether this comment has been deleted.
+    ///
+    /// A deleted comment having replies is kept as a placeholder, so that
+    /// the thread stays intact.
+    deleted: bool,
+
+    /// How many direct replies this comment has got (not counting the deleted
+    /// ones, even if these are still in the thread as placeholders).
+    ///
+    /// Note that some of the replies might not be listed due to `maxDepth`,
+    /// or since there are too many of them (see `parentId`).
+    replies_count: u64,
+
+    /// How many times each of the reactions has been given to this comment.
+    ///
+    /// Reactions not given to this comment are omitted.
+    #[schema(example = json!({"upvote": 2, "eyes": 1}))]
+    reactions: BTreeMap<Reaction, u64>,
+
+    /// Reactions the current user has given to this comment.
+    ///
+    /// Always empty for unauthenticated requests.
+    my_reactions: Vec<Reaction>,
+
+    /// Replies to this comment, oldest first.
+    ///
+    /// Only provided for `nested` threads.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(no_recursion)]
+    replies: Option<Vec<Comment>>,
 }
 
 /// Add comment to article.
 ///
+/// The comment can be a reply to another comment under the same article,
+/// see `parentId`. Only the article's authors can comment if the article's
+/// comments are locked, see `commentsLocked`.
+///
 /// Authentication required.
 #[utoipa::path(
     post,
@@ -72,9 +168,10 @@ pub(crate) struct Comment {
     responses(
         (status = 200, description = "Comment successfully created", body = CommentPayload<Comment>),
         (status = 401, description = "Token missing or invalid."),
+        (status = 403, description = "Article's comments are locked"),
         (status = 404, description = "Article not found"),
         (status = 415, description = "Method not allow / Content-Type is incorrect"),
-        (status = 422, description = "Missing or invalid comment attributes", body = Validation),
+        (status = 422, description = "Missing or invalid comment attributes (incl. unknown parent comment)", body = Validation),
         (status = 500, description = "Internal server error."),
     ),
     security(("HttpAuthBearerJWT" = [])),
@@ -90,17 +187,65 @@ pub async fn create_comment(
 
     utils::moderate_content(&ctx, &comment.body, "body").await?;
 
+    let no_parent = || Error::unprocessable_entity([("parentId", "no such comment to reply to")]);
+    let details = sqlx::query!(
+        r#"
+        SELECT
+            article.article_id,
+            (
+                article.comments_locked AND
+                NOT EXISTS(
+                    SELECT 1 FROM article_authors
+                    WHERE
+                        article_id = article.article_id AND
+                        user_id = $2 AND
+                        accepted_at IS NOT NULL
+                )
+            ) AS "locked!",
+            -- we cannot reply to deleted comments or comments under other articles
+            (
+                $3::UUID IS NULL OR
+                EXISTS(
+                    SELECT 1 FROM comments
+                    WHERE
+                        comment_id = $3 AND
+                        article_id = article.article_id AND
+                        deleted_at IS NULL
+                )
+            ) AS "parent_found!"
+        FROM articles article
+        WHERE slug = $1
+        "#,
+        &slug,
+        *uid,
+        comment.parent_id,
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+    if details.locked {
+        return Err(Error::Forbidden);
+    }
+    if !details.parent_found {
+        return Err(no_parent());
+    }
+
+    let mut tx = ctx.db.begin().await?;
     let data = sqlx::query!(
         r#"
         WITH comment AS (
-            INSERT INTO comments (article_id, user_id, body)
-            SELECT article_id, $2, $3 FROM articles WHERE slug = $1
-            RETURNING comment_id, created_at, updated_at
+            INSERT INTO comments (article_id, user_id, body, parent_comment_id, depth)
+            SELECT
+                article_id, $2, $3, $4,
+                COALESCE((SELECT depth + 1 FROM comments WHERE comment_id = $4), 0)
+            FROM articles WHERE slug = $1
+            RETURNING comment_id, created_at, updated_at, depth
         )
         SELECT
             comment.comment_id AS comment_id,
             comment.created_at AS comment_created_at,
             comment.updated_at AS comment_updated_at,
+            comment.depth AS comment_depth,
             comment_author.bio AS comment_author_bio,
             comment_author.username AS comment_author_username,
             comment_author.image AS comment_author_image
@@ -108,20 +253,33 @@ pub async fn create_comment(
         "#,
         &slug,
         *uid,
-        &comment.body
+        &comment.body,
+        comment.parent_id,
     )
-    .fetch_optional(&ctx.db)
+    .fetch_optional(&mut *tx)
     .await
-    .on_constraint("comments_user_id_fkey", |_| Error::Unauthorized)?
+    .on_constraint("comments_user_id_fkey", |_| Error::Unauthorized)
+    // the parent has just been removed
+    .on_constraint("comments_parent_comment_id_fkey", |_| no_parent())?
     .ok_or(Error::NotFound)?;
+    mentions::sync(
+        &mut tx,
+        &details.article_id,
+        Some(&data.comment_id),
+        &uid,
+        &comment.body,
+    )
+    .await?;
+    tx.commit().await?;
 
     let payload = CommentPayload {
         comment: Comment {
             id: data.comment_id,
             created_at: data.comment_created_at,
             updated_at: data.comment_updated_at.unwrap_or(data.comment_created_at),
+            edited: false,
             body: comment.body,
-            author: Author {
+            author: Some(Author {
                 username: data.comment_author_username,
                 bio: data.comment_author_bio,
                 image: parse_image_url(data.comment_author_image.as_deref())?,
@@ -129,10 +287,31 @@ pub async fn create_comment(
                 // the comment's author cannot follow the calling user since
                 // this is the same actor in this case;
                 following: false,
-            },
+            }),
+            pinned: false,
+            hidden: false,
+            parent_id: comment.parent_id,
+            depth: data.comment_depth as u32,
+            deleted: false,
+            replies_count: 0,
+            reactions: BTreeMap::new(),
+            my_reactions: Vec::new(),
+            replies: None,
         },
     };
 
+    // authors get notified, see `notify_on_comment` trigger, and so do
+    // the mentioned users
+    notifications::publish(&ctx, &uid).await;
+    let event = CommentEvent {
+        article: &slug,
+        comment: &payload.comment,
+    };
+    let topics = [Topic::Article(details.article_id)];
+    if let Err(e) = ctx.events.publish(&topics, "comment", &event).await {
+        warn!(error = ?e, "failed to publish comment");
+    }
+
     Ok(Json(payload))
 }
 
@@ -140,13 +319,120 @@ pub async fn create_comment(
 
 /// Contrainer for list comments endpoint.
 #[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct CommentsList {
     /// List of comments.
     comments: Vec<Comment>,
+
+    /// How many comments (including replies) have been left under the article.
+    #[schema(examples(1))]
+    comments_count: usize,
+
+    /// Cursor to fetch the next page of comments, if any.
+    #[schema(required = true)]
+    next_cursor: Option<String>,
+}
+
+/// Comments list sort order.
+///
+/// Only applies to top-level comments, while replies are always listed
+/// oldest first (i.e. in the order of the discussion).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CommentsSort {
+    /// Most recent comments first.
+    #[default]
+    Newest,
+
+    /// Oldest comments first.
+    Oldest,
+
+    /// Most upvoted comments first, with other reactions counting too,
+    /// though with less weight.
+    Top,
+}
+
+/// How to lay out the comments' threads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CommentsView {
+    /// Each comment is immediately followed by its replies, see `depth`.
+    #[default]
+    Flat,
+
+    /// Only top-level comments are listed, with the replies nested into
+    /// them, see `replies`.
+    Nested,
+}
+
+#[derive(Debug, Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub(crate) struct CommentsQuery {
+    /// How to lay out the threads.
+    #[param(inline, nullable = false, default = "flat")]
+    view: Option<CommentsView>,
+
+    /// How deep into the threads to go, `0` for the listed comments only
+    /// (i.e. the top-level comments, or the replies to `parentId`).
+    #[param(nullable = false, default = 5, maximum = 20)]
+    #[validate(range(max = 20, message = "max depth too large"))]
+    max_depth: Option<u32>,
+
+    /// Sort order of top-level comments.
+    ///
+    /// Does not apply to the replies to `parentId`.
+    #[param(inline, nullable = false, default = "newest")]
+    sort: Option<CommentsSort>,
+
+    /// Identifier of the comment to list the replies to (rather than the
+    /// top-level comments).
+    ///
+    /// This is how the replies not listed along with their thread can be
+    /// fetched, see `repliesCount`.
+    #[param(nullable = false)]
+    parent_id: Option<Uuid>,
+
+    /// Limit number of returned top-level comments (or replies to `parentId`).
+    ///
+    /// Replies to the returned comments do not count towards the limit.
+    #[param(nullable = false, default = 20, maximum = 100)]
+    #[validate(range(min = 1, max = 100, message = "limit should be between 1 and 100"))]
+    limit: Option<usize>,
+
+    /// Cursor to fetch comments starting from.
+    ///
+    /// Use `nextCursor` from a previously returned page (and the same `sort`).
+    #[param(nullable = false)]
+    cursor: Option<String>,
+}
+
+/// Position in comments list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CommentsCursor {
+    /// Comment's score (only for the `top` sort order).
+    #[serde(rename = "s", default)]
+    score: i64,
+
+    #[serde(rename = "t")]
+    created_at: DateTime<Utc>,
+
+    #[serde(rename = "id")]
+    comment_id: Uuid,
 }
 
 /// List comments to article.
 ///
+/// Top-level comments are listed newest first by default, see `sort` for
+/// other options, while replies are listed oldest first (i.e. in the order
+/// of the discussion). Top-level comments are paginated with a cursor, see
+/// `nextCursor` in the response, and each of them is accompanied with its
+/// replies, see `maxDepth`. The pinned comment (if any) goes first on the
+/// first page regardless of the sort order.
+///
+/// At most 50 replies (the oldest ones) are listed per thread, and the rest
+/// of them can be paginated through with `parentId`.
+///
 /// Authentication is optional.
 #[utoipa::path(
     get,
@@ -159,10 +445,13 @@ pub(crate) struct CommentsList {
             description = "Article's slug identifier.",
             example = "why-memory-safety-matters"
         ),
+        CommentsQuery,
     ),
     responses(
         (status = 200, description = "Comments list successfully retrieved", body = CommentsList),
         (status = 401, description = "Token missing or invalid (in case authenicated access has been used)"),
+        (status = 404, description = "Article (or parent comment) not found"),
+        (status = 422, description = "Invalid query", body = Validation),
         (status = 500, description = "Internal server error."),
     ),
     security(
@@ -174,20 +463,130 @@ pub(crate) struct CommentsList {
 pub async fn list_comments(
     ctx: State<Arc<AppContext>>,
     Path(slug): Path<String>,
+    q: Result<Query<CommentsQuery>, QueryRejection>,
     uid: MaybeUserID,
 ) -> Result<Json<CommentsList>, Error> {
-    let article_id = sqlx::query_scalar!(r"SELECT article_id FROM articles WHERE slug = $1", &slug)
-        .fetch_optional(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
+    let Query(q) = q?;
+    q.validate()?;
+    let cursor = q
+        .cursor
+        .as_deref()
+        .map(utils::decode_cursor::<CommentsCursor>)
+        .transpose()?;
+    let sort = q.sort.unwrap_or_default();
+    let limit = q.limit.unwrap_or(DEFAULT_LIMIT);
+
+    let article = sqlx::query!(
+        r#"
+        SELECT
+            article_id,
+            comments_count,
+            (
+                SELECT comment_id FROM comments
+                WHERE article_id = article.article_id AND pinned_at IS NOT NULL
+            ) AS pinned_comment_id,
+            (
+                $2::UUID IS NOT NULL AND
+                EXISTS(
+                    SELECT 1 FROM article_authors
+                    WHERE
+                        article_id = article.article_id AND
+                        user_id = $2 AND
+                        accepted_at IS NOT NULL
+                )
+            ) AS "moderator!",
+            (
+                $3::UUID IS NULL OR
+                EXISTS(
+                    SELECT 1 FROM comments
+                    WHERE comment_id = $3 AND article_id = article.article_id
+                )
+            ) AS "parent_found!"
+        FROM articles article
+        WHERE slug = $1
+        "#,
+        &slug,
+        uid.0.as_deref(),
+        q.parent_id,
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .filter(|article| article.parent_found)
+    .ok_or(Error::NotFound)?;
+
+    // NB: we are fetching one extra row to tell if there is a next page
+    let mut page = db::fetch_page(
+        &ctx,
+        &article.article_id,
+        q.parent_id.as_ref(),
+        sort,
+        cursor.as_ref(),
+        limit as i64 + 1,
+    )
+    .await?;
+    let next_cursor = if page.len() > limit {
+        page.truncate(limit);
+        page.last().map(|root| {
+            utils::encode_cursor(&CommentsCursor {
+                score: root.score,
+                created_at: root.created_at,
+                comment_id: root.comment_id,
+            })
+        })
+    } else {
+        None
+    };
+    let page: Vec<_> = article
+        .pinned_comment_id
+        .filter(|_| cursor.is_none() && q.parent_id.is_none())
+        .into_iter()
+        .chain(page.into_iter().map(|root| root.comment_id))
+        .collect();
 
     let comments = sqlx::query!(
         r#"
+        WITH RECURSIVE
+            thread AS (
+                SELECT comment_id, comment_id AS root_id, depth + $3 AS max_depth
+                FROM comments WHERE comment_id = ANY($2)
+                UNION ALL
+                SELECT reply.comment_id, thread.root_id, thread.max_depth
+                FROM comments reply JOIN thread ON reply.parent_comment_id = thread.comment_id
+                WHERE reply.depth <= thread.max_depth
+            ),
+            -- replies come after what they are replying to, and so the oldest
+            -- comments of a thread are making up a whole (but shorter) thread
+            capped AS (
+                SELECT comment_id FROM (
+                    SELECT
+                        comment_id,
+                        row_number() OVER (
+                            PARTITION BY root_id ORDER BY created_at, comment_id
+                        ) AS position
+                    FROM thread JOIN comments USING (comment_id)
+                ) thread
+                WHERE position <= $4
+            )
         SELECT
             comment.comment_id AS comment_id,
             comment.created_at AS comment_created_at,
             comment.updated_at AS comment_updated_at,
             comment.body AS comment_body,
+            comment.parent_comment_id AS comment_parent_id,
+            comment.depth AS comment_depth,
+            comment.deleted_at IS NOT NULL AS "comment_deleted!",
+            comment.pinned_at IS NOT NULL AS "comment_pinned!",
+            comment.hidden_at IS NOT NULL AS "comment_hidden!",
+            EXISTS(
+                SELECT 1 FROM comment_edits WHERE comment_id = comment.comment_id
+            ) AS "comment_edited!",
+            (
+                SELECT count(*) FROM comments
+                WHERE parent_comment_id = comment.comment_id AND deleted_at IS NULL
+            ) AS "comment_replies_count!",
+            comment_reactions_json(comment.comment_id) AS "comment_reactions!: SqlxJson<BTreeMap<Reaction, u64>>",
+            comment_user_reactions_json(comment.comment_id, $1) AS "comment_my_reactions!: SqlxJson<Vec<Reaction>>",
+            coalesce(comment.user_id = $1, FALSE) AS "comment_by_user!",
             comment_author.bio AS comment_author_bio,
             comment_author.username AS comment_author_username,
             comment_author.image AS comment_author_image,
@@ -199,48 +598,257 @@ pub async fn list_comments(
                         AND following_user_id = $1
                     )
             ) AS "comment_author_following!"
-        FROM comments comment JOIN users comment_author USING (user_id)
-        WHERE comment.article_id = $2
-        ORDER BY comment_created_at DESC
-        LIMIT $3
+        FROM capped
+            JOIN comments comment USING (comment_id)
+            JOIN users comment_author USING (user_id)
+        ORDER BY comment_created_at
         "#,
         uid.0.as_deref(),
-        article_id,
-        // Realword spec is not mentioning this. Apparently, comment lists
-        // should support either pagination or infinite scrolling; meanwhile,
-        // we are setting a magic number limit
-        100,
+        &page,
+        q.max_depth.unwrap_or(5) as i32,
+        // the listed comment itself is at the thread's first position
+        MAX_REPLIES as i64 + 1,
     )
     .fetch_all(&ctx.db)
     .await?;
-    let payload = CommentsList {
-        comments: comments
-            .into_iter()
-            .map(|data| {
-                let comment = Comment {
-                    id: data.comment_id,
-                    created_at: data.comment_created_at,
-                    updated_at: data.comment_updated_at.unwrap_or(data.comment_created_at),
-                    body: data.comment_body,
-                    author: Author {
-                        username: data.comment_author_username,
-                        bio: data.comment_author_bio,
-                        image: parse_image_url(data.comment_author_image.as_deref())?,
-                        following: data.comment_author_following,
-                    },
+    let comments = comments
+        .into_iter()
+        .map(|data| {
+            let redacted = data.comment_deleted
+                || (data.comment_hidden && !article.moderator && !data.comment_by_user);
+            let (body, author) = if redacted {
+                (String::new(), None)
+            } else {
+                let author = Author {
+                    username: data.comment_author_username,
+                    bio: data.comment_author_bio,
+                    image: parse_image_url(data.comment_author_image.as_deref())?,
+                    following: data.comment_author_following,
                 };
-                Ok(comment)
-            })
-            .collect::<Result<_, Error>>()?,
+                (data.comment_body, Some(author))
+            };
+            let comment = Comment {
+                id: data.comment_id,
+                created_at: data.comment_created_at,
+                updated_at: data.comment_updated_at.unwrap_or(data.comment_created_at),
+                edited: data.comment_edited,
+                body,
+                author,
+                pinned: data.comment_pinned,
+                hidden: data.comment_hidden,
+                parent_id: data.comment_parent_id,
+                depth: data.comment_depth as u32,
+                deleted: data.comment_deleted,
+                replies_count: data.comment_replies_count as u64,
+                reactions: data.comment_reactions.0,
+                my_reactions: data.comment_my_reactions.0,
+                replies: None,
+            };
+            Ok(comment)
+        })
+        .collect::<Result<_, Error>>()?;
+    let payload = CommentsList {
+        comments: thread(&page, comments, q.view.unwrap_or_default()),
+        comments_count: article.comments_count as usize,
+        next_cursor,
     };
     Ok(Json(payload))
 }
 
+/// Lay out the comments (given in chronological order) into threads.
+///
+/// Threads will be ordered as the comments on the `page` (which are usually
+/// top-level comments, but can be replies to a comment as well).
+fn thread(page: &[Uuid], comments: Vec<Comment>, view: CommentsView) -> Vec<Comment> {
+    let mut roots = HashMap::new();
+    let mut replies: HashMap<Uuid, Vec<Comment>> = HashMap::new();
+    for comment in comments {
+        match comment.parent_id {
+            Some(parent_id) if !page.contains(&comment.id) => {
+                replies.entry(parent_id).or_default().push(comment)
+            }
+            _ => {
+                roots.insert(comment.id, comment);
+            }
+        }
+    }
+    let roots = page
+        .iter()
+        .filter_map(|comment_id| roots.remove(comment_id));
+
+    fn nest(mut comment: Comment, replies: &mut HashMap<Uuid, Vec<Comment>>) -> Comment {
+        let nested = replies.remove(&comment.id).unwrap_or_default();
+        comment.replies = Some(nested.into_iter().map(|c| nest(c, replies)).collect());
+        comment
+    }
+
+    fn flatten(
+        comment: Comment,
+        replies: &mut HashMap<Uuid, Vec<Comment>>,
+        flattened: &mut Vec<Comment>,
+    ) {
+        let comment_id = comment.id;
+        flattened.push(comment);
+        for reply in replies.remove(&comment_id).unwrap_or_default() {
+            flatten(reply, replies, flattened);
+        }
+    }
+
+    match view {
+        CommentsView::Nested => roots.map(|c| nest(c, &mut replies)).collect(),
+        CommentsView::Flat => {
+            let mut flattened = Vec::new();
+            for root in roots {
+                flatten(root, &mut replies, &mut flattened);
+            }
+            flattened
+        }
+    }
+}
+
+// ------------------------------ UPDATE COMMENT -------------------------------
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub(crate) struct CommentUpdate {
+    /// Comment's new text.
+    #[schema(
+        examples("Memory safety matters, but it's all about ecosystem imho",),
+        min_length = 1,
+        max_length = 500
+    )]
+    #[validate(length(
+        min = 1,
+        max = 500,
+        message = "comment should be at least 1 and at max 500 characters long"
+    ))]
+    body: String,
+}
+
+/// Update comment.
+///
+/// Only the comment's author can edit the comment and only within 15 minutes
+/// after the comment has been created. The comment's prior text is preserved
+/// in the edit history.
+///
+/// Authentication required.
+#[utoipa::path(
+    put,
+    path = "/{slug}/comments/{comment_id}",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "why-memory-safety-matters",
+        ),
+        (
+            "comment_id" = String, Path,
+            format = Uuid,
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ),
+    responses(
+        (status = 200, description = "Comment successfully updated", body = CommentPayload<Comment>),
+        (status = 401, description = "Token missing or invalid"),
+        (status = 403, description = "User does not have permissions to update this comment"),
+        (status = 404, description = "Article or comment not found"),
+        (status = 415, description = "Method not allow / Content-Type is incorrect"),
+        (status = 422, description = "Invalid comment attributes, malformed comment_id in path, or edit window elapsed", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "UPDATE COMMENT", skip(ctx))]
+pub async fn update_comment(
+    ctx: State<Arc<AppContext>>,
+    Path((slug, comment_id)): Path<(String, String)>,
+    uid: UserID,
+    Json(CommentPayload { comment: patch }): Json<CommentPayload<CommentUpdate>>,
+) -> Result<Json<CommentPayload<Comment>>, Error> {
+    let comment_id = Uuid::parse_str(&comment_id)
+        .map_err(|_| Error::unprocessable_entity([("path", "comment_id is not a valid UUID")]))?;
+    patch.validate()?;
+
+    utils::moderate_content(&ctx, &patch.body, "body").await?;
+
+    let mut tx = ctx.db.begin().await?;
+    let current = sqlx::query!(
+        r#"
+        SELECT
+            comment.article_id,
+            comment.user_id,
+            comment.body,
+            comment.created_at > NOW() - make_interval(secs => $3) AS "editable!"
+        FROM comments comment JOIN articles article USING (article_id)
+        WHERE comment.comment_id = $1 AND article.slug = $2 AND comment.deleted_at IS NULL
+        FOR UPDATE OF comment
+        "#,
+        comment_id,
+        &slug,
+        EDIT_WINDOW.as_secs_f64(),
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if current.user_id != *uid {
+        warn!("user tried to update comment w/o proper permissions");
+        return Err(Error::Forbidden);
+    }
+    if !current.editable {
+        return Err(Error::unprocessable_entity([(
+            "body",
+            "comment can no longer be edited",
+        )]));
+    }
+
+    // re-submitting the same text is not an edit
+    let edited = current.body != patch.body;
+    if edited {
+        sqlx::query!(
+            "INSERT INTO comment_edits (comment_id, body) VALUES ($1, $2)",
+            comment_id,
+            current.body,
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE comments SET body = $2 WHERE comment_id = $1",
+            comment_id,
+            &patch.body,
+        )
+        .execute(&mut *tx)
+        .await?;
+        mentions::sync(
+            &mut tx,
+            &current.article_id,
+            Some(&comment_id),
+            &uid,
+            &patch.body,
+        )
+        .await?;
+    }
+    tx.commit().await?;
+
+    notifications::publish(&ctx, &uid).await;
+
+    let comment = read_comment(&ctx, comment_id, &uid).await?;
+    Ok(Json(CommentPayload { comment }))
+}
+
 // ------------------------------ DELETE COMMENT -------------------------------
 
 /// Delete comment.
 ///
-/// Authentication required.
+/// A comment having replies is not removed, but rather turned into
+/// a "deleted" placeholder, so that the replies are kept in the thread.
+/// The placeholder is removed once all of the replies are gone.
+///
+/// Authentication required. Only the comment's author or the article's
+/// authors can perform this action.
 #[utoipa::path(
     delete,
     path = "/{slug}/comments/{comment_id}",
@@ -283,19 +891,62 @@ pub async fn delete_comment(
     let res = sqlx::query!(
         r#"
         WITH
+            comment AS (
+                SELECT
+                    comment_id,
+                    EXISTS(
+                        SELECT 1 FROM comments WHERE parent_comment_id = $1
+                    ) AS has_replies
+                FROM comments
+                WHERE
+                    comment_id = $1 AND
+                    deleted_at IS NULL AND
+                    (
+                        user_id = $2 OR
+                        EXISTS(
+                            SELECT 1 FROM article_authors
+                            WHERE
+                                article_id = comments.article_id AND
+                                user_id = $2 AND
+                                accepted_at IS NOT NULL
+                        )
+                    )
+            ),
             comment_deleted AS (
                 DELETE FROM comments
-                WHERE comment_id = $1 AND user_id = $2
+                WHERE comment_id IN (SELECT comment_id FROM comment WHERE NOT has_replies)
+                RETURNING 1
+            ),
+            comment_blanked AS (
+                UPDATE comments SET body = '', deleted_at = NOW(), pinned_at = NULL
+                WHERE comment_id IN (SELECT comment_id FROM comment WHERE has_replies)
                 RETURNING 1
             ),
+            -- prior versions, reactions and mentions should be gone along with the
+            -- comment's text (for removed comments, the cascade takes care of this)
+            edits_deleted AS (
+                DELETE FROM comment_edits
+                WHERE comment_id IN (SELECT comment_id FROM comment WHERE has_replies)
+            ),
+            reactions_deleted AS (
+                DELETE FROM comment_reactions
+                WHERE comment_id IN (SELECT comment_id FROM comment WHERE has_replies)
+            ),
+            mentions_deleted AS (
+                DELETE FROM mentions
+                WHERE comment_id IN (SELECT comment_id FROM comment WHERE has_replies)
+            ),
             comment_existed AS (
-                SELECT 1 FROM comments WHERE comment_id = $1::TEXT::UUID
+                SELECT 1 FROM comments WHERE comment_id = $1::TEXT::UUID AND deleted_at IS NULL
             ),
             article_exists AS (
                 SELECT 1 FROM articles WHERE slug = $3
             )
         SELECT
-            EXISTS(SELECT 1 FROM comment_deleted) AS "comment_deleted!",
+            (
+                EXISTS(SELECT 1 FROM comment_deleted) OR
+                EXISTS(SELECT 1 FROM comment_blanked)
+            ) AS "comment_deleted!",
             EXISTS(SELECT 1 FROM comment_existed) AS "comment_existed!",
             EXISTS(SELECT 1 FROM article_exists) AS "article_exists!"
         "#,
@@ -316,3 +967,712 @@ pub async fn delete_comment(
 
     Err(Error::Forbidden)
 }
+
+// ------------------------------- MODERATION ----------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Moderation {
+    Pin,
+    Unpin,
+    Hide,
+    Unhide,
+}
+
+/// Pin comment.
+///
+/// Only a top-level comment can be pinned and only one comment can be pinned
+/// per article: pinning a comment unpins the previously pinned one, if any.
+///
+/// Authentication required. Only the article's authors can perform this action.
+#[utoipa::path(
+    post,
+    path = "/{slug}/comments/{comment_id}/pin",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "why-memory-safety-matters",
+        ),
+        (
+            "comment_id" = String, Path,
+            format = Uuid,
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ),
+    responses(
+        (status = 200, description = "Comment successfully pinned", body = CommentPayload<Comment>),
+        (status = 401, description = "Token missing or invalid"),
+        (status = 403, description = "User does not have permissions to moderate comments under this article"),
+        (status = 404, description = "Article or comment not found"),
+        (status = 422, description = "Malformed comment_id in path or not a top-level comment", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "PIN COMMENT", skip(ctx))]
+pub async fn pin_comment(
+    ctx: State<Arc<AppContext>>,
+    Path((slug, comment_id)): Path<(String, String)>,
+    uid: UserID,
+) -> Result<Json<CommentPayload<Comment>>, Error> {
+    moderate_comment(&ctx, &slug, &comment_id, &uid, Moderation::Pin).await
+}
+
+/// Unpin comment.
+///
+/// This operation is idempotent.
+///
+/// Authentication required. Only the article's authors can perform this action.
+#[utoipa::path(
+    delete,
+    path = "/{slug}/comments/{comment_id}/pin",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "why-memory-safety-matters",
+        ),
+        (
+            "comment_id" = String, Path,
+            format = Uuid,
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ),
+    responses(
+        (status = 200, description = "Comment successfully unpinned", body = CommentPayload<Comment>),
+        (status = 401, description = "Token missing or invalid"),
+        (status = 403, description = "User does not have permissions to moderate comments under this article"),
+        (status = 404, description = "Article or comment not found"),
+        (status = 422, description = "Malformed comment_id in path", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "UNPIN COMMENT", skip(ctx))]
+pub async fn unpin_comment(
+    ctx: State<Arc<AppContext>>,
+    Path((slug, comment_id)): Path<(String, String)>,
+    uid: UserID,
+) -> Result<Json<CommentPayload<Comment>>, Error> {
+    moderate_comment(&ctx, &slug, &comment_id, &uid, Moderation::Unpin).await
+}
+
+/// Hide comment.
+///
+/// The hidden comment stays in the thread, but its text and author are only
+/// revealed to the article's authors and the comment's author. This operation
+/// is idempotent.
+///
+/// Authentication required. Only the article's authors can perform this action.
+#[utoipa::path(
+    post,
+    path = "/{slug}/comments/{comment_id}/hide",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "why-memory-safety-matters",
+        ),
+        (
+            "comment_id" = String, Path,
+            format = Uuid,
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ),
+    responses(
+        (status = 200, description = "Comment successfully hidden", body = CommentPayload<Comment>),
+        (status = 401, description = "Token missing or invalid"),
+        (status = 403, description = "User does not have permissions to moderate comments under this article"),
+        (status = 404, description = "Article or comment not found"),
+        (status = 422, description = "Malformed comment_id in path", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "HIDE COMMENT", skip(ctx))]
+pub async fn hide_comment(
+    ctx: State<Arc<AppContext>>,
+    Path((slug, comment_id)): Path<(String, String)>,
+    uid: UserID,
+) -> Result<Json<CommentPayload<Comment>>, Error> {
+    moderate_comment(&ctx, &slug, &comment_id, &uid, Moderation::Hide).await
+}
+
+/// Unhide comment.
+///
+/// This operation is idempotent.
+///
+/// Authentication required. Only the article's authors can perform this action.
+#[utoipa::path(
+    delete,
+    path = "/{slug}/comments/{comment_id}/hide",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "why-memory-safety-matters",
+        ),
+        (
+            "comment_id" = String, Path,
+            format = Uuid,
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ),
+    responses(
+        (status = 200, description = "Comment successfully unhidden", body = CommentPayload<Comment>),
+        (status = 401, description = "Token missing or invalid"),
+        (status = 403, description = "User does not have permissions to moderate comments under this article"),
+        (status = 404, description = "Article or comment not found"),
+        (status = 422, description = "Malformed comment_id in path", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "UNHIDE COMMENT", skip(ctx))]
+pub async fn unhide_comment(
+    ctx: State<Arc<AppContext>>,
+    Path((slug, comment_id)): Path<(String, String)>,
+    uid: UserID,
+) -> Result<Json<CommentPayload<Comment>>, Error> {
+    moderate_comment(&ctx, &slug, &comment_id, &uid, Moderation::Unhide).await
+}
+
+async fn moderate_comment(
+    ctx: &AppContext,
+    slug: &str,
+    comment_id: &str,
+    uid: &Uuid,
+    action: Moderation,
+) -> Result<Json<CommentPayload<Comment>>, Error> {
+    let comment_id = Uuid::parse_str(comment_id)
+        .map_err(|_| Error::unprocessable_entity([("path", "comment_id is not a valid UUID")]))?;
+
+    let mut tx = ctx.db.begin().await?;
+    let current = sqlx::query!(
+        r#"
+        SELECT
+            comment.article_id,
+            comment.parent_comment_id IS NULL AS "top_level!",
+            EXISTS(
+                SELECT 1 FROM article_authors
+                WHERE
+                    article_id = comment.article_id AND
+                    user_id = $3 AND
+                    accepted_at IS NOT NULL
+            ) AS "moderator!"
+        FROM comments comment JOIN articles article USING (article_id)
+        WHERE comment.comment_id = $1 AND article.slug = $2 AND comment.deleted_at IS NULL
+        FOR UPDATE OF comment
+        "#,
+        comment_id,
+        slug,
+        uid,
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if !current.moderator {
+        warn!("user tried to moderate comment w/o proper permissions");
+        return Err(Error::Forbidden);
+    }
+
+    match action {
+        Moderation::Pin => {
+            if !current.top_level {
+                return Err(Error::unprocessable_entity([(
+                    "path",
+                    "only top-level comments can be pinned",
+                )]));
+            }
+            sqlx::query!(
+                r#"
+                UPDATE comments SET pinned_at = NULL
+                WHERE article_id = $1 AND comment_id <> $2 AND pinned_at IS NOT NULL
+                "#,
+                current.article_id,
+                comment_id,
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!(
+                "UPDATE comments SET pinned_at = COALESCE(pinned_at, NOW()) WHERE comment_id = $1",
+                comment_id,
+            )
+            .execute(&mut *tx)
+            .await
+            // another comment has been pinned concurrently
+            .on_constraint("comments_pinned_idx", |_| {
+                Error::unprocessable_entity([("path", "another comment is being pinned")])
+            })?;
+        }
+        Moderation::Unpin => {
+            sqlx::query!(
+                "UPDATE comments SET pinned_at = NULL WHERE comment_id = $1",
+                comment_id,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        Moderation::Hide => {
+            sqlx::query!(
+                "UPDATE comments SET hidden_at = COALESCE(hidden_at, NOW()) WHERE comment_id = $1",
+                comment_id,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        Moderation::Unhide => {
+            sqlx::query!(
+                "UPDATE comments SET hidden_at = NULL WHERE comment_id = $1",
+                comment_id,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+    tx.commit().await?;
+
+    let comment = read_comment(ctx, comment_id, uid).await?;
+    Ok(Json(CommentPayload { comment }))
+}
+
+// ------------------------------- REACTIONS -----------------------------------
+
+/// Reaction to a comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Reaction {
+    /// The comment is helpful or insightful, see also `top` comments.
+    Upvote,
+    Heart,
+    Laugh,
+    Hooray,
+    Confused,
+    Eyes,
+    Rocket,
+}
+
+impl Reaction {
+    const ALL: [Reaction; 7] = [
+        Self::Upvote,
+        Self::Heart,
+        Self::Laugh,
+        Self::Hooray,
+        Self::Confused,
+        Self::Eyes,
+        Self::Rocket,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Upvote => "upvote",
+            Self::Heart => "heart",
+            Self::Laugh => "laugh",
+            Self::Hooray => "hooray",
+            Self::Confused => "confused",
+            Self::Eyes => "eyes",
+            Self::Rocket => "rocket",
+        }
+    }
+
+    fn parse(reaction: &str) -> Result<Self, Error> {
+        Self::ALL
+            .into_iter()
+            .find(|r| r.as_str() == reaction)
+            .ok_or_else(|| Error::unprocessable_entity([("path", "unknown reaction")]))
+    }
+}
+
+/// React to comment.
+///
+/// Note that this operation is idempotent: if this user already reacted to
+/// the comment this way, a successful response will be returned. A user can
+/// react to the same comment in a few different ways though.
+///
+/// Authentication required.
+#[utoipa::path(
+    post,
+    path = "/{slug}/comments/{comment_id}/reactions/{reaction}",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "why-memory-safety-matters",
+        ),
+        (
+            "comment_id" = String, Path,
+            format = Uuid,
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "reaction" = Reaction, Path,
+            example = "upvote",
+        ),
+    ),
+    responses(
+        (status = 200, description = "Reaction successfully added", body = CommentPayload<Comment>),
+        (status = 401, description = "Token missing or invalid"),
+        (status = 404, description = "Article or comment not found"),
+        (status = 422, description = "Malformed comment_id or unknown reaction in path", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "REACT TO COMMENT", skip(ctx))]
+pub async fn add_reaction(
+    ctx: State<Arc<AppContext>>,
+    Path((slug, comment_id, reaction)): Path<(String, String, String)>,
+    uid: UserID,
+) -> Result<Json<CommentPayload<Comment>>, Error> {
+    let comment_id = Uuid::parse_str(&comment_id)
+        .map_err(|_| Error::unprocessable_entity([("path", "comment_id is not a valid UUID")]))?;
+    let reaction = Reaction::parse(&reaction)?;
+
+    let _comment_id = sqlx::query_scalar!(
+        r#"
+        WITH
+            existing_comment AS (
+                SELECT comment_id
+                FROM comments JOIN articles USING (article_id)
+                WHERE comment_id = $1 AND slug = $2 AND deleted_at IS NULL
+            ),
+            _reaction_action AS (
+                INSERT INTO comment_reactions (comment_id, user_id, reaction)
+                SELECT comment_id, $3, $4 FROM existing_comment
+                ON CONFLICT DO NOTHING
+            )
+        SELECT comment_id FROM existing_comment
+        "#,
+        comment_id,
+        &slug,
+        *uid,
+        reaction.as_str(),
+    )
+    .fetch_optional(&ctx.db)
+    .await
+    .on_constraint("comment_reactions_user_id_fkey", |_| Error::Unauthorized)?
+    .ok_or(Error::NotFound)?;
+
+    let comment = read_comment(&ctx, comment_id, &uid).await?;
+    Ok(Json(CommentPayload { comment }))
+}
+
+/// Revoke reaction to comment.
+///
+/// Similar to the `add_reaction` endpoint, this operation is idempotent.
+///
+/// Authentication required.
+#[utoipa::path(
+    delete,
+    path = "/{slug}/comments/{comment_id}/reactions/{reaction}",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "why-memory-safety-matters",
+        ),
+        (
+            "comment_id" = String, Path,
+            format = Uuid,
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "reaction" = Reaction, Path,
+            example = "upvote",
+        ),
+    ),
+    responses(
+        (status = 200, description = "Reaction successfully revoked", body = CommentPayload<Comment>),
+        (status = 401, description = "Token missing or invalid"),
+        (status = 404, description = "Article or comment not found"),
+        (status = 422, description = "Malformed comment_id or unknown reaction in path", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "REVOKE COMMENT REACTION", skip(ctx))]
+pub async fn remove_reaction(
+    ctx: State<Arc<AppContext>>,
+    Path((slug, comment_id, reaction)): Path<(String, String, String)>,
+    uid: UserID,
+) -> Result<Json<CommentPayload<Comment>>, Error> {
+    let comment_id = Uuid::parse_str(&comment_id)
+        .map_err(|_| Error::unprocessable_entity([("path", "comment_id is not a valid UUID")]))?;
+    let reaction = Reaction::parse(&reaction)?;
+
+    let _comment_id = sqlx::query_scalar!(
+        r#"
+        WITH
+            existing_comment AS (
+                SELECT comment_id
+                FROM comments JOIN articles USING (article_id)
+                WHERE comment_id = $1 AND slug = $2 AND deleted_at IS NULL
+            ),
+            _reaction_action AS (
+                DELETE FROM comment_reactions
+                WHERE
+                    comment_id IN (SELECT comment_id FROM existing_comment) AND
+                    user_id = $3 AND
+                    reaction = $4
+            )
+        SELECT comment_id FROM existing_comment
+        "#,
+        comment_id,
+        &slug,
+        *uid,
+        reaction.as_str(),
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    let comment = read_comment(&ctx, comment_id, &uid).await?;
+    Ok(Json(CommentPayload { comment }))
+}
+
+/// Read a (not deleted) comment as seen by the user.
+async fn read_comment(ctx: &AppContext, comment_id: Uuid, uid: &Uuid) -> Result<Comment, Error> {
+    let data = sqlx::query!(
+        r#"
+        SELECT
+            comment.created_at AS comment_created_at,
+            comment.updated_at AS comment_updated_at,
+            comment.body AS comment_body,
+            comment.parent_comment_id AS comment_parent_id,
+            comment.depth AS comment_depth,
+            comment.pinned_at IS NOT NULL AS "comment_pinned!",
+            comment.hidden_at IS NOT NULL AS "comment_hidden!",
+            EXISTS(
+                SELECT 1 FROM comment_edits WHERE comment_id = comment.comment_id
+            ) AS "comment_edited!",
+            (
+                SELECT count(*) FROM comments
+                WHERE parent_comment_id = comment.comment_id AND deleted_at IS NULL
+            ) AS "comment_replies_count!",
+            comment_reactions_json(comment.comment_id) AS "comment_reactions!: SqlxJson<BTreeMap<Reaction, u64>>",
+            comment_user_reactions_json(comment.comment_id, $2) AS "comment_my_reactions!: SqlxJson<Vec<Reaction>>",
+            (
+                comment.user_id = $2 OR
+                EXISTS(
+                    SELECT 1 FROM article_authors
+                    WHERE
+                        article_id = comment.article_id AND
+                        user_id = $2 AND
+                        accepted_at IS NOT NULL
+                )
+            ) AS "comment_revealed!",
+            comment_author.bio AS comment_author_bio,
+            comment_author.username AS comment_author_username,
+            comment_author.image AS comment_author_image,
+            EXISTS(
+                SELECT 1 FROM follows
+                WHERE followed_user_id = comment_author.user_id AND following_user_id = $2
+            ) AS "comment_author_following!"
+        FROM comments comment JOIN users comment_author USING (user_id)
+        WHERE comment.comment_id = $1
+        "#,
+        comment_id,
+        uid,
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    // the comment has just been deleted
+    .ok_or(Error::NotFound)?;
+
+    let (body, author) = if data.comment_hidden && !data.comment_revealed {
+        (String::new(), None)
+    } else {
+        let author = Author {
+            username: data.comment_author_username,
+            bio: data.comment_author_bio,
+            image: parse_image_url(data.comment_author_image.as_deref())?,
+            following: data.comment_author_following,
+        };
+        (data.comment_body, Some(author))
+    };
+    let comment = Comment {
+        id: comment_id,
+        created_at: data.comment_created_at,
+        updated_at: data.comment_updated_at.unwrap_or(data.comment_created_at),
+        edited: data.comment_edited,
+        body,
+        author,
+        pinned: data.comment_pinned,
+        hidden: data.comment_hidden,
+        parent_id: data.comment_parent_id,
+        depth: data.comment_depth as u32,
+        deleted: false,
+        replies_count: data.comment_replies_count as u64,
+        reactions: data.comment_reactions.0,
+        my_reactions: data.comment_my_reactions.0,
+        replies: None,
+    };
+    Ok(comment)
+}
+
+mod db {
+    use super::{CommentsCursor, CommentsSort};
+    use crate::AppContext;
+    use crate::http::errors::Error;
+    use chrono::{DateTime, Utc};
+    use uuid::Uuid;
+
+    pub struct PageRow {
+        pub comment_id: Uuid,
+        pub created_at: DateTime<Utc>,
+        pub score: i64,
+    }
+
+    /// Fetch the page of top-level comments (other than the pinned one),
+    /// or of the replies to the `parent_id` comment.
+    ///
+    /// There is a statement per sort order, so that the chronological ones
+    /// can seek through the index rather than sort all the comments.
+    pub async fn fetch_page(
+        ctx: &AppContext,
+        article_id: &Uuid,
+        parent_id: Option<&Uuid>,
+        sort: CommentsSort,
+        cursor: Option<&CommentsCursor>,
+        limit: i64,
+    ) -> Result<Vec<PageRow>, Error> {
+        let created_at = cursor.map(|c| c.created_at);
+        let comment_id = cursor.map(|c| c.comment_id);
+        let rows = match (parent_id, sort) {
+            // replies are listed in the order of the discussion
+            (Some(parent_id), _) => {
+                sqlx::query_as!(
+                    PageRow,
+                    r#"
+                    SELECT comment_id, created_at, 0::BIGINT AS "score!"
+                    FROM comments
+                    WHERE
+                        parent_comment_id = $1 AND
+                        (created_at, comment_id) > (
+                            coalesce($2::TIMESTAMPTZ, '-infinity'),
+                            coalesce($3::UUID, '00000000-0000-0000-0000-000000000000')
+                        )
+                    ORDER BY created_at, comment_id
+                    LIMIT $4
+                    "#,
+                    parent_id,
+                    created_at,
+                    comment_id,
+                    limit,
+                )
+                .fetch_all(&ctx.db)
+                .await?
+            }
+            (None, CommentsSort::Oldest) => {
+                sqlx::query_as!(
+                    PageRow,
+                    r#"
+                    SELECT comment_id, created_at, 0::BIGINT AS "score!"
+                    FROM comments
+                    WHERE
+                        article_id = $1 AND
+                        parent_comment_id IS NULL AND
+                        pinned_at IS NULL AND
+                        (created_at, comment_id) > (
+                            coalesce($2::TIMESTAMPTZ, '-infinity'),
+                            coalesce($3::UUID, '00000000-0000-0000-0000-000000000000')
+                        )
+                    ORDER BY created_at, comment_id
+                    LIMIT $4
+                    "#,
+                    article_id,
+                    created_at,
+                    comment_id,
+                    limit,
+                )
+                .fetch_all(&ctx.db)
+                .await?
+            }
+            (None, CommentsSort::Newest) => {
+                sqlx::query_as!(
+                    PageRow,
+                    r#"
+                    SELECT comment_id, created_at, 0::BIGINT AS "score!"
+                    FROM comments
+                    WHERE
+                        article_id = $1 AND
+                        parent_comment_id IS NULL AND
+                        pinned_at IS NULL AND
+                        (created_at, comment_id) < (
+                            coalesce($2::TIMESTAMPTZ, 'infinity'),
+                            coalesce($3::UUID, 'ffffffff-ffff-ffff-ffff-ffffffffffff')
+                        )
+                    ORDER BY created_at DESC, comment_id DESC
+                    LIMIT $4
+                    "#,
+                    article_id,
+                    created_at,
+                    comment_id,
+                    limit,
+                )
+                .fetch_all(&ctx.db)
+                .await?
+            }
+            // the scores are not stored, and so all the top-level comments
+            // are to be scored and sorted
+            (None, CommentsSort::Top) => {
+                sqlx::query_as!(
+                    PageRow,
+                    r#"
+                    WITH roots AS (
+                        SELECT
+                            comment_id,
+                            created_at,
+                            (
+                                SELECT coalesce(sum(CASE reaction WHEN 'upvote' THEN 2 ELSE 1 END), 0)
+                                FROM comment_reactions WHERE comment_id = root.comment_id
+                            )::BIGINT AS score
+                        FROM comments root
+                        WHERE article_id = $1 AND parent_comment_id IS NULL AND pinned_at IS NULL
+                    )
+                    SELECT comment_id, created_at, score AS "score!"
+                    FROM roots
+                    WHERE
+                        $2::TIMESTAMPTZ IS NULL OR
+                        (score, created_at, comment_id) < ($4::BIGINT, $2, $3::UUID)
+                    ORDER BY score DESC, created_at DESC, comment_id DESC
+                    LIMIT $5
+                    "#,
+                    article_id,
+                    created_at,
+                    comment_id,
+                    cursor.map(|c| c.score),
+                    limit,
+                )
+                .fetch_all(&ctx.db)
+                .await?
+            }
+        };
+        Ok(rows)
+    }
+}
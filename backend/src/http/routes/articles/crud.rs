@@ -1,16 +1,22 @@
-use super::{Article, ArticlePayload, Author};
+use super::{
+    Article, ArticleAuthor, ArticlePayload, Author, AuthorRole, mentions, related, stats, tags,
+};
 use crate::http::errors::ResultExt as _;
 use crate::http::errors::{Error, Validation};
-use crate::http::extractors::MaybeUserID;
 use crate::http::extractors::UserID;
+use crate::http::extractors::{ClientIp, MaybeUserID};
 use crate::http::routes::users;
+use crate::http::routes::users::notifications;
 use crate::http::utils;
+use crate::services::analytics::Signal;
+use crate::services::events::Topic;
 use crate::state::AppContext;
 use axum::Json;
-use axum::extract::{Path, State, rejection::JsonRejection};
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use std::sync::Arc;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 use validator_derive::Validate;
 
@@ -93,17 +99,18 @@ pub async fn create_article(
 
     let slug = slug::slugify(&article.title);
 
-    // Realworld end-to-end test suite expects tags to be sorted
-    article.tags.sort();
+    article.tags = tags::canonicalize(&ctx.db, &article.tags, "tags").await?;
 
+    let mut tx = ctx.db.begin().await?;
     let details = sqlx::query!(
         r#"
         WITH article as (
             INSERT INTO "articles" (user_id, slug, title, description, body, tags)
             VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING user_id, created_at, updated_at
+            RETURNING article_id, user_id, created_at, updated_at
         )
         SELECT
+            article.article_id as article_id,
             article.created_at as article_created_at,
             article.updated_at as article_updated_at,
             author.username as author_username,
@@ -118,33 +125,74 @@ pub async fn create_article(
         article.body,
         &article.tags,
     )
-    .fetch_one(&ctx.db)
+    .fetch_one(&mut *tx)
     .await
     .on_constraint("articles_slug_key", |_| {
         Error::unprocessable_entity([("title", "article with this title already exists")])
     })?;
+    mentions::sync(&mut tx, &details.article_id, None, &id, &article.body).await?;
+    tx.commit().await?;
+
+    notifications::publish(&ctx, &id).await;
+    let followers = sqlx::query_scalar!(
+        "SELECT following_user_id FROM follows WHERE followed_user_id = $1",
+        &*id,
+    )
+    .fetch_all(&ctx.db)
+    .await?;
 
+    let image = users::utils::parse_image_url(details.author_image.as_deref())?;
     let payload = ArticlePayload {
         article: Article {
             slug,
             title: article.title,
             body: article.body,
+            body_html: None,
+            toc: None,
             description: article.description,
             tags: article.tags,
+            snippet: None,
             created_at: details.article_created_at,
             updated_at: details
                 .article_updated_at
                 .unwrap_or(details.article_created_at),
             favorited: false,
             favorited_count: 0,
+            bookmarked: false,
+            comments_count: 0,
+            comments_locked: false,
             author: Author {
-                bio: details.author_bio,
-                image: users::utils::parse_image_url(details.author_image.as_deref())?,
-                username: details.author_username,
+                bio: details.author_bio.clone(),
+                image: image.clone(),
+                username: details.author_username.clone(),
                 following: false,
             },
+            // co-authors can only be invited once the article is there
+            authors: vec![ArticleAuthor {
+                profile: Author {
+                    bio: details.author_bio,
+                    image,
+                    username: details.author_username,
+                    following: false,
+                },
+                role: AuthorRole::Owner,
+            }],
+            series: None,
         },
     };
+    let topics: Vec<_> = followers.into_iter().map(Topic::User).collect();
+    if let Err(e) = ctx.events.publish(&topics, "article", &payload).await {
+        warn!(error = ?e, "failed to publish article");
+    }
+    // there might be lots of followers to email
+    let article_id = details.article_id;
+    let notifier_ctx = Arc::clone(&ctx.0);
+    tokio::spawn(async move {
+        let db = &notifier_ctx.db;
+        if let Err(e) = notifier_ctx.notifier.announce_article(db, article_id).await {
+            warn!(error = ?e, "failed to email article's announcement");
+        }
+    });
     Ok((StatusCode::CREATED, Json(payload)))
 }
 
@@ -198,6 +246,13 @@ pub struct ArticleUpdate {
     ))]
     #[serde(rename = "tagList")]
     tags: Option<Vec<String>>,
+
+    /// Whether to lock the article's comments.
+    ///
+    /// Once locked, only the article's authors can leave comments.
+    #[schema(nullable = false)]
+    #[serde(rename = "commentsLocked")]
+    comments_locked: Option<bool>,
 }
 
 /// Update article.
@@ -211,7 +266,8 @@ pub struct ArticleUpdate {
 /// a patch operation).
 //
 /// Just like for all other mutation endpoints, authentication is required.
-/// Moreover, only the article's author can perform this action.
+/// Moreover, only the article's owner or editors (see `invite_author`) can
+/// perform this action.
 #[utoipa::path(
     put,
     path = "/{slug}",
@@ -250,11 +306,11 @@ pub async fn update_article(
 
     let new_slug = patch.title.as_deref().map(slug::slugify);
 
-    // Realworld end-to-end test suite expects tags to be sorted
-    if let Some(tags) = patch.tags.as_mut() {
-        tags.sort();
+    if let Some(new_tags) = patch.tags.take() {
+        patch.tags = Some(tags::canonicalize(&ctx.db, &new_tags, "tags").await?);
     }
 
+    let mut tx = ctx.db.begin().await?;
     let details = sqlx::query!(
         r#"
         WITH updated_article as (
@@ -264,13 +320,29 @@ pub async fn update_article(
                 title = COALESCE($4, title),
                 description = COALESCE($5, description),
                 body = COALESCE($6, body),
-                tags = COALESCE($7, tags)
-            WHERE slug = $1 AND user_id = $2
-            RETURNING slug
+                tags = COALESCE($7, tags),
+                comments_locked = COALESCE($8, comments_locked)
+            WHERE
+                slug = $1 AND
+                (
+                    user_id = $2 OR
+                    EXISTS(
+                        SELECT 1 FROM article_authors
+                        WHERE
+                            article_id = articles.article_id AND
+                            user_id = $2 AND
+                            accepted_at IS NOT NULL
+                    )
+                )
+            RETURNING article_id, slug, user_id
         )
         SELECT
             EXISTS(SELECT article_id FROM articles WHERE slug = $1) "existed!",
-            (SELECT slug FROM updated_article) "new_slug";
+            -- the statement sees the article as it was before the update
+            (SELECT tags FROM articles WHERE slug = $1) "old_tags",
+            (SELECT article_id FROM updated_article) "updated_article_id",
+            (SELECT slug FROM updated_article) "new_slug",
+            (SELECT user_id FROM updated_article) "author_id";
         "#,
         slug,
         *uid,
@@ -278,16 +350,33 @@ pub async fn update_article(
         patch.title,
         patch.description,
         patch.body,
-        patch.tags.as_deref()
+        patch.tags.as_deref(),
+        patch.comments_locked,
     )
-    .fetch_one(&ctx.db)
+    .fetch_one(&mut *tx)
     .await
     .on_constraint("articles_slug_key", |_| {
         Error::unprocessable_entity([("title", "article with this title already exists")])
     })?;
 
-    if let Some(slug) = details.new_slug {
-        let article = db::read_article(&ctx, &slug, Some(&uid)).await?;
+    if let (Some(article_id), Some(slug), Some(author_id)) = (
+        details.updated_article_id,
+        details.new_slug,
+        details.author_id,
+    ) {
+        if let Some(ref body) = patch.body {
+            mentions::sync(&mut tx, &article_id, None, &author_id, body).await?;
+        }
+        tx.commit().await?;
+        // mentions are from the article's author, even if edited by a co-author
+        notifications::publish(&ctx, &author_id).await;
+
+        let (article_id, article) = db::read_article(&ctx, &slug, Some(&uid)).await?;
+        let tags = [details.old_tags.unwrap_or_default(), article.tags.clone()].concat();
+        if let Err(e) = related::invalidate(&ctx, &article_id, &tags).await {
+            // the update has been saved, and the stale lists will expire anyway
+            warn!(error = ?e, "failed to invalidate related articles");
+        }
         return Ok(Json(ArticlePayload { article }));
     }
 
@@ -301,6 +390,23 @@ pub async fn update_article(
 }
 
 // --------------------------------- READ -------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum RenderFormat {
+    Html,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct ReadQuery {
+    /// Render article's body in the specified format.
+    ///
+    /// With `html`, the response will additionally contain `bodyHtml`
+    /// (sanitized HTML) and `toc` (table of contents).
+    #[param(nullable = false, example = "html")]
+    render: Option<RenderFormat>,
+}
+
 /// Read article by slug.
 ///
 /// This will fetch an article by its unique slug identifier.
@@ -318,6 +424,7 @@ pub async fn update_article(
             description = "Article slug identifier",
             example = "how-to-design-a-programming-language"
         ),
+        ReadQuery,
     ),
     responses(
         (status = 200, description = "Article successfully retrieved", body = ArticlePayload<Article>),
@@ -334,10 +441,29 @@ pub async fn update_article(
 pub async fn read_article(
     ctx: State<Arc<AppContext>>,
     Path(slug): Path<String>,
+    q: Result<Query<ReadQuery>, QueryRejection>,
     uid: MaybeUserID,
+    ip: ClientIp,
 ) -> Result<Json<ArticlePayload<Article>>, Error> {
+    let Query(q) = q?;
     let uid = uid.0.as_deref();
-    let article = db::read_article(&ctx, &slug, uid).await?;
+    let (article_id, mut article) = db::read_article(&ctx, &slug, uid).await?;
+    article.series = db::read_article_series(&ctx, article_id).await?;
+    // losing a view is not a reason to fail (or delay) the request
+    let (analytics_ctx, visitor) = (Arc::clone(&ctx.0), uid.copied());
+    tokio::spawn(async move {
+        let uid = visitor.as_ref();
+        if let Err(e) =
+            stats::record_signal(&analytics_ctx, Signal::View, article_id, uid, ip).await
+        {
+            warn!(error = ?e, "failed to record article view");
+        }
+    });
+    if let Some(RenderFormat::Html) = q.render {
+        let rendered = utils::render_markdown(&ctx, &article.body).await?;
+        article.body_html = Some(rendered.html);
+        article.toc = Some(rendered.toc);
+    }
     Ok(Json(ArticlePayload { article }))
 }
 
@@ -345,7 +471,8 @@ pub async fn read_article(
 /// Delete article by slug.
 ///
 /// This will delete the article with the specified unique slug identifier.
-/// Authentication _is_ required to delete articles.
+/// Authentication _is_ required to delete articles, and only the article's
+/// owner can delete it (editors cannot).
 #[utoipa::path(
     delete,
     path = "/{slug}",
@@ -433,7 +560,7 @@ pub async fn favorite_article(
     Path(slug): Path<String>,
     uid: UserID,
 ) -> Result<Json<ArticlePayload<Article>>, Error> {
-    let _article_id = sqlx::query_scalar!(
+    sqlx::query_scalar!(
         r#"
         WITH
             existing_article AS (SELECT article_id FROM articles WHERE slug = $1),
@@ -455,7 +582,9 @@ pub async fn favorite_article(
     // and login which should make things more clear
     .on_constraint("favorites_user_id_fkey", |_| Error::Unauthorized)?
     .ok_or(Error::NotFound)?;
-    let article = db::read_article(&ctx, &slug, Some(&uid)).await?;
+    // authors get notified, see `notify_on_favorite` trigger
+    notifications::publish(&ctx, &uid).await;
+    let (_, article) = db::read_article(&ctx, &slug, Some(&uid)).await?;
     Ok(Json(ArticlePayload { article }))
 }
 
@@ -519,16 +648,122 @@ pub async fn unfavorite_article(
     // and login which should make things more clear
     .on_constraint("favorites_user_id_fkey", |_| Error::Unauthorized)?
     .ok_or(Error::NotFound)?;
-    let article = db::read_article(&ctx, &slug, Some(&uid)).await?;
+    let (_, article) = db::read_article(&ctx, &slug, Some(&uid)).await?;
+    Ok(Json(ArticlePayload { article }))
+}
+
+// ------------------------ BOOKMARK / UNBOOKMARK -----------------------------
+/// Bookmark article.
+///
+/// Add the article to the current user's reading list, see `bookmarked_articles`.
+/// Unlike favorites, bookmarks are private and never visible to other users.
+///
+/// This operation is idempotent: if this user already bookmarked the article
+/// in question, a successful response will be returned.
+#[utoipa::path(
+    post,
+    path = "/{slug}/bookmark",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "how-to-design-a-programming-language"
+        ),
+    ),
+    responses(
+        (status = 200, description = "Article successfully bookmarked", body = ArticlePayload<Article>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 404, description = "Article not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "BOOKMARK ARTICLE", skip(ctx))]
+pub async fn bookmark_article(
+    ctx: State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    uid: UserID,
+) -> Result<Json<ArticlePayload<Article>>, Error> {
+    let _article_id = sqlx::query_scalar!(
+        r#"
+        WITH
+            existing_article AS (SELECT article_id FROM articles WHERE slug = $1),
+            _bookmark_action AS (
+                INSERT INTO bookmarks (article_id, user_id)
+                SELECT article_id, $2 FROM existing_article
+                ON CONFLICT DO NOTHING
+            )
+        SELECT article_id FROM existing_article
+        "#,
+        slug,
+        *uid
+    )
+    .fetch_optional(&ctx.db)
+    .await
+    // see the same edge-case in `favorite_article`
+    .on_constraint("bookmarks_user_id_fkey", |_| Error::Unauthorized)?
+    .ok_or(Error::NotFound)?;
+    let (_, article) = db::read_article(&ctx, &slug, Some(&uid)).await?;
+    Ok(Json(ArticlePayload { article }))
+}
+
+/// Remove bookmark.
+///
+/// Remove the article from the current user's reading list.
+///
+/// Similar to the `bookmark` endpoint, this operation is idempotent.
+#[utoipa::path(
+    delete,
+    path = "/{slug}/bookmark",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "how-to-design-a-programming-language"
+        ),
+    ),
+    responses(
+        (status = 200, description = "Bookmark successfully removed", body = ArticlePayload<Article>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 404, description = "Article not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "UNBOOKMARK ARTICLE", skip(ctx))]
+pub async fn unbookmark_article(
+    ctx: State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    uid: UserID,
+) -> Result<Json<ArticlePayload<Article>>, Error> {
+    let _article_id = sqlx::query_scalar!(
+        r#"
+        WITH
+            existing_article AS (SELECT article_id FROM articles WHERE slug = $1),
+            _unbookmark_action AS (
+                DELETE FROM bookmarks
+                WHERE article_id = (SELECT article_id FROM existing_article) AND user_id = $2
+            )
+        SELECT article_id FROM existing_article
+        "#,
+        slug,
+        *uid
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+    let (_, article) = db::read_article(&ctx, &slug, Some(&uid)).await?;
     Ok(Json(ArticlePayload { article }))
 }
 
-mod db {
+pub(super) mod db {
     use crate::AppContext;
     use crate::http::errors::Error;
-    use crate::http::routes::articles::Article;
-    use crate::http::routes::articles::Author;
-    use crate::http::routes::users::utils as users_utils;
+    use crate::http::routes::articles::{Article, ArticleLink, ArticleRow, ArticleSeries};
     use uuid::Uuid;
 
     #[instrument(name = "FETCH ARTICLE FROM DATABASE", skip(ctx))]
@@ -536,15 +771,18 @@ mod db {
         ctx: &AppContext,
         slug: &str,
         user_id: Option<&Uuid>,
-    ) -> Result<Article, Error> {
-        let details = sqlx::query!(
+    ) -> Result<(Uuid, Article), Error> {
+        let details = sqlx::query_as!(
+            ArticleRow,
             r#"
             SELECT
+                article.article_id,
                 article.slug,
                 article.title,
                 article.description,
-                article.body,
+                article.body AS "body?",
                 article.tags,
+                NULL::TEXT AS "snippet?",
                 article.created_at,
                 article.updated_at,
                 (
@@ -554,18 +792,28 @@ mod db {
                         WHERE article_id = article.article_id AND user_id = $2::UUID
                     )
                 ) AS "favorited!",
-                (SELECT COUNT(*) FROM favorites WHERE article_id = article.article_id) AS favorited_count,
+                article.favorites_count,
+                (
+                    $2::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM bookmarks
+                        WHERE article_id = article.article_id AND user_id = $2::UUID
+                    )
+                ) AS "bookmarked!",
+                article.comments_count,
+                article.comments_locked,
                 author.username AS author_username,
                 author.bio AS author_bio,
                 author.image AS author_image,
                 (
-                    $2::UUID IS NOT NULL AND EXISTS
-                    (
+                    $2::UUID IS NOT NULL AND
+                    EXISTS(
                         SELECT 1 FROM follows
-                        WHERE followed_user_id = author.user_id
-                        AND following_user_id = $2
+                        WHERE followed_user_id = author.user_id AND following_user_id = $2::UUID
                     )
-                ) AS "author_following!"
+                ) AS "author_following!",
+                article_authors_json(article.article_id, $2::UUID) AS "authors!: _",
+                NULL::BIGINT AS "total?"
             FROM "articles" article
             JOIN "users" author USING (user_id)
             WHERE slug = $1;
@@ -576,23 +824,70 @@ mod db {
         .fetch_optional(&ctx.db)
         .await?
         .ok_or(Error::NotFound)?;
+        Ok((details.article_id, details.try_into()?))
+    }
 
-        Ok(Article {
+    /// Fetch the article's place in the series, if the article is part of any.
+    #[instrument(name = "FETCH ARTICLE'S SERIES FROM DATABASE", skip(ctx))]
+    pub async fn read_article_series(
+        ctx: &AppContext,
+        article_id: Uuid,
+    ) -> Result<Option<ArticleSeries>, Error> {
+        let details = sqlx::query!(
+            r#"
+            SELECT
+                series.slug,
+                series.title,
+                (
+                    SELECT count(*) FROM series_articles
+                    WHERE series_id = entry.series_id AND position <= entry.position
+                ) AS "position!",
+                (
+                    SELECT count(*) FROM series_articles
+                    WHERE series_id = entry.series_id
+                ) AS "articles_count!",
+                previous.slug AS "previous_slug?",
+                previous.title AS "previous_title?",
+                next.slug AS "next_slug?",
+                next.title AS "next_title?"
+            FROM
+                series_articles entry
+                    JOIN series USING (series_id)
+                    LEFT JOIN LATERAL (
+                        SELECT slug, title
+                        FROM series_articles JOIN articles USING (article_id)
+                        WHERE series_id = entry.series_id AND position < entry.position
+                        ORDER BY position DESC
+                        LIMIT 1
+                    ) previous ON TRUE
+                    LEFT JOIN LATERAL (
+                        SELECT slug, title
+                        FROM series_articles JOIN articles USING (article_id)
+                        WHERE series_id = entry.series_id AND position > entry.position
+                        ORDER BY position ASC
+                        LIMIT 1
+                    ) next ON TRUE
+            WHERE entry.article_id = $1
+            "#,
+            article_id
+        )
+        .fetch_optional(&ctx.db)
+        .await?;
+
+        let series = details.map(|details| ArticleSeries {
             slug: details.slug,
             title: details.title,
-            body: details.body,
-            description: details.description,
-            tags: details.tags,
-            created_at: details.created_at,
-            updated_at: details.updated_at.unwrap_or(details.created_at),
-            favorited: details.favorited,
-            favorited_count: details.favorited_count.unwrap_or_default() as usize,
-            author: Author {
-                bio: details.author_bio,
-                image: users_utils::parse_image_url(details.author_image.as_deref())?,
-                username: details.author_username,
-                following: details.author_following,
-            },
-        })
+            position: details.position as usize,
+            articles_count: details.articles_count as usize,
+            previous: details
+                .previous_slug
+                .zip(details.previous_title)
+                .map(|(slug, title)| ArticleLink { slug, title }),
+            next: details
+                .next_slug
+                .zip(details.next_title)
+                .map(|(slug, title)| ArticleLink { slug, title }),
+        });
+        Ok(series)
     }
 }
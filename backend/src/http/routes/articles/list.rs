@@ -1,12 +1,18 @@
-use super::Article;
-use crate::http::errors::Error;
+use super::{Article, tags};
+use crate::http::errors::{Error, Validation};
 use crate::http::extractors::{MaybeUserID, UserID};
+use crate::http::utils;
 use crate::state::AppContext;
 use axum::Json;
 use axum::extract::rejection::QueryRejection;
 use axum::extract::{Query, State};
+use chrono::{DateTime, Utc};
+use serde_with::formats::CommaSeparator;
+use serde_with::{StringWithSeparator, serde_as};
+use sqlx::PgPool;
 use std::sync::Arc;
 use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 use validator::Validate;
 use validator_derive::Validate;
 
@@ -23,31 +29,307 @@ pub(crate) struct ArticlesList {
     #[serde(rename = "articlesCount")]
     #[schema(examples(1))]
     count: usize,
+
+    /// Cursor to fetch the next (older) page of articles, if any.
+    #[schema(
+        required = true,
+        example = "eyJ0IjoiMjAyNS0xMC0xOVQwNjoyODowMFoiLCJpZCI6IjEyM2U0NTY3LWU4OWItMTJkMy1hNDU2LTQyNjYxNDE3NDAwMCJ9"
+    )]
+    next_cursor: Option<String>,
+
+    /// Cursor to fetch the previous (more recent) page of articles, if any.
+    #[schema(required = true)]
+    prev_cursor: Option<String>,
 }
 
+/// Articles list sort order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ArticlesSort {
+    /// Most recently published articles first.
+    #[default]
+    Recent,
+
+    /// Most favorited articles first.
+    Favorites,
+
+    /// Most commented articles first.
+    Comments,
+
+    /// Most recently updated articles first.
+    Updated,
+}
+
+impl ArticlesSort {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Recent => "recent",
+            Self::Favorites => "favorites",
+            Self::Comments => "comments",
+            Self::Updated => "updated",
+        }
+    }
+}
+
+/// How to match articles against multiple tags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TagMatch {
+    /// Article should have all of the tags.
+    #[default]
+    All,
+
+    /// Article should have at least one of the tags.
+    Any,
+}
+
+#[serde_as]
 #[derive(Debug, Deserialize, ToSchema, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
 #[into_params(parameter_in = Query)]
 pub(crate) struct ListQuery {
     /// Filter articles by tag.
-    #[param(nullable = false, example = "Compilers")]
-    tag: Option<String>,
+    ///
+    /// Comma-separated list of tags, see also `tagMatch`.
+    #[serde(default)]
+    #[serde_as(as = "StringWithSeparator<CommaSeparator, String>")]
+    #[param(value_type = Option<String>, nullable = false, example = "Compilers,Rust")]
+    tag: Vec<String>,
+
+    /// Whether articles should have all or any of the tags in `tag` filter.
+    #[param(inline, nullable = false, default = "all")]
+    tag_match: Option<TagMatch>,
 
     /// Filter articles by author (username).
-    #[param(nullable = false, example = "timClicks")]
-    author: Option<String>,
+    ///
+    /// Comma-separated list of usernames, articles by any of these authors
+    /// will be returned.
+    #[serde(default)]
+    #[serde_as(as = "StringWithSeparator<CommaSeparator, String>")]
+    #[param(value_type = Option<String>, nullable = false, example = "timClicks,rob.pike")]
+    author: Vec<String>,
 
     /// Filter articles favorited by user (username).
     #[param(nullable = false, example = "rob.pike")]
     favorited: Option<String>,
 
+    /// Filter articles by series (series' slug).
+    #[param(nullable = false, example = "how-to-train-your-dragon")]
+    series: Option<String>,
+
+    /// Exclude articles having any of these tags.
+    ///
+    /// Comma-separated list of tags.
+    #[serde(default)]
+    #[serde_as(as = "StringWithSeparator<CommaSeparator, String>")]
+    #[param(value_type = Option<String>, nullable = false, example = "Politics")]
+    exclude_tag: Vec<String>,
+
+    /// Exclude articles by any of these authors.
+    ///
+    /// Comma-separated list of usernames.
+    #[serde(default)]
+    #[serde_as(as = "StringWithSeparator<CommaSeparator, String>")]
+    #[param(value_type = Option<String>, nullable = false, example = "troll42")]
+    exclude_author: Vec<String>,
+
+    /// Only articles published at or after this moment.
+    #[param(nullable = false, example = "2025-01-01T00:00:00Z")]
+    since: Option<DateTime<Utc>>,
+
+    /// Only articles published before this moment.
+    #[param(nullable = false, example = "2026-01-01T00:00:00Z")]
+    until: Option<DateTime<Utc>>,
+
+    /// Sort order.
+    #[param(inline, nullable = false, default = "recent")]
+    sort: Option<ArticlesSort>,
+
     /// Limit number of returned articles.
     #[param(nullable = false, default = 20, maximum = 1000)]
     #[validate(range(max = 1000, message = "limit too large"))]
     limit: Option<usize>,
 
     /// Offset/skip number of articles.
+    ///
+    /// Cannot be combined with `cursor`.
     #[param(nullable = false, default = 0)]
     offset: Option<usize>,
+
+    /// Cursor to fetch articles starting from.
+    ///
+    /// Use `nextCursor` or `prevCursor` from a previously returned page.
+    /// Unlike with `offset`, pages fetched with a cursor are stable: articles
+    /// published in the meantime will not cause entries to be skipped or
+    /// returned twice. Only supported with the `recent` sort order.
+    #[param(nullable = false)]
+    cursor: Option<String>,
+}
+
+/// Position in articles list (which is sorted by creation date).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArticlesCursor {
+    #[serde(rename = "t")]
+    created_at: DateTime<Utc>,
+
+    #[serde(rename = "id")]
+    article_id: Uuid,
+
+    /// Whether we are fetching the page _preceding_ this position.
+    #[serde(rename = "b", default)]
+    backwards: bool,
+}
+
+/// Article's position in a list, see `Pagination::paginate`.
+///
+/// This is the article's creation date, unless the list is sorted otherwise
+/// (e.g. the reading list is sorted by when the articles have been bookmarked).
+#[derive(Debug)]
+struct Position {
+    article_id: Uuid,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct Pagination {
+    cursor: Option<ArticlesCursor>,
+    sort: ArticlesSort,
+    offset: usize,
+    limit: usize,
+}
+
+impl ListQuery {
+    /// Validate the query, including the checks spanning multiple fields.
+    fn check(&self) -> Result<(), Error> {
+        self.validate()?;
+        if let (Some(since), Some(until)) = (self.since, self.until)
+            && since >= until
+        {
+            return Err(Error::unprocessable_entity([(
+                "until",
+                "until should be later than since",
+            )]));
+        }
+        Ok(())
+    }
+
+    /// Normalize the tags filters, see `tags::canonicalize`.
+    async fn canonicalize_tags(&mut self, db: &PgPool) -> Result<(), Error> {
+        self.tag = tags::canonicalize(db, &self.tag, "tag").await?;
+        self.exclude_tag = tags::canonicalize(db, &self.exclude_tag, "excludeTag").await?;
+        Ok(())
+    }
+
+    fn match_any_tag(&self) -> bool {
+        self.tag_match == Some(TagMatch::Any)
+    }
+
+    fn pagination(&self) -> Result<Pagination, Error> {
+        Pagination::try_new(
+            self.cursor.as_deref(),
+            self.sort.unwrap_or_default(),
+            self.offset,
+            self.limit,
+        )
+    }
+}
+
+impl Pagination {
+    fn try_new(
+        cursor: Option<&str>,
+        sort: ArticlesSort,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Self, Error> {
+        let cursor = cursor
+            .map(utils::decode_cursor::<ArticlesCursor>)
+            .transpose()?;
+        if cursor.is_some() && offset.is_some() {
+            return Err(Error::unprocessable_entity([(
+                "offset",
+                "offset cannot be combined with cursor",
+            )]));
+        }
+        if cursor.is_some() && sort != ArticlesSort::Recent {
+            return Err(Error::unprocessable_entity([(
+                "cursor",
+                "cursor is only supported with the recent sort order",
+            )]));
+        }
+        Ok(Pagination {
+            cursor,
+            sort,
+            offset: offset.unwrap_or(DEFAULT_OFFSET),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
+        })
+    }
+
+    fn cursor_created_at(&self) -> Option<DateTime<Utc>> {
+        self.cursor.as_ref().map(|c| c.created_at)
+    }
+
+    fn cursor_article_id(&self) -> Option<Uuid> {
+        self.cursor.as_ref().map(|c| c.article_id)
+    }
+
+    fn backwards(&self) -> bool {
+        self.cursor.as_ref().is_some_and(|c| c.backwards)
+    }
+
+    /// Number of rows to fetch from the database.
+    ///
+    /// We are fetching one extra article to learn if there is another page.
+    fn fetch_limit(&self) -> i64 {
+        self.limit as i64 + 1
+    }
+
+    /// Trim the extra position and issue cursors to the adjacent pages.
+    ///
+    /// Expects `rows` to be sorted from the most recent to the oldest, which
+    /// is what we return to the caller regardless of the direction we are
+    /// paginating in. No cursors are issued unless articles are sorted by
+    /// creation date.
+    ///
+    /// This is to be done before the articles themselves are fetched, so that
+    /// an article deleted in the meantime does not affect the pagination.
+    fn paginate(&self, mut rows: Vec<Position>) -> (Vec<Position>, Option<String>, Option<String>) {
+        let has_more = rows.len() > self.limit;
+        if has_more {
+            // when paginating backwards, the rows closest to the cursor
+            // are the oldest ones, and so the extra row is the most recent one
+            if self.backwards() {
+                rows.remove(0);
+            } else {
+                rows.truncate(self.limit);
+            }
+        }
+        let cursor = |position: &Position, backwards| {
+            utils::encode_cursor(&ArticlesCursor {
+                created_at: position.created_at,
+                article_id: position.article_id,
+                backwards,
+            })
+        };
+        let (next, prev) = if self.sort != ArticlesSort::Recent {
+            (None, None)
+        } else if self.backwards() {
+            (
+                rows.last().map(|row| cursor(row, false)),
+                rows.first()
+                    .filter(|_| has_more)
+                    .map(|row| cursor(row, true)),
+            )
+        } else {
+            let seeked = self.cursor.is_some() || self.offset > 0;
+            (
+                rows.last()
+                    .filter(|_| has_more)
+                    .map(|row| cursor(row, false)),
+                rows.first().filter(|_| seeked).map(|row| cursor(row, true)),
+            )
+        };
+        (rows, next, prev)
+    }
 }
 
 /// List articles.
@@ -55,6 +337,10 @@ pub(crate) struct ListQuery {
 /// Authentication is _optional_, but needed to learn if, for each article,
 /// the article has been favorited (a.k.a. liked) by the user, or whether
 /// the user is following the article's author.
+///
+/// By default, articles are sorted by creation date, the most recent ones
+/// first, see `sort` for other options. Both `offset` and `cursor` based
+/// pagination is supported, see `nextCursor` and `prevCursor` in the response.
 #[utoipa::path(
     get,
     path = "",
@@ -83,22 +369,61 @@ pub async fn list_articles(
     q: Result<Query<ListQuery>, QueryRejection>,
     uid: MaybeUserID,
 ) -> Result<Json<ArticlesList>, Error> {
-    let Query(q) = q?;
-    q.validate()?;
-    let payload = db::fetch_general_feed(&ctx.db, &q, uid.0.as_deref()).await?;
+    let Query(mut q) = q?;
+    q.check()?;
+    q.canonicalize_tags(&ctx.db).await?;
+    let payload = db::fetch_feed(&ctx.db, &q, None, uid.0.as_deref()).await?;
     Ok(Json(payload))
 }
 
+/// Which articles to include into the personal feed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FeedMode {
+    /// Articles by the users the current user is following.
+    #[default]
+    Authors,
+
+    /// Articles having any of the tags the current user is following
+    /// (except for the user's own articles).
+    Tags,
+
+    /// Articles either by the followed users or having the followed tags.
+    All,
+}
+
+impl FeedMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Authors => "authors",
+            Self::Tags => "tags",
+            Self::All => "all",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct FeedQuery {
+    /// Which articles to include into the feed.
+    #[param(inline, nullable = false, default = "authors")]
+    mode: Option<FeedMode>,
+}
+
 /// Personal feed.
 ///
 /// Similar to the `list_articles` operation, but will return only articles
 /// authored by users the current (calling) user is following. Hence, authentication
 /// is required.
+///
+/// Articles having the tags the user is following can be included into
+/// the feed as well, see `mode`. An article matching both a followed author
+/// and a followed tag is only listed once.
 #[utoipa::path(
     get,
     path = "/feed",
     tags = ["Articles"],
-    params(ListQuery),
+    params(FeedQuery, ListQuery),
     responses(
         (status = 200, description = "Articles list successfully retrieved", body = ArticlesList),
         (status = 401, description = "Token missing or invalid"),
@@ -112,75 +437,560 @@ pub async fn list_articles(
 #[instrument(name = "PERSONAL FEED", skip_all)]
 pub async fn personal_feed(
     ctx: State<Arc<AppContext>>,
+    // see `search_articles` on why we are extracting from the query twice
+    feed: Result<Query<FeedQuery>, QueryRejection>,
     q: Result<Query<ListQuery>, QueryRejection>,
     uid: UserID,
+) -> Result<Json<ArticlesList>, Error> {
+    let (Query(feed), Query(mut q)) = (feed?, q?);
+    q.check()?;
+    q.canonicalize_tags(&ctx.db).await?;
+    let mode = feed.mode.unwrap_or_default();
+    let payload = db::fetch_feed(&ctx.db, &q, Some((mode, &uid)), Some(&uid)).await?;
+    Ok(Json(payload))
+}
+
+#[derive(Debug, Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct SearchQuery {
+    /// Search query.
+    ///
+    /// Supports web search engines' syntax: "quoted text" to search for
+    /// a phrase, `or` to search for either of the words, and `-` to exclude
+    /// articles containing a word.
+    #[param(example = "memory safety -garbage")]
+    #[validate(length(
+        min = 1,
+        max = 200,
+        message = "search query should be at least 1 and at max 200 characters long"
+    ))]
+    q: String,
+}
+
+/// Search articles.
+///
+/// Will perform full-text search over articles' titles, descriptions, and
+/// contents, returning the most relevant articles first (matches in title are
+/// ranked higher than those in description, which in their turn rank higher
+/// than matches in the article's body). Each article in the list will contain
+/// a `snippet` with the matching fragments highlighted.
+///
+/// Search can be combined with the same filters that the `list_articles`
+/// operation supports. Authentication is _optional_.
+#[utoipa::path(
+    get,
+    path = "/search",
+    tags = ["Articles"],
+    params(SearchQuery, ListQuery),
+    responses(
+        (status = 200, description = "Articles list successfully retrieved", body = ArticlesList),
+        (status = 401, description = "Token missing or invalid (in case authenicated access has been used)"),
+        (status = 422, description = "Missing or invalid search query", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        (),
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "SEARCH ARTICLES", skip_all)]
+pub async fn search_articles(
+    ctx: State<Arc<AppContext>>,
+    // both extractors are deserializing the same query string, so that the
+    // filters available for search are always the ones of `list_articles`
+    search: Result<Query<SearchQuery>, QueryRejection>,
+    q: Result<Query<ListQuery>, QueryRejection>,
+    uid: MaybeUserID,
+) -> Result<Json<ArticlesList>, Error> {
+    let (Query(search), Query(mut q)) = (search?, q?);
+    search.validate()?;
+    q.check()?;
+    if q.cursor.is_some() {
+        return Err(Error::unprocessable_entity([(
+            "cursor",
+            "search results are ranked by relevance, use offset instead",
+        )]));
+    }
+    if q.sort.is_some() {
+        return Err(Error::unprocessable_entity([(
+            "sort",
+            "search results are ranked by relevance",
+        )]));
+    }
+    q.canonicalize_tags(&ctx.db).await?;
+    let payload = db::search_articles(&ctx.db, &search.q, &q, uid.0.as_deref()).await?;
+    Ok(Json(payload))
+}
+
+/// Time window to rank articles' activity within.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TrendingWindow {
+    /// Last 24 hours.
+    Day,
+
+    /// Last 7 days.
+    #[default]
+    Week,
+
+    /// Last 30 days.
+    Month,
+}
+
+impl TrendingWindow {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct TrendingQuery {
+    /// Time window to rank articles' activity within.
+    #[param(inline, nullable = false, default = "week")]
+    window: Option<TrendingWindow>,
+
+    /// Only return trending articles having this tag.
+    #[param(nullable = false, example = "Compilers")]
+    tag: Option<String>,
+
+    /// Limit number of returned articles.
+    #[param(nullable = false, default = 20, maximum = 100)]
+    #[validate(range(max = 100, message = "limit too large"))]
+    limit: Option<usize>,
+
+    /// Offset/skip number of articles.
+    #[param(nullable = false, default = 0)]
+    offset: Option<usize>,
+}
+
+/// Trending articles.
+///
+/// Articles that gained the most traction (comments, favorites and, to
+/// a much lesser extent, views) within the given time window, with the more
+/// recent activity weighing more. Rankings are re-computed periodically
+/// in background, and so a freshly favorited article might not be immediately
+/// reflected here.
+///
+/// Authentication is _optional_.
+#[utoipa::path(
+    get,
+    path = "/trending",
+    tags = ["Articles"],
+    params(TrendingQuery),
+    responses(
+        (status = 200, description = "Articles list successfully retrieved", body = ArticlesList),
+        (status = 401, description = "Token missing or invalid (in case authenicated access has been used)"),
+        (status = 422, description = "Invalid query", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        (),
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "TRENDING ARTICLES", skip_all)]
+pub async fn trending_articles(
+    ctx: State<Arc<AppContext>>,
+    q: Result<Query<TrendingQuery>, QueryRejection>,
+    uid: MaybeUserID,
+) -> Result<Json<ArticlesList>, Error> {
+    let Query(mut q) = q?;
+    q.validate()?;
+    if let Some(tag) = q.tag.take() {
+        q.tag = tags::canonicalize(&ctx.db, &[tag], "tag").await?.pop();
+    }
+    let payload = db::fetch_trending(&ctx.db, &q, uid.0.as_deref()).await?;
+    Ok(Json(payload))
+}
+
+#[derive(Debug, Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct BookmarksQuery {
+    /// Limit number of returned articles.
+    #[param(nullable = false, default = 20, maximum = 1000)]
+    #[validate(range(max = 1000, message = "limit too large"))]
+    limit: Option<usize>,
+
+    /// Offset/skip number of articles.
+    ///
+    /// Cannot be combined with `cursor`.
+    #[param(nullable = false, default = 0)]
+    offset: Option<usize>,
+
+    /// Cursor to fetch articles starting from.
+    ///
+    /// Use `nextCursor` or `prevCursor` from a previously returned page.
+    #[param(nullable = false)]
+    cursor: Option<String>,
+}
+
+/// Reading list.
+///
+/// Articles bookmarked by the current user, the most recently bookmarked
+/// articles first. Just like with `list_articles`, both `offset` and `cursor`
+/// based pagination is supported. Bookmarks are private, and so this is only
+/// ever available to the user themselves.
+#[utoipa::path(
+    get,
+    path = "/bookmarks",
+    tags = ["Users"],
+    params(BookmarksQuery),
+    responses(
+        (status = 200, description = "Articles list successfully retrieved", body = ArticlesList),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 422, description = "Invalid query", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "LIST BOOKMARKS", skip_all)]
+pub async fn bookmarked_articles(
+    ctx: State<Arc<AppContext>>,
+    q: Result<Query<BookmarksQuery>, QueryRejection>,
+    uid: UserID,
 ) -> Result<Json<ArticlesList>, Error> {
     let Query(q) = q?;
     q.validate()?;
-    let payload = db::fetch_personal_feed(&ctx.db, &q, &uid).await?;
+    let page = Pagination::try_new(q.cursor.as_deref(), ArticlesSort::Recent, q.offset, q.limit)?;
+    let payload = db::fetch_bookmarks(&ctx.db, &page, &uid).await?;
     Ok(Json(payload))
 }
 
 mod db {
-    use super::{ArticlesList, ListQuery};
+    use super::{
+        ArticlesList, ArticlesSort, FeedMode, ListQuery, Pagination, Position, TrendingQuery,
+    };
     use super::{DEFAULT_LIMIT, DEFAULT_OFFSET};
     use crate::http::errors::Error;
-    use crate::http::routes::articles::{Article, Author};
-    use crate::http::routes::users::utils::parse_image_url;
+    use crate::http::routes::articles::{Article, ArticleRow};
     use sqlx::PgPool;
+    use std::collections::HashMap;
     use uuid::Uuid;
 
-    pub async fn fetch_general_feed(
+    /// Articles list, either the general one or the user's personal feed.
+    ///
+    /// The articles are selected from `listed_articles`, which is where
+    /// the filters of `ListQuery` (and the personal feed) are defined.
+    pub async fn fetch_feed(
         pg_pool: &PgPool,
         q: &ListQuery,
+        feed: Option<(FeedMode, &Uuid)>,
         uid: Option<&Uuid>,
     ) -> Result<ArticlesList, Error> {
-        let resp = sqlx::query!(
-        r#"
-        SELECT
-            coalesce(count(*) OVER(), 0) "count!",
-            article.slug,
-            article.title,
-            article.description,
-            article.tags,
-            article.created_at,
-            article.updated_at,
-            (
-                $6::UUID IS NOT NULL AND
-                EXISTS(
-                    SELECT 1 FROM favorites
-                    WHERE article_id = article.article_id AND user_id = $6::UUID
-                )
-            ) AS "favorited!",
-            (SELECT COUNT(*) FROM favorites WHERE article_id = article.article_id) AS favorited_count,
-            author.username as "author_username",
-            author.bio as "author_bio",
-            author.image as "author_image"
-        FROM
-            "articles" article JOIN "users" author USING (user_id)
-        WHERE
-            ($1::text IS NULL OR author.username = $1::text) AND
-            ($2::text IS NULL OR article.tags @> ARRAY[$2::text]) AND
-            (
-                $3::text IS NULL OR
-                EXISTS(
-                    SELECT 1 FROM favorites fav JOIN users USING (user_id)
-                    WHERE fav.article_id = article.article_id AND username = $3
-                )
+        let page = q.pagination()?;
+        // the page and the number of matching articles are fetched separately,
+        // so that seeking with a cursor does not need to scan and sort all of
+        // the articles matching the query;
+        //
+        // with the default isolation level `READ COMMITTED`, an article can get
+        // published in between the two queries, and so the count can be off by
+        // a few articles; we are allowing this as not that likely to happen
+        let (positions, count) = tokio::try_join!(
+            fetch_feed_page(pg_pool, q, feed, &page),
+            count_feed(pg_pool, q, feed),
+        )?;
+        let (positions, next_cursor, prev_cursor) = page.paginate(positions);
+        let articles = fetch_articles(pg_pool, positions, uid).await?;
+        Ok(ArticlesList {
+            articles,
+            count: count as usize,
+            next_cursor,
+            prev_cursor,
+        })
+    }
+
+    async fn fetch_feed_page(
+        pg_pool: &PgPool,
+        q: &ListQuery,
+        feed: Option<(FeedMode, &Uuid)>,
+        page: &Pagination,
+    ) -> Result<Vec<Position>, Error> {
+        let (mode, following) = feed.unzip();
+        // each order gets its own statement, so that the planner can seek
+        // the `articles_created_at_idx` index when listing recent articles
+        let positions = if page.sort != ArticlesSort::Recent {
+            sqlx::query_as!(
+                Position,
+                r#"
+                SELECT article.article_id AS "article_id!", article.created_at AS "created_at!"
+                FROM listed_articles($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) article
+                ORDER BY
+                    CASE $12::TEXT
+                        WHEN 'favorites' THEN article.favorites_count
+                        WHEN 'comments' THEN article.comments_count
+                    END DESC NULLS LAST,
+                    CASE WHEN $12 = 'updated' THEN coalesce(article.updated_at, article.created_at) END DESC,
+                    article.created_at DESC,
+                    article.article_id DESC
+                OFFSET $13
+                LIMIT $14
+                "#,
+                &q.author,
+                &q.tag,
+                q.match_any_tag(),
+                q.favorited,
+                q.series,
+                &q.exclude_tag,
+                &q.exclude_author,
+                q.since,
+                q.until,
+                following,
+                mode.map(|mode| mode.as_str()),
+                page.sort.as_str(),
+                page.offset as i64,
+                page.fetch_limit(),
             )
-        ORDER BY article.created_at DESC
-        OFFSET $4
-        LIMIT $5
-    "#,
-        q.author,
-        q.tag,
-        q.favorited,
-        q.offset.unwrap_or(DEFAULT_OFFSET) as i64,
-        q.limit.unwrap_or(DEFAULT_LIMIT) as i64,
-        uid,
-    )
-    .fetch_all(pg_pool)
-    .await?;
+            .fetch_all(pg_pool)
+            .await?
+        } else if page.backwards() {
+            let mut positions = sqlx::query_as!(
+                Position,
+                r#"
+                SELECT article.article_id AS "article_id!", article.created_at AS "created_at!"
+                FROM listed_articles($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) article
+                WHERE (article.created_at, article.article_id) > ($12::TIMESTAMPTZ, $13::UUID)
+                ORDER BY article.created_at ASC, article.article_id ASC
+                LIMIT $14
+                "#,
+                &q.author,
+                &q.tag,
+                q.match_any_tag(),
+                q.favorited,
+                q.series,
+                &q.exclude_tag,
+                &q.exclude_author,
+                q.since,
+                q.until,
+                following,
+                mode.map(|mode| mode.as_str()),
+                page.cursor_created_at(),
+                page.cursor_article_id(),
+                page.fetch_limit(),
+            )
+            .fetch_all(pg_pool)
+            .await?;
+            positions.reverse();
+            positions
+        } else {
+            // with no cursor, we are seeking past the "infinitely recent" article
+            sqlx::query_as!(
+                Position,
+                r#"
+                SELECT article.article_id AS "article_id!", article.created_at AS "created_at!"
+                FROM listed_articles($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) article
+                WHERE
+                    (article.created_at, article.article_id) < (
+                        coalesce($12::TIMESTAMPTZ, 'infinity'),
+                        coalesce($13::UUID, 'ffffffff-ffff-ffff-ffff-ffffffffffff')
+                    )
+                ORDER BY article.created_at DESC, article.article_id DESC
+                OFFSET $14
+                LIMIT $15
+                "#,
+                &q.author,
+                &q.tag,
+                q.match_any_tag(),
+                q.favorited,
+                q.series,
+                &q.exclude_tag,
+                &q.exclude_author,
+                q.since,
+                q.until,
+                following,
+                mode.map(|mode| mode.as_str()),
+                page.cursor_created_at(),
+                page.cursor_article_id(),
+                page.offset as i64,
+                page.fetch_limit(),
+            )
+            .fetch_all(pg_pool)
+            .await?
+        };
+        Ok(positions)
+    }
+
+    async fn count_feed(
+        pg_pool: &PgPool,
+        q: &ListQuery,
+        feed: Option<(FeedMode, &Uuid)>,
+    ) -> Result<i64, Error> {
+        let (mode, following) = feed.unzip();
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT coalesce(count(*), 0) "count!"
+            FROM listed_articles($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+            &q.author,
+            &q.tag,
+            q.match_any_tag(),
+            q.favorited,
+            q.series,
+            &q.exclude_tag,
+            &q.exclude_author,
+            q.since,
+            q.until,
+            following,
+            mode.map(|mode| mode.as_str()),
+        )
+        .fetch_one(pg_pool)
+        .await?;
+        Ok(count)
+    }
+
+    /// Fetch articles at the given `positions`, keeping the order.
+    async fn fetch_articles(
+        pg_pool: &PgPool,
+        positions: Vec<Position>,
+        uid: Option<&Uuid>,
+    ) -> Result<Vec<Article>, Error> {
+        let article_ids: Vec<_> = positions.iter().map(|p| p.article_id).collect();
+        let mut articles: HashMap<_, _> = sqlx::query_as!(
+            ArticleRow,
+            r#"
+            SELECT
+                article.article_id,
+                article.slug,
+                article.title,
+                article.description,
+                NULL::TEXT AS "body?",
+                article.tags,
+                NULL::TEXT AS "snippet?",
+                article.created_at,
+                article.updated_at,
+                (
+                    $2::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM favorites
+                        WHERE article_id = article.article_id AND user_id = $2::UUID
+                    )
+                ) AS "favorited!",
+                article.favorites_count,
+                (
+                    $2::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM bookmarks
+                        WHERE article_id = article.article_id AND user_id = $2::UUID
+                    )
+                ) AS "bookmarked!",
+                article.comments_count,
+                article.comments_locked,
+                author.username AS author_username,
+                author.bio AS author_bio,
+                author.image AS author_image,
+                (
+                    $2::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM follows
+                        WHERE followed_user_id = author.user_id AND following_user_id = $2::UUID
+                    )
+                ) AS "author_following!",
+                article_authors_json(article.article_id, $2::UUID) AS "authors!: _",
+                NULL::BIGINT AS "total?"
+            FROM "articles" article JOIN "users" author USING (user_id)
+            WHERE article.article_id = ANY($1)
+            "#,
+            &article_ids,
+            uid,
+        )
+        .fetch_all(pg_pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.article_id, row))
+        .collect();
+
+        positions
+            .into_iter()
+            // the article might have been deleted in the meantime
+            .filter_map(|position| articles.remove(&position.article_id))
+            .map(Article::try_from)
+            .collect()
+    }
+
+    pub async fn search_articles(
+        pg_pool: &PgPool,
+        search: &str,
+        q: &ListQuery,
+        uid: Option<&Uuid>,
+    ) -> Result<ArticlesList, Error> {
+        let resp = sqlx::query_as!(
+            ArticleRow,
+            r#"
+            SELECT
+                -- the columns are not known to be non-null when selected
+                -- from a function, hence the overrides
+                article.article_id AS "article_id!",
+                article.slug AS "slug!",
+                article.title AS "title!",
+                article.description AS "description!",
+                NULL::TEXT AS "body?",
+                article.tags AS "tags!",
+                ts_headline(
+                    'english',
+                    article.body,
+                    query,
+                    'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=30, MinWords=10'
+                ) AS "snippet?",
+                article.created_at AS "created_at!",
+                article.updated_at,
+                (
+                    $6::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM favorites
+                        WHERE article_id = article.article_id AND user_id = $6::UUID
+                    )
+                ) AS "favorited!",
+                article.favorites_count AS "favorites_count!",
+                (
+                    $6::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM bookmarks
+                        WHERE article_id = article.article_id AND user_id = $6::UUID
+                    )
+                ) AS "bookmarked!",
+                article.comments_count AS "comments_count!",
+                article.comments_locked AS "comments_locked!",
+                author.username AS author_username,
+                author.bio AS author_bio,
+                author.image AS author_image,
+                (
+                    $6::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM follows
+                        WHERE followed_user_id = author.user_id AND following_user_id = $6::UUID
+                    )
+                ) AS "author_following!",
+                article_authors_json(article.article_id, $6::UUID) AS "authors!: _",
+                count(*) OVER() AS "total?"
+            FROM
+                listed_articles($1, $2, $8, $3, $13, $9, $10, $11, $12, NULL, NULL) article
+                    JOIN "users" author USING (user_id),
+                websearch_to_tsquery('english', $7) query
+            WHERE article.search @@ query
+            ORDER BY ts_rank_cd(article.search, query) DESC, article.created_at DESC
+            OFFSET $4
+            LIMIT $5
+            "#,
+            &q.author,
+            &q.tag,
+            q.favorited,
+            q.offset.unwrap_or(DEFAULT_OFFSET) as i64,
+            q.limit.unwrap_or(DEFAULT_LIMIT) as i64,
+            uid,
+            search,
+            q.match_any_tag(),
+            &q.exclude_tag,
+            &q.exclude_author,
+            q.since,
+            q.until,
+            q.series,
+        )
+        .fetch_all(pg_pool)
+        .await?;
 
         let payload = if resp.is_empty() {
             // no rows can mean that:
@@ -190,190 +1000,240 @@ mod db {
             //  - `offset` greater than or equal to articles count or ...
             //  - `limit` is set to 0
             //  we are doing an extra db call without limit and offset
-            //  to get the count of articles matching the filter;
-            //
-            //  there is a chance that an article matching query gets inserted
-            //  and committed into the table after the initial query and prior to
-            //  the following one, but with the default isolation level `READ COMMITTED`,
-            //  even if we create a transaction ...
-            //  ```
-            //  let tx = ctx.db.begin().await?;
-            //  ```
-            //  ... and use the transaction as query executor we still can hit
-            //  this corner case where we are returning 0 articles but also saying
-            //  that there is, say, 1 article matching the query; we are allowing
-            //  this case as not that likely to actually happen, but should keep it mind
+            //  to get the count of articles matching the filter (which is
+            //  subject to the same race as described in `fetch_feed`)
             let count = sqlx::query_scalar!(
                 r#"
-            SELECT
-                coalesce(count(*), 0) "count!"
-            FROM
-                articles JOIN users USING (user_id)
-            WHERE
-                ($1::text IS NULL OR username = $1::text) AND
-                ($2::text IS NULL OR tags @> ARRAY[$2::text]) AND
-                ($3::text IS NULL OR article_id IN (
-                    SELECT article_id FROM favorites fav JOIN users USING (user_id)
-                    WHERE fav.article_id = article_id AND username = $3
-                )
-            )
-            "#,
-                q.author,
-                q.tag,
+                SELECT
+                    coalesce(count(*), 0) "count!"
+                FROM
+                    listed_articles($1, $2, $3, $4, $5, $6, $7, $8, $9, NULL, NULL) article,
+                    websearch_to_tsquery('english', $10) query
+                WHERE article.search @@ query
+                "#,
+                &q.author,
+                &q.tag,
+                q.match_any_tag(),
                 q.favorited,
+                q.series,
+                &q.exclude_tag,
+                &q.exclude_author,
+                q.since,
+                q.until,
+                search,
             )
             .fetch_one(pg_pool)
             .await?;
             ArticlesList {
                 articles: vec![],
                 count: count as usize,
+                next_cursor: None,
+                prev_cursor: None,
             }
         } else {
-            let count = resp[0].count as usize;
-            let mut articles = Vec::with_capacity(resp.len());
-            for item in resp {
-                let article = Article {
-                    slug: item.slug,
-                    title: item.title,
-                    // as per the spec, to get the article's body, they need to query
-                    // a dedicated endpoint (`/api/articles/{slug}`); we could also
-                    // create a dedicated struct (say, ArticleListItem) omiting
-                    // the `body` field to not confuse out API user, but since
-                    // we are currently the only consumer of this API and provided
-                    // we have this documented via Open API UI (Scalar), it's fine
-                    // to re-use the "core" articles endpoints struct
-                    body: String::default(),
-                    description: item.description,
-                    tags: item.tags,
-                    created_at: item.created_at,
-                    updated_at: item.updated_at.unwrap_or(item.created_at),
-                    favorited: item.favorited,
-                    favorited_count: item.favorited_count.unwrap_or_default() as usize,
-                    author: {
-                        Author {
-                            username: item.author_username,
-                            bio: item.author_bio,
-                            image: parse_image_url(item.author_image.as_deref())?,
-                            // TODO: update once the follow/unfollow logic is there
-                            following: false,
-                        }
-                    },
-                };
-                articles.push(article);
+            let count = resp[0].total.unwrap_or_default() as usize;
+            let articles = resp
+                .into_iter()
+                .map(Article::try_from)
+                .collect::<Result<Vec<_>, _>>()?;
+            ArticlesList {
+                articles,
+                count,
+                next_cursor: None,
+                prev_cursor: None,
             }
-            ArticlesList { articles, count }
         };
         Ok(payload)
     }
 
-    pub async fn fetch_personal_feed(
+    pub async fn fetch_trending(
         pg_pool: &PgPool,
-        q: &ListQuery,
-        uid: &Uuid,
+        q: &TrendingQuery,
+        uid: Option<&Uuid>,
     ) -> Result<ArticlesList, Error> {
-        let resp = sqlx::query!(
-        r#"
-        SELECT
-            coalesce(count(*) OVER(), 0) "count!",
-            article.slug,
-            article.title,
-            article.description,
-            article.tags,
-            article.created_at,
-            article.updated_at,
-            EXISTS(
-                SELECT 1 FROM favorites
-                WHERE article_id = article.article_id AND user_id = $6::UUID
-            ) AS "favorited!",
-            (SELECT COUNT(*) FROM favorites WHERE article_id = article.article_id) AS favorited_count,
-            author.username AS author_username,
-            author.bio AS author_bio,
-            author.image AS author_image
-        FROM
-            "articles" article
-                JOIN "follows" ON user_id = followed_user_id
-                JOIN "users" author USING (user_id)
-        WHERE
-            following_user_id = $6::UUID AND
-            ($1::text IS NULL OR author.username = $1::text) AND
-            ($2::text IS NULL OR article.tags @> ARRAY[$2::text]) AND
-            (
-                $3::text IS NULL OR
-                EXISTS(
-                    SELECT 1 FROM favorites fav JOIN users USING (user_id)
-                    WHERE fav.article_id = article.article_id AND username = $3
-                )
-            )
-        ORDER BY article.created_at DESC
-        OFFSET $4
-        LIMIT $5
-    "#,
-        q.author,
-        q.tag,
-        q.favorited,
-        q.offset.unwrap_or(DEFAULT_OFFSET) as i64,
-        q.limit.unwrap_or(DEFAULT_LIMIT) as i64,
-        uid,
-    )
-    .fetch_all(pg_pool)
-    .await?;
+        let window = q.window.unwrap_or_default();
+        let resp = sqlx::query_as!(
+            ArticleRow,
+            r#"
+            SELECT
+                article.article_id,
+                article.slug,
+                article.title,
+                article.description,
+                NULL::TEXT AS "body?",
+                article.tags,
+                NULL::TEXT AS "snippet?",
+                article.created_at,
+                article.updated_at,
+                (
+                    $5::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM favorites
+                        WHERE article_id = article.article_id AND user_id = $5::UUID
+                    )
+                ) AS "favorited!",
+                article.favorites_count,
+                (
+                    $5::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM bookmarks
+                        WHERE article_id = article.article_id AND user_id = $5::UUID
+                    )
+                ) AS "bookmarked!",
+                article.comments_count,
+                article.comments_locked,
+                author.username AS author_username,
+                author.bio AS author_bio,
+                author.image AS author_image,
+                (
+                    $5::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM follows
+                        WHERE followed_user_id = author.user_id AND following_user_id = $5::UUID
+                    )
+                ) AS "author_following!",
+                article_authors_json(article.article_id, $5::UUID) AS "authors!: _",
+                count(*) OVER() AS "total?"
+            FROM
+                "trending_articles" trending
+                    JOIN "articles" article USING (article_id)
+                    JOIN "users" author USING (user_id)
+            WHERE
+                trending.period = $1 AND
+                ($2::TEXT IS NULL OR article.tags @> ARRAY[$2::TEXT])
+            ORDER BY trending.score DESC, article.created_at DESC
+            OFFSET $3
+            LIMIT $4
+            "#,
+            window.as_str(),
+            q.tag,
+            q.offset.unwrap_or(DEFAULT_OFFSET) as i64,
+            q.limit.unwrap_or(DEFAULT_LIMIT) as i64,
+            uid,
+        )
+        .fetch_all(pg_pool)
+        .await?;
 
         let payload = if resp.is_empty() {
+            // see the comment in `search_articles` on why we are doing
+            // an extra call here
             let count = sqlx::query_scalar!(
                 r#"
                 SELECT
                     coalesce(count(*), 0) "count!"
                 FROM
-                    articles
-                        JOIN follows ON user_id = followed_user_id
-                        JOIN users USING (user_id)
+                    trending_articles JOIN articles USING (article_id)
                 WHERE
-                    following_user_id = $4::UUID AND
-                    ($1::text IS NULL OR username = $1::text) AND
-                    ($2::text IS NULL OR tags @> ARRAY[$2::text]) AND
-                    ($3::text IS NULL OR article_id IN (
-                        SELECT article_id FROM favorites fav JOIN users USING (user_id)
-                        WHERE fav.article_id = article_id AND username = $3)
-                    )
-            "#,
-                q.author,
+                    period = $1 AND
+                    ($2::TEXT IS NULL OR tags @> ARRAY[$2::TEXT])
+                "#,
+                window.as_str(),
                 q.tag,
-                q.favorited,
-                uid,
             )
             .fetch_one(pg_pool)
             .await?;
             ArticlesList {
                 articles: vec![],
                 count: count as usize,
+                next_cursor: None,
+                prev_cursor: None,
             }
         } else {
-            let count = resp[0].count as usize;
-            let mut articles = Vec::with_capacity(resp.len());
-            for item in resp {
-                let article = Article {
-                    slug: item.slug,
-                    title: item.title,
-                    body: String::default(),
-                    description: item.description,
-                    tags: item.tags,
-                    created_at: item.created_at,
-                    updated_at: item.updated_at.unwrap_or(item.created_at),
-                    favorited: item.favorited,
-                    favorited_count: item.favorited_count.unwrap_or_default() as usize,
-                    author: {
-                        Author {
-                            username: item.author_username,
-                            bio: item.author_bio,
-                            image: parse_image_url(item.author_image.as_deref())?,
-                            following: true,
-                        }
-                    },
-                };
-                articles.push(article);
+            let count = resp[0].total.unwrap_or_default() as usize;
+            let articles = resp
+                .into_iter()
+                .map(Article::try_from)
+                .collect::<Result<Vec<_>, _>>()?;
+            ArticlesList {
+                articles,
+                count,
+                next_cursor: None,
+                prev_cursor: None,
             }
-            ArticlesList { articles, count }
         };
         Ok(payload)
     }
+
+    pub async fn fetch_bookmarks(
+        pg_pool: &PgPool,
+        page: &Pagination,
+        uid: &Uuid,
+    ) -> Result<ArticlesList, Error> {
+        // the list is sorted by when the articles have been bookmarked, and so
+        // we are paginating over bookmarks first, and only then fetching the
+        // articles themselves; see `fetch_feed` on why the count is separate
+        let (positions, count) = tokio::try_join!(
+            fetch_bookmarks_page(pg_pool, page, uid),
+            count_bookmarks(pg_pool, uid),
+        )?;
+        let (positions, next_cursor, prev_cursor) = page.paginate(positions);
+        let articles = fetch_articles(pg_pool, positions, Some(uid)).await?;
+        Ok(ArticlesList {
+            articles,
+            count: count as usize,
+            next_cursor,
+            prev_cursor,
+        })
+    }
+
+    async fn fetch_bookmarks_page(
+        pg_pool: &PgPool,
+        page: &Pagination,
+        uid: &Uuid,
+    ) -> Result<Vec<Position>, Error> {
+        // see `fetch_feed_page` on why the statements are separate
+        let positions = if page.backwards() {
+            let mut positions = sqlx::query_as!(
+                Position,
+                r#"
+                SELECT article_id, created_at FROM bookmarks
+                WHERE user_id = $1 AND (created_at, article_id) > ($2::TIMESTAMPTZ, $3::UUID)
+                ORDER BY created_at ASC, article_id ASC
+                LIMIT $4
+                "#,
+                uid,
+                page.cursor_created_at(),
+                page.cursor_article_id(),
+                page.fetch_limit(),
+            )
+            .fetch_all(pg_pool)
+            .await?;
+            positions.reverse();
+            positions
+        } else {
+            sqlx::query_as!(
+                Position,
+                r#"
+                SELECT article_id, created_at FROM bookmarks
+                WHERE
+                    user_id = $1 AND
+                    (created_at, article_id) < (
+                        coalesce($2::TIMESTAMPTZ, 'infinity'),
+                        coalesce($3::UUID, 'ffffffff-ffff-ffff-ffff-ffffffffffff')
+                    )
+                ORDER BY created_at DESC, article_id DESC
+                OFFSET $4
+                LIMIT $5
+                "#,
+                uid,
+                page.cursor_created_at(),
+                page.cursor_article_id(),
+                page.offset as i64,
+                page.fetch_limit(),
+            )
+            .fetch_all(pg_pool)
+            .await?
+        };
+        Ok(positions)
+    }
+
+    async fn count_bookmarks(pg_pool: &PgPool, uid: &Uuid) -> Result<i64, Error> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT coalesce(count(*), 0) AS "count!" FROM bookmarks WHERE user_id = $1"#,
+            uid
+        )
+        .fetch_one(pg_pool)
+        .await?;
+        Ok(count)
+    }
 }
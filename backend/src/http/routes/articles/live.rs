@@ -0,0 +1,203 @@
+use crate::http::errors::Error;
+use crate::http::extractors::{ACCESS_TOKEN_PROTOCOL, SocketUserID};
+use crate::services::events::{Event, PRESENCE_TTL, Topic};
+use crate::state::AppContext;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::response::Response;
+use futures_util::{SinkExt as _, StreamExt as _};
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{Instant, interval_at, timeout};
+use uuid::Uuid;
+
+/// How many messages may be waiting to be sent to a client.
+///
+/// A client which is not keeping up with the discussion, i.e. has got this
+/// many messages pending, is disconnected (and is free to reconnect).
+const OUTBOX_CAPACITY: usize = 64;
+
+/// For how long sending a message to a client may take.
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Clients are not expected to send anything but control frames.
+const MAX_INCOMING_MESSAGE_SIZE: usize = 1024;
+
+/// Payload of the `presence` event.
+#[derive(Debug, Serialize, Deserialize)]
+struct PresenceEvent {
+    /// How many users are currently in the article's live channel.
+    readers: u64,
+}
+
+/// Join article's live discussion.
+///
+/// Upgrades the connection to WebSocket, over which the server is sending
+/// JSON messages with `id`, `name`, and `data` fields (just like the events
+/// of the `/api/stream` endpoint), namely:
+///
+/// - `presence` events with the number of users currently reading the article
+///   (`readers`), sent as users join and leave;
+/// - `comment` events with the new comments under the article.
+///
+/// A client which is too slow to keep up with the messages gets disconnected.
+///
+/// Authentication required. Since browsers cannot set the `Authorization`
+/// header when opening a WebSocket, the token can instead be offered as
+/// a subprotocol following the `access_token` one, i.e.
+/// `new WebSocket(url, ["access_token", token])`.
+#[utoipa::path(
+    get,
+    path = "/{slug}/live",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "why-memory-safety-matters",
+        ),
+    ),
+    responses(
+        (status = 101, description = "Switching to WebSocket protocol"),
+        (status = 400, description = "Not a WebSocket upgrade request"),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 404, description = "Article not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "JOIN LIVE ARTICLE", skip(ctx, ws))]
+pub async fn live_article(
+    State(ctx): State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    uid: SocketUserID,
+    ws: WebSocketUpgrade,
+) -> Result<Response, Error> {
+    let article_id = sqlx::query_scalar!("SELECT article_id FROM articles WHERE slug = $1", slug)
+        .fetch_optional(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let user_id = uid.0;
+    let response = ws
+        .max_message_size(MAX_INCOMING_MESSAGE_SIZE)
+        // selected only if offered, see `SocketUserID`
+        .protocols([ACCESS_TOKEN_PROTOCOL])
+        .on_upgrade(move |socket| serve(ctx, socket, article_id, user_id));
+    Ok(response)
+}
+
+/// Relay the article's events to the client for as long as they are connected.
+async fn serve(ctx: Arc<AppContext>, socket: WebSocket, article_id: Uuid, user_id: Uuid) {
+    let connection_id = Uuid::new_v4();
+    let topics = [Topic::Article(article_id), Topic::Presence(article_id)];
+    let events = match ctx.events.subscribe(&topics, None).await {
+        Ok(events) => events,
+        Err(e) => {
+            error!(error = ?e, "failed to subscribe to article's events");
+            return;
+        }
+    };
+    let mut events = pin!(events);
+
+    // messages are sent from a separate task, and so a client taking their
+    // time to receive them does not hold up reading the events (and does not
+    // make them pile up in the app instance's fan-out), see `OUTBOX_CAPACITY`
+    let (mut sender, mut receiver) = socket.split();
+    let (outbox, mut pending) = mpsc::channel::<Message>(OUTBOX_CAPACITY);
+    let writer = tokio::spawn(async move {
+        while let Some(message) = pending.recv().await {
+            if !matches!(
+                timeout(SEND_TIMEOUT, sender.send(message)).await,
+                Ok(Ok(()))
+            ) {
+                return;
+            }
+        }
+        let _ = sender.close().await;
+    });
+
+    let mut readers = None;
+    update_presence(&ctx, article_id, user_id, connection_id, true, &mut readers).await;
+    let refresh_period = PRESENCE_TTL / 3;
+    let mut refresh = interval_at(Instant::now() + refresh_period, refresh_period);
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else {
+                    warn!("article's events subscription terminated");
+                    break;
+                };
+                if event.name == "presence" {
+                    readers = serde_json::from_value::<PresenceEvent>(event.data.clone())
+                        .ok()
+                        .map(|presence| presence.readers);
+                }
+                if outbox.try_send(to_message(&event)).is_err() {
+                    warn!("disconnecting client lagging behind article's events");
+                    break;
+                }
+            }
+            message = receiver.next() => match message {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                // pings are answered automatically
+                Some(Ok(_)) => {}
+            },
+            _ = refresh.tick() => {
+                update_presence(&ctx, article_id, user_id, connection_id, true, &mut readers).await;
+            }
+        }
+    }
+    drop(outbox);
+    writer.abort();
+    update_presence(
+        &ctx,
+        article_id,
+        user_id,
+        connection_id,
+        false,
+        &mut readers,
+    )
+    .await;
+}
+
+/// Enter (or refresh) or leave the article's live channel, letting everyone
+/// there know the number of `readers` if it has changed.
+async fn update_presence(
+    ctx: &AppContext,
+    article_id: Uuid,
+    user_id: Uuid,
+    connection_id: Uuid,
+    entering: bool,
+    readers: &mut Option<u64>,
+) {
+    let topic = Topic::Article(article_id);
+    let present = if entering {
+        ctx.events.enter(topic, user_id, connection_id).await
+    } else {
+        ctx.events.leave(topic, user_id, connection_id).await
+    };
+    let present = match present {
+        Ok(present) => present,
+        Err(e) => {
+            warn!(error = ?e, "failed to update presence");
+            return;
+        }
+    };
+    if *readers == Some(present) {
+        return;
+    }
+    *readers = Some(present);
+    let event = PresenceEvent { readers: present };
+    let topics = [Topic::Presence(article_id)];
+    if let Err(e) = ctx.events.announce(&topics, "presence", &event).await {
+        warn!(error = ?e, "failed to publish presence");
+    }
+}
+
+fn to_message(event: &Event) -> Message {
+    let event = serde_json::to_string(event).expect("event to be serializable");
+    Message::Text(event.into())
+}
@@ -0,0 +1,61 @@
+use crate::http::errors::Error;
+use crate::services::moderator::utils::parse_mentions;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Store users mentioned in the article's (or the comment's) `content`.
+///
+/// Users mentioned for the first time in this article (or comment) are
+/// notified, so that editing the text does not notify them over again.
+/// The author mentioning themselves is not a mention, while the users who
+/// have blocked the author are not notified.
+///
+/// Note that `author_id` is the text's author, and not the user editing it
+/// (e.g. the article's co-author), since that is who the mention is from.
+///
+/// This is to be done in the same transaction the text is stored in, while
+/// the notifications are to be published once the transaction is committed,
+/// see `notifications::publish`.
+pub(super) async fn sync(
+    tx: &mut Transaction<'_, Postgres>,
+    article_id: &Uuid,
+    comment_id: Option<&Uuid>,
+    author_id: &Uuid,
+    content: &str,
+) -> Result<(), Error> {
+    let usernames = parse_mentions(content);
+    sqlx::query!(
+        r#"
+        WITH
+            mentioned AS (
+                SELECT user_id FROM users WHERE username = ANY($4) AND user_id <> $3
+            ),
+            _mentions_deleted AS (
+                DELETE FROM mentions
+                WHERE
+                    article_id = $1 AND
+                    comment_id IS NOT DISTINCT FROM $2 AND
+                    user_id NOT IN (SELECT user_id FROM mentioned)
+            ),
+            mentions_added AS (
+                INSERT INTO mentions (user_id, article_id, comment_id)
+                SELECT user_id, $1, $2 FROM mentioned
+                ON CONFLICT DO NOTHING
+                RETURNING user_id
+            )
+        SELECT notify(user_id, $3, 'mention', $1, $2)
+        FROM mentions_added mentioned
+        WHERE NOT EXISTS(
+            SELECT 1 FROM blocks
+            WHERE blocking_user_id = mentioned.user_id AND blocked_user_id = $3
+        )
+        "#,
+        article_id,
+        comment_id,
+        author_id,
+        &usernames,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(())
+}
@@ -1,14 +1,26 @@
 use crate::AppContext;
-use chrono::{DateTime, Utc};
+use crate::http::errors::Error;
+use crate::http::routes::users::utils::parse_image_url;
+use crate::services::renderer::{self, TocEntry};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 use std::sync::Arc;
 use url::Url;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use utoipa_axum::router::OpenApiRouter;
+use uuid::Uuid;
+use validator_derive::Validate;
 
+mod authors;
 mod comments;
 mod crud;
 mod list;
+mod live;
+mod mentions;
+mod related;
+mod series;
+mod stats;
 mod tags;
 
 // ---------------------------- SHARED TYPES -----------------------------------
@@ -65,6 +77,23 @@ pub(crate) struct Article {
     )]
     body: String,
 
+    /// Article's contents rendered as sanitized HTML.
+    ///
+    /// Only present when requested with `render=html`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(
+        nullable = false,
+        example = "<h2><a href=\"#intro\" aria-hidden=\"true\" class=\"anchor\" id=\"intro\"></a>Intro</h2>"
+    )]
+    body_html: Option<String>,
+
+    /// Article's table of contents (headings of the article's body).
+    ///
+    /// Only present when requested with `render=html`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    toc: Option<Vec<TocEntry>>,
+
     /// Tags.
     #[schema(
         example = json!(vec!["programming".to_string(), "language design".to_string()]),
@@ -73,6 +102,17 @@ pub(crate) struct Article {
     #[serde(rename = "tagList")]
     tags: Vec<String>,
 
+    /// Fragments of the article matching the search query.
+    ///
+    /// Matching words are wrapped with `<mark>` tags, any other markup
+    /// is escaped. Only present in search results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(
+        nullable = false,
+        example = "Before we begin ... <mark>memory</mark> <mark>safety</mark> matters"
+    )]
+    snippet: Option<String>,
+
     /// When this article was created.
     created_at: DateTime<Utc>,
 
@@ -86,8 +126,263 @@ pub(crate) struct Article {
     #[serde(rename = "favoritesCount")]
     favorited_count: usize,
 
+    /// If this article is in the current user's reading list.
+    ///
+    /// Unlike favorites, bookmarks are private to the user.
+    bookmarked: bool,
+
+    /// How many comments have been left under this article.
+    comments_count: usize,
+
+    /// Whether the article's authors closed the discussion.
+    ///
+    /// Only the article's authors can comment on an article with comments locked.
+    comments_locked: bool,
+
     /// The article's author details.
+    ///
+    /// This is the article's owner, see `authors` for all of the article's
+    /// authors (including co-authors).
     author: Author,
+
+    /// All of the article's authors, the owner first.
+    authors: Vec<ArticleAuthor>,
+
+    /// Series this article is part of (if any).
+    ///
+    /// Only present when reading a single article.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    series: Option<ArticleSeries>,
+}
+
+/// Article's author role.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum AuthorRole {
+    /// Created the article, can update and delete it, and invite editors.
+    Owner,
+
+    /// Co-author who accepted the owner's invitation, can update the article.
+    Editor,
+}
+
+impl AuthorRole {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Owner => "owner",
+            Self::Editor => "editor",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct ArticleAuthor {
+    #[serde(flatten)]
+    profile: Author,
+
+    /// Author's role.
+    role: AuthorRole,
+}
+
+/// Article's author as fetched from the database.
+#[derive(Debug, Deserialize)]
+struct ArticleAuthorRow {
+    username: String,
+    bio: String,
+    image: Option<String>,
+    role: AuthorRole,
+    following: bool,
+}
+
+impl TryFrom<ArticleAuthorRow> for ArticleAuthor {
+    type Error = Error;
+
+    fn try_from(row: ArticleAuthorRow) -> Result<Self, Self::Error> {
+        Ok(ArticleAuthor {
+            profile: Author {
+                username: row.username,
+                bio: row.bio,
+                image: parse_image_url(row.image.as_deref())?,
+                following: row.following,
+            },
+            role: row.role,
+        })
+    }
+}
+
+/// Link to an article.
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct ArticleLink {
+    /// Article's slug.
+    #[schema(example = "how-to-train-your-dragon-part-2", format = "slug")]
+    slug: String,
+
+    /// Article's title.
+    #[schema(example = "How to train your dragon. Part 2")]
+    title: String,
+}
+
+/// Article's place in the series it is part of.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArticleSeries {
+    /// Series' slug.
+    #[schema(example = "how-to-train-your-dragon", format = "slug")]
+    slug: String,
+
+    /// Series' title.
+    #[schema(example = "How to train your dragon")]
+    title: String,
+
+    /// Article's position in the series (starting with 1).
+    #[schema(example = 2)]
+    position: usize,
+
+    /// Number of articles in the series.
+    #[schema(example = 3)]
+    articles_count: usize,
+
+    /// Previous article in the series, if any.
+    #[schema(required = true)]
+    previous: Option<ArticleLink>,
+
+    /// Next article in the series, if any.
+    #[schema(required = true)]
+    next: Option<ArticleLink>,
+}
+
+/// Article (alongside its author) as fetched from the database.
+///
+/// All the queries returning articles select these very columns, so that
+/// they can be converted into [`Article`] the same way. Listings select
+/// `NULL` as `body` and only search does select `snippet`.
+struct ArticleRow {
+    article_id: Uuid,
+    slug: String,
+    title: String,
+    description: String,
+    body: Option<String>,
+    tags: Vec<String>,
+    snippet: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: Option<DateTime<Utc>>,
+    favorited: bool,
+    favorites_count: i32,
+    bookmarked: bool,
+    comments_count: i32,
+    comments_locked: bool,
+    author_username: String,
+    author_bio: String,
+    author_image: Option<String>,
+    author_following: bool,
+    authors: Json<Vec<ArticleAuthorRow>>,
+    /// Number of articles matching the listing query (regardless of
+    /// offset and limit), or `NULL` when fetching a single article.
+    total: Option<i64>,
+}
+
+impl TryFrom<ArticleRow> for Article {
+    type Error = Error;
+
+    fn try_from(row: ArticleRow) -> Result<Self, Self::Error> {
+        Ok(Article {
+            slug: row.slug,
+            title: row.title,
+            // as per the spec, to get the article's body, they need to query
+            // a dedicated endpoint (`/api/articles/{slug}`); we could also
+            // create a dedicated struct (say, ArticleListItem) omiting
+            // the `body` field to not confuse out API user, but since
+            // we are currently the only consumer of this API and provided
+            // we have this documented via Open API UI (Scalar), it's fine
+            // to re-use the "core" articles endpoints struct
+            body: row.body.unwrap_or_default(),
+            body_html: None,
+            toc: None,
+            description: row.description,
+            tags: row.tags,
+            snippet: row.snippet.as_deref().map(renderer::sanitize_snippet),
+            created_at: row.created_at,
+            updated_at: row.updated_at.unwrap_or(row.created_at),
+            favorited: row.favorited,
+            favorited_count: row.favorites_count as usize,
+            bookmarked: row.bookmarked,
+            comments_count: row.comments_count as usize,
+            comments_locked: row.comments_locked,
+            author: Author {
+                username: row.author_username,
+                bio: row.author_bio,
+                image: parse_image_url(row.author_image.as_deref())?,
+                following: row.author_following,
+            },
+            authors: row
+                .authors
+                .0
+                .into_iter()
+                .map(ArticleAuthor::try_from)
+                .collect::<Result<_, _>>()?,
+            series: None,
+        })
+    }
+}
+
+/// Article(s) performance on a given day.
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct DailyStats {
+    /// Calendar day (UTC).
+    #[schema(example = "2025-10-19")]
+    pub day: NaiveDate,
+
+    /// How many times the article(s) have been viewed.
+    ///
+    /// Repeated views by the same reader within a short period of time
+    /// are counted once.
+    pub views: u64,
+
+    /// How many times the article(s) have been read through to the end.
+    pub reads: u64,
+
+    /// How many times the article(s) have been favorited.
+    pub favorites: u64,
+
+    /// How many comments have been left.
+    pub comments: u64,
+}
+
+/// Daily stats as fetched from the database.
+pub(crate) struct DailyStatsRow {
+    pub day: NaiveDate,
+    pub views: i64,
+    pub reads: i64,
+    pub favorites: i64,
+    pub comments: i64,
+}
+
+impl From<DailyStatsRow> for DailyStats {
+    fn from(row: DailyStatsRow) -> Self {
+        DailyStats {
+            day: row.day,
+            views: row.views as u64,
+            reads: row.reads as u64,
+            favorites: row.favorites as u64,
+            comments: row.comments as u64,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct StatsQuery {
+    /// Number of days (including today) to get daily stats for.
+    #[param(nullable = false, default = 30, minimum = 1, maximum = 365)]
+    #[validate(range(min = 1, max = 365, message = "days should be between 1 and 365"))]
+    pub days: Option<u32>,
+}
+
+impl StatsQuery {
+    pub fn days(&self) -> i32 {
+        self.days.unwrap_or(30) as i32
+    }
 }
 
 /// Container for all `article` related endpoints.
@@ -107,19 +402,55 @@ pub(crate) fn router(ctx: Arc<AppContext>) -> OpenApiRouter {
             crud::delete_article,
         ))
         .routes(routes!(crud::favorite_article, crud::unfavorite_article,))
+        .routes(routes!(crud::bookmark_article, crud::unbookmark_article,))
+        .routes(routes!(authors::invite_author,))
+        .routes(routes!(authors::accept_invitation,))
+        .routes(routes!(authors::remove_author,))
         .routes(routes!(
             comments::create_comment,
             comments::list_comments,
+            comments::update_comment,
             comments::delete_comment,
         ))
+        .routes(routes!(comments::add_reaction, comments::remove_reaction,))
+        .routes(routes!(comments::pin_comment, comments::unpin_comment,))
+        .routes(routes!(comments::hide_comment, comments::unhide_comment,))
+        .routes(routes!(live::live_article,))
         .routes(routes!(crud::read_article,))
+        .routes(routes!(related::related_articles,))
+        .routes(routes!(stats::article_stats,))
+        .routes(routes!(stats::read_through,))
         .routes(routes!(list::list_articles,))
+        .routes(routes!(list::search_articles,))
+        .routes(routes!(list::trending_articles,))
         .routes(routes!(list::personal_feed,));
 
-    let tags_router = OpenApiRouter::new().routes(routes!(tags::list_tags,));
+    let series_router = OpenApiRouter::new()
+        .routes(routes!(series::create_series))
+        .routes(routes!(
+            series::read_series,
+            series::update_series,
+            series::delete_series,
+        ));
+
+    let tags_router = OpenApiRouter::new()
+        .routes(routes!(tags::list_tags,))
+        .routes(routes!(tags::update_tag,))
+        .routes(routes!(tags::related_tags,))
+        .routes(routes!(tags::add_alias,))
+        .routes(routes!(tags::remove_alias,))
+        .routes(routes!(tags::follow_tag, tags::unfollow_tag,));
+
+    // current user's reading list and invitations, living alongside
+    // other `/user` endpoints
+    let user_router = OpenApiRouter::new()
+        .routes(routes!(list::bookmarked_articles,))
+        .routes(routes!(authors::list_invitations,));
 
     OpenApiRouter::new()
         .nest("/articles", articles_router)
+        .nest("/series", series_router)
         .nest("/tags", tags_router)
+        .nest("/user", user_router)
         .with_state(ctx)
 }
@@ -0,0 +1,290 @@
+use super::Article;
+use crate::http::errors::{Error, Validation};
+use crate::http::extractors::MaybeUserID;
+use crate::state::AppContext;
+use axum::Json;
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Path, Query, State};
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::Validate;
+use validator_derive::Validate;
+
+const DEFAULT_LIMIT: usize = 5;
+
+/// How many candidates we are ranking (and caching) per article.
+///
+/// This is more than a reader can ask for, since some of the candidates
+/// are then excluded for the reader, see `related_articles`.
+const MAX_CANDIDATES: i64 = 50;
+
+/// For how long the rankings are cached.
+///
+/// The cached rankings of articles sharing tags with an edited article are
+/// dropped right away (see `invalidate`), but any other change (e.g. a new
+/// favorite or article) is only reflected once the rankings expire.
+const RELATED_CACHE_TTL: Duration = Duration::from_secs(60 * 15);
+
+fn cache_key(article_id: &Uuid) -> String {
+    format!("related:{article_id}")
+}
+
+fn cache_tags(tags: &[String]) -> Vec<String> {
+    tags.iter().map(|tag| format!("related:{tag}")).collect()
+}
+
+/// Drop the cached recommendations for the article and for any article
+/// having any of the `tags`, since the article might be recommended there.
+///
+/// Should be called whenever the article's contents change, with both
+/// the article's former and current tags.
+pub(super) async fn invalidate(
+    ctx: &AppContext,
+    article_id: &Uuid,
+    tags: &[String],
+) -> Result<(), Error> {
+    ctx.cache.delete(&cache_key(article_id)).await?;
+    invalidate_tags(ctx, tags).await
+}
+
+/// Drop the cached recommendations for the articles having any of the `tags`.
+pub(super) async fn invalidate_tags(ctx: &AppContext, tags: &[String]) -> Result<(), Error> {
+    ctx.cache.delete_tagged(&cache_tags(tags)).await?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct RelatedArticlesList {
+    /// Related articles, the most relevant ones first.
+    articles: Vec<Article>,
+}
+
+#[derive(Debug, Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct RelatedQuery {
+    /// Limit number of returned articles.
+    #[param(nullable = false, default = 5, maximum = 20)]
+    #[validate(range(max = 20, message = "limit too large"))]
+    limit: Option<usize>,
+}
+
+/// Related articles.
+///
+/// Articles to continue reading with once the reader has reached the end
+/// of this one. Other articles are ranked by how many tags they share with
+/// this article, how many readers favorited both articles, and by how similar
+/// their contents are to this article's title.
+///
+/// Authentication is _optional_, but if the user is authenticated, their own
+/// articles and the articles they already favorited will not be recommended.
+///
+/// Note that the rankings are cached for up to 15 minutes, and so a freshly
+/// published article (or the most recent favorites) might not be immediately
+/// reflected in the recommendations.
+#[utoipa::path(
+    get,
+    path = "/{slug}/related",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "how-to-design-a-programming-language"
+        ),
+        RelatedQuery,
+    ),
+    responses(
+        (status = 200, description = "Related articles successfully retrieved", body = RelatedArticlesList),
+        (status = 401, description = "Token missing or invalid (in case authenicated access has been used)"),
+        (status = 404, description = "Article not found"),
+        (status = 422, description = "Invalid query", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        (),
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "RELATED ARTICLES", skip(ctx))]
+pub async fn related_articles(
+    ctx: State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    q: Result<Query<RelatedQuery>, QueryRejection>,
+    uid: MaybeUserID,
+) -> Result<Json<RelatedArticlesList>, Error> {
+    let Query(q) = q?;
+    q.validate()?;
+    let article = sqlx::query!(
+        "SELECT article_id, tags FROM articles WHERE slug = $1",
+        slug
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    // the rankings do not depend on the reader, and so can be shared
+    let key = cache_key(&article.article_id);
+    let candidates = match ctx.cache.get_json::<Vec<Uuid>>(&key).await? {
+        Some(candidates) => candidates,
+        None => {
+            let candidates = db::rank_candidates(&ctx, &article.article_id).await?;
+            // candidates are mostly sharing tags with the article, which is
+            // what we are invalidating the cached rankings by
+            ctx.cache
+                .set_tagged(
+                    &key,
+                    &candidates,
+                    &cache_tags(&article.tags),
+                    RELATED_CACHE_TTL,
+                )
+                .await?;
+            candidates
+        }
+    };
+
+    let limit = q.limit.unwrap_or(DEFAULT_LIMIT);
+    let articles = db::fetch_articles(&ctx, &candidates, uid.0.as_deref(), limit).await?;
+    Ok(Json(RelatedArticlesList { articles }))
+}
+
+mod db {
+    use super::MAX_CANDIDATES;
+    use crate::AppContext;
+    use crate::http::errors::Error;
+    use crate::http::routes::articles::{Article, ArticleRow};
+    use uuid::Uuid;
+
+    /// Rank other articles by their relevance to the article.
+    pub async fn rank_candidates(ctx: &AppContext, article_id: &Uuid) -> Result<Vec<Uuid>, Error> {
+        let candidates = sqlx::query_scalar!(
+            r#"
+            WITH
+                source AS (
+                    SELECT
+                        article_id,
+                        tags,
+                        -- title's lexemes (as in the search index) matching
+                        -- any other article containing either of them
+                        to_tsquery('simple', (
+                            SELECT string_agg(quote_literal(lexeme), ' | ')
+                            FROM UNNEST(tsvector_to_array(to_tsvector('english', title))) lexeme
+                        )) AS terms
+                    FROM articles
+                    WHERE article_id = $1
+                ),
+                favoriters AS (SELECT user_id FROM favorites WHERE article_id = $1),
+                candidates AS (
+                    SELECT
+                        article.article_id,
+                        article.created_at,
+                        cardinality(
+                            ARRAY(SELECT UNNEST(article.tags) INTERSECT SELECT UNNEST(source.tags))
+                        ) AS shared_tags,
+                        (
+                            SELECT count(*) FROM favorites JOIN favoriters USING (user_id)
+                            WHERE favorites.article_id = article.article_id
+                        ) AS shared_favoriters,
+                        coalesce(ts_rank(article.search, source.terms), 0) AS similarity
+                    FROM articles article, source
+                    WHERE
+                        article.article_id <> source.article_id AND
+                        (
+                            article.tags && source.tags OR
+                            article.search @@ source.terms OR
+                            EXISTS(
+                                SELECT 1 FROM favorites JOIN favoriters USING (user_id)
+                                WHERE favorites.article_id = article.article_id
+                            )
+                        )
+                )
+            SELECT article_id FROM candidates
+            ORDER BY
+                shared_tags + ln(1 + shared_favoriters) + similarity DESC,
+                created_at DESC
+            LIMIT $2
+            "#,
+            article_id,
+            MAX_CANDIDATES,
+        )
+        .fetch_all(&ctx.db)
+        .await?;
+        Ok(candidates)
+    }
+
+    /// Fetch the candidates the reader has not authored or favorited yet.
+    ///
+    /// The candidates' order is preserved.
+    pub async fn fetch_articles(
+        ctx: &AppContext,
+        candidates: &[Uuid],
+        uid: Option<&Uuid>,
+        limit: usize,
+    ) -> Result<Vec<Article>, Error> {
+        let rows = sqlx::query_as!(
+            ArticleRow,
+            r#"
+            SELECT
+                article.article_id,
+                article.slug,
+                article.title,
+                article.description,
+                NULL::TEXT AS "body?",
+                article.tags,
+                NULL::TEXT AS "snippet?",
+                article.created_at,
+                article.updated_at,
+                FALSE AS "favorited!",
+                article.favorites_count,
+                (
+                    $2::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM bookmarks
+                        WHERE article_id = article.article_id AND user_id = $2::UUID
+                    )
+                ) AS "bookmarked!",
+                article.comments_count,
+                article.comments_locked,
+                author.username AS author_username,
+                author.bio AS author_bio,
+                author.image AS author_image,
+                (
+                    $2::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM follows
+                        WHERE followed_user_id = author.user_id AND following_user_id = $2::UUID
+                    )
+                ) AS "author_following!",
+                article_authors_json(article.article_id, $2::UUID) AS "authors!: _",
+                NULL::BIGINT AS "total?"
+            FROM "articles" article JOIN "users" author USING (user_id)
+            WHERE
+                article.article_id = ANY($1) AND
+                (
+                    $2::UUID IS NULL OR
+                    NOT EXISTS(
+                        SELECT 1 FROM article_authors
+                        WHERE
+                            article_id = article.article_id AND
+                            user_id = $2::UUID AND
+                            accepted_at IS NOT NULL
+                    ) AND
+                    NOT EXISTS(
+                        SELECT 1 FROM favorites
+                        WHERE article_id = article.article_id AND user_id = $2::UUID
+                    )
+                )
+            ORDER BY array_position($1, article.article_id)
+            LIMIT $3
+            "#,
+            candidates,
+            uid,
+            limit as i64,
+        )
+        .fetch_all(&ctx.db)
+        .await?;
+        rows.into_iter().map(Article::try_from).collect()
+    }
+}
@@ -0,0 +1,501 @@
+use super::{ArticleLink, Author};
+use crate::http::errors::{Error, Validation};
+use crate::http::extractors::{MaybeUserID, UserID};
+use crate::state::AppContext;
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use utoipa::ToSchema;
+use validator::Validate;
+use validator_derive::Validate;
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Series {
+    /// Series' slug.
+    #[schema(example = "how-to-train-your-dragon", format = "slug")]
+    slug: String,
+
+    /// Series' title.
+    #[schema(example = "How to train your dragon", min_length = 1)]
+    title: String,
+
+    /// Series' description.
+    #[schema(example = "Everything you need to know about dragons in 3 parts")]
+    description: String,
+
+    /// Articles of this series in reading order.
+    articles: Vec<ArticleLink>,
+
+    /// When this series was created.
+    created_at: DateTime<Utc>,
+
+    /// When this series was last updated.
+    updated_at: DateTime<Utc>,
+
+    /// The series' author details.
+    author: Author,
+}
+
+/// Container for all `series` related endpoints.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub(crate) struct SeriesPayload<U> {
+    series: U,
+}
+
+// -------------------------------- CREATE ------------------------------------
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct SeriesCreate {
+    /// Series' title.
+    ///
+    /// This is will be used to generate a slug for this series.
+    #[schema(example = "How to train your dragon", min_length = 1, max_length = 200)]
+    #[validate(length(min = 1, message = "title should be at least 1 character long"))]
+    title: String,
+
+    /// Series' description.
+    #[schema(example = "Everything you need to know about dragons in 3 parts")]
+    #[serde(default)]
+    description: String,
+
+    /// Slugs of the series' articles in reading order.
+    ///
+    /// Only the author's own articles can be added to their series, and an
+    /// article can be part of at most one series.
+    #[schema(
+        example = json!(vec!["how-to-train-your-dragon-part-1".to_string()]),
+        max_items = 100,
+    )]
+    #[validate(length(max = 100, message = "series can contain up to 100 articles"))]
+    #[serde(default)]
+    articles: Vec<String>,
+}
+
+/// Create series.
+///
+/// This will register a new series of the current user's articles assigning
+/// it a slug, which uniquely identifies it among other series.
+#[utoipa::path(
+    post,
+    path = "",
+    tags = ["Series"],
+    responses(
+        (status = 201, description = "Series successfully created", body = SeriesPayload<Series>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 422, description = "Missing or invalid series attributes", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "CREATE SERIES", skip_all)]
+pub async fn create_series(
+    ctx: State<Arc<AppContext>>,
+    uid: UserID,
+    input: Result<Json<SeriesPayload<SeriesCreate>>, JsonRejection>,
+) -> Result<(StatusCode, Json<SeriesPayload<Series>>), Error> {
+    let SeriesPayload { series } = input?.0;
+    series.validate()?;
+    let slug = slug::slugify(&series.title);
+
+    let mut tx = ctx.db.begin().await?;
+    let article_ids = db::resolve_articles(&mut tx, &uid, &series.articles).await?;
+    let series_id = db::insert_series(&mut tx, &uid, &slug, &series).await?;
+    db::set_articles(&mut tx, series_id, &article_ids).await?;
+    tx.commit().await?;
+
+    let series = db::read_series(&ctx, &slug, Some(&uid)).await?;
+    Ok((StatusCode::CREATED, Json(SeriesPayload { series })))
+}
+
+// --------------------------------- READ -------------------------------------
+/// Read series by slug.
+///
+/// Authentication is _optional_, but needed to learn whether the user is
+/// following the series' author.
+#[utoipa::path(
+    get,
+    path = "/{slug}",
+    tags = ["Series"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Series' slug identifier.",
+            example = "how-to-train-your-dragon"
+        ),
+    ),
+    responses(
+        (status = 200, description = "Series successfully retrieved", body = SeriesPayload<Series>),
+        (status = 401, description = "Token missing or invalid (in case authenicated access has been used)"),
+        (status = 404, description = "Series not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        (),
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "READ SERIES", skip(ctx))]
+pub async fn read_series(
+    ctx: State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    uid: MaybeUserID,
+) -> Result<Json<SeriesPayload<Series>>, Error> {
+    let series = db::read_series(&ctx, &slug, uid.0.as_deref()).await?;
+    Ok(Json(SeriesPayload { series }))
+}
+
+// -------------------------------- UPDATE ------------------------------------
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct SeriesUpdate {
+    /// Series' title.
+    ///
+    /// This is will be used to generate a slug for this series.
+    #[schema(
+        example = "How to train your dragon",
+        min_length = 1,
+        max_length = 200,
+        nullable = false
+    )]
+    #[validate(length(min = 1, message = "title should be at least 1 character long"))]
+    title: Option<String>,
+
+    /// Series' description.
+    #[schema(
+        example = "Everything you need to know about dragons in 3 parts",
+        nullable = false
+    )]
+    description: Option<String>,
+
+    /// Slugs of the series' articles in reading order.
+    ///
+    /// When provided, replaces the current list of articles altogether.
+    #[schema(
+        example = json!(vec!["how-to-train-your-dragon-part-1".to_string()]),
+        max_items = 100,
+        nullable = false
+    )]
+    #[validate(length(max = 100, message = "series can contain up to 100 articles"))]
+    articles: Option<Vec<String>>,
+}
+
+/// Update series.
+///
+/// Just like with the article update endpoint, the payload can contain only
+/// a partial series, and the slug will be re-calculated if the title changes.
+/// Use `articles` to add, remove or reorder the series' articles.
+///
+/// Only the series' author can perform this action.
+#[utoipa::path(
+    put,
+    path = "/{slug}",
+    tags = ["Series"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Series' slug identifier.",
+            example = "how-to-train-your-dragon"
+        ),
+    ),
+    responses(
+        (status = 200, description = "Series successfully updated", body = SeriesPayload<Series>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 403, description = "User does not have permissions to update this series."),
+        (status = 404, description = "Series not found"),
+        (status = 422, description = "Missing or invalid series attributes", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "UPDATE SERIES", skip(ctx, input))]
+pub async fn update_series(
+    ctx: State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    uid: UserID,
+    input: Result<Json<SeriesPayload<SeriesUpdate>>, JsonRejection>,
+) -> Result<Json<SeriesPayload<Series>>, Error> {
+    let SeriesPayload { series: patch } = input?.0;
+    patch.validate()?;
+    let series_id = db::authored_series(&ctx, &slug, &uid).await?;
+    let new_slug = patch.title.as_deref().map(slug::slugify);
+
+    let mut tx = ctx.db.begin().await?;
+    if let Some(articles) = &patch.articles {
+        let article_ids = db::resolve_articles(&mut tx, &uid, articles).await?;
+        db::set_articles(&mut tx, series_id, &article_ids).await?;
+    }
+    db::update_series(&mut tx, series_id, new_slug.as_deref(), &patch).await?;
+    tx.commit().await?;
+
+    let slug = new_slug.unwrap_or(slug);
+    let series = db::read_series(&ctx, &slug, Some(&uid)).await?;
+    Ok(Json(SeriesPayload { series }))
+}
+
+// -------------------------------- DELETE ------------------------------------
+/// Delete series by slug.
+///
+/// The series' articles are _not_ deleted, they are simply not part
+/// of any series anymore. Only the series' author can perform this action.
+#[utoipa::path(
+    delete,
+    path = "/{slug}",
+    tags = ["Series"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Series' slug identifier.",
+            example = "how-to-train-your-dragon"
+        ),
+    ),
+    responses(
+        (status = 204, description = "Series successfully deleted."),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 403, description = "User does not have permissions to delete this series."),
+        (status = 404, description = "Series not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "DELETE SERIES", skip(ctx))]
+pub async fn delete_series(
+    ctx: State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    uid: UserID,
+) -> Result<StatusCode, Error> {
+    let series_id = db::authored_series(&ctx, &slug, &uid).await?;
+    sqlx::query!("DELETE FROM series WHERE series_id = $1", series_id)
+        .execute(&ctx.db)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+mod db {
+    use super::{Series, SeriesCreate, SeriesUpdate};
+    use crate::AppContext;
+    use crate::http::errors::{Error, ResultExt as _};
+    use crate::http::routes::articles::{ArticleLink, Author};
+    use crate::http::routes::users::utils::parse_image_url;
+    use sqlx::{Postgres, Transaction};
+    use std::collections::HashSet;
+    use uuid::Uuid;
+
+    /// Get the series' identifier, making sure the user is its author.
+    pub async fn authored_series(ctx: &AppContext, slug: &str, uid: &Uuid) -> Result<Uuid, Error> {
+        let series = sqlx::query!(
+            "SELECT series_id, user_id FROM series WHERE slug = $1",
+            slug
+        )
+        .fetch_optional(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+        if series.user_id != *uid {
+            warn!("user tried to modify series w/o proper permissions");
+            return Err(Error::Forbidden);
+        }
+        Ok(series.series_id)
+    }
+
+    /// Turn articles' slugs into identifiers, preserving the order.
+    ///
+    /// All the articles should exist and be authored by the user.
+    pub async fn resolve_articles(
+        tx: &mut Transaction<'_, Postgres>,
+        uid: &Uuid,
+        slugs: &[String],
+    ) -> Result<Vec<Uuid>, Error> {
+        if slugs.iter().collect::<HashSet<_>>().len() != slugs.len() {
+            return Err(Error::unprocessable_entity([(
+                "articles",
+                "articles should not repeat",
+            )]));
+        }
+        let articles = sqlx::query!(
+            r#"
+            SELECT
+                input.slug AS "slug!",
+                article.article_id AS "article_id?",
+                article.user_id AS "user_id?"
+            FROM
+                UNNEST($1::TEXT[]) WITH ORDINALITY input (slug, n)
+                    LEFT JOIN articles article ON article.slug = input.slug
+            ORDER BY input.n
+            "#,
+            slugs,
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+        let mut article_ids = Vec::with_capacity(articles.len());
+        for article in articles {
+            match (article.article_id, article.user_id) {
+                (Some(article_id), Some(user_id)) if user_id == *uid => {
+                    article_ids.push(article_id)
+                }
+                (Some(_), _) => {
+                    return Err(Error::unprocessable_entity([(
+                        "articles",
+                        format!("article '{}' is authored by another user", article.slug),
+                    )]));
+                }
+                _ => {
+                    return Err(Error::unprocessable_entity([(
+                        "articles",
+                        format!("article '{}' not found", article.slug),
+                    )]));
+                }
+            }
+        }
+        Ok(article_ids)
+    }
+
+    pub async fn insert_series(
+        tx: &mut Transaction<'_, Postgres>,
+        uid: &Uuid,
+        slug: &str,
+        series: &SeriesCreate,
+    ) -> Result<Uuid, Error> {
+        let series_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO series (user_id, slug, title, description)
+            VALUES ($1, $2, $3, $4)
+            RETURNING series_id
+            "#,
+            uid,
+            slug,
+            series.title,
+            series.description,
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .on_constraint("series_slug_key", |_| {
+            Error::unprocessable_entity([("title", "series with this title already exists")])
+        })?;
+        Ok(series_id)
+    }
+
+    pub async fn update_series(
+        tx: &mut Transaction<'_, Postgres>,
+        series_id: Uuid,
+        new_slug: Option<&str>,
+        patch: &SeriesUpdate,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE series
+            SET
+                slug = COALESCE($2, slug),
+                title = COALESCE($3, title),
+                description = COALESCE($4, description)
+            WHERE series_id = $1
+            "#,
+            series_id,
+            new_slug,
+            patch.title,
+            patch.description,
+        )
+        .execute(&mut **tx)
+        .await
+        .on_constraint("series_slug_key", |_| {
+            Error::unprocessable_entity([("title", "series with this title already exists")])
+        })?;
+        Ok(())
+    }
+
+    /// Replace the series' articles with the given ones (in this very order).
+    pub async fn set_articles(
+        tx: &mut Transaction<'_, Postgres>,
+        series_id: Uuid,
+        article_ids: &[Uuid],
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "DELETE FROM series_articles WHERE series_id = $1",
+            series_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO series_articles (series_id, article_id, position)
+            SELECT $1, article_id, position::INTEGER
+            FROM UNNEST($2::UUID[]) WITH ORDINALITY entry (article_id, position)
+            "#,
+            series_id,
+            article_ids,
+        )
+        .execute(&mut **tx)
+        .await
+        .on_constraint("series_articles_article_id_key", |_| {
+            Error::unprocessable_entity([("articles", "article is already part of another series")])
+        })?;
+        Ok(())
+    }
+
+    #[instrument(name = "FETCH SERIES FROM DATABASE", skip(ctx))]
+    pub async fn read_series(
+        ctx: &AppContext,
+        slug: &str,
+        user_id: Option<&Uuid>,
+    ) -> Result<Series, Error> {
+        let details = sqlx::query!(
+            r#"
+            SELECT
+                series.series_id,
+                series.slug,
+                series.title,
+                series.description,
+                series.created_at,
+                series.updated_at,
+                author.username AS author_username,
+                author.bio AS author_bio,
+                author.image AS author_image,
+                (
+                    $2::UUID IS NOT NULL AND
+                    EXISTS(
+                        SELECT 1 FROM follows
+                        WHERE followed_user_id = author.user_id AND following_user_id = $2::UUID
+                    )
+                ) AS "author_following!"
+            FROM series JOIN users author USING (user_id)
+            WHERE slug = $1
+            "#,
+            slug,
+            user_id,
+        )
+        .fetch_optional(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+        let articles = sqlx::query_as!(
+            ArticleLink,
+            r#"
+            SELECT article.slug, article.title
+            FROM series_articles JOIN articles article USING (article_id)
+            WHERE series_id = $1
+            ORDER BY position
+            "#,
+            details.series_id,
+        )
+        .fetch_all(&ctx.db)
+        .await?;
+
+        Ok(Series {
+            slug: details.slug,
+            title: details.title,
+            description: details.description,
+            articles,
+            created_at: details.created_at,
+            updated_at: details.updated_at.unwrap_or(details.created_at),
+            author: Author {
+                username: details.author_username,
+                bio: details.author_bio,
+                image: parse_image_url(details.author_image.as_deref())?,
+                following: details.author_following,
+            },
+        })
+    }
+}
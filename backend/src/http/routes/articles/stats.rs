@@ -0,0 +1,245 @@
+use super::{DailyStats, DailyStatsRow, StatsQuery};
+use crate::http::errors::{Error, Validation};
+use crate::http::extractors::{ClientIp, MaybeUserID, UserID};
+use crate::http::utils;
+use crate::services::analytics::Signal;
+use crate::state::AppContext;
+use axum::Json;
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use std::sync::Arc;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArticleStats {
+    /// Total number of views.
+    views: u64,
+
+    /// Total number of read-throughs.
+    reads: u64,
+
+    /// How many users favorited this article.
+    favorites_count: u64,
+
+    /// How many comments have been left.
+    comments_count: u64,
+
+    /// Day-by-day stats, the earliest day first.
+    daily: Vec<DailyStats>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct ArticleStatsPayload {
+    stats: ArticleStats,
+}
+
+/// Article stats.
+///
+/// Views, read-throughs, favorites and comments of the article (both overall
+/// and day-by-day). Only available to the article's authors.
+///
+/// Note that views and read-throughs are written to the database periodically,
+/// and so the stats might be lagging behind a bit.
+#[utoipa::path(
+    get,
+    path = "/{slug}/stats",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "how-to-design-a-programming-language"
+        ),
+        StatsQuery,
+    ),
+    responses(
+        (status = 200, description = "Article stats successfully retrieved", body = ArticleStatsPayload),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 403, description = "User is not an author of this article."),
+        (status = 404, description = "Article not found"),
+        (status = 422, description = "Invalid query", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "ARTICLE STATS", skip(ctx))]
+pub async fn article_stats(
+    ctx: State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    q: Result<Query<StatsQuery>, QueryRejection>,
+    uid: UserID,
+) -> Result<Json<ArticleStatsPayload>, Error> {
+    let Query(q) = q?;
+    q.validate()?;
+    let article = sqlx::query!(
+        r#"
+        SELECT
+            article_id,
+            EXISTS(
+                SELECT 1 FROM article_authors
+                WHERE
+                    article_id = articles.article_id AND
+                    user_id = $2 AND
+                    accepted_at IS NOT NULL
+            ) AS "is_author!"
+        FROM articles
+        WHERE slug = $1
+        "#,
+        slug,
+        *uid,
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+    if !article.is_author {
+        return Err(Error::Forbidden);
+    }
+
+    let totals = sqlx::query!(
+        r#"
+        SELECT
+            (SELECT coalesce(sum(views), 0)::BIGINT FROM article_stats WHERE article_id = $1) AS "views!",
+            (SELECT coalesce(sum(reads), 0)::BIGINT FROM article_stats WHERE article_id = $1) AS "reads!",
+            favorites_count,
+            comments_count
+        FROM articles
+        WHERE article_id = $1
+        "#,
+        article.article_id,
+    )
+    .fetch_one(&ctx.db)
+    .await?;
+
+    let daily = sqlx::query_as!(
+        DailyStatsRow,
+        r#"
+        SELECT
+            series.day::DATE AS "day!",
+            coalesce(stats.views, 0) AS "views!",
+            coalesce(stats.reads, 0) AS "reads!",
+            (
+                SELECT count(*) FROM favorites
+                WHERE
+                    article_id = $1 AND
+                    created_at >= series.day AT TIME ZONE 'UTC' AND
+                    created_at < (series.day + INTERVAL '1 day') AT TIME ZONE 'UTC'
+            ) AS "favorites!",
+            (
+                SELECT count(*) FROM comments
+                WHERE
+                    article_id = $1 AND
+                    deleted_at IS NULL AND
+                    created_at >= series.day AT TIME ZONE 'UTC' AND
+                    created_at < (series.day + INTERVAL '1 day') AT TIME ZONE 'UTC'
+            ) AS "comments!"
+        FROM
+            generate_series(
+                ((NOW() AT TIME ZONE 'UTC')::DATE - ($2::INT - 1))::TIMESTAMP,
+                (NOW() AT TIME ZONE 'UTC')::DATE::TIMESTAMP,
+                INTERVAL '1 day'
+            ) series (day)
+            LEFT JOIN article_stats stats
+                ON stats.article_id = $1 AND stats.day = series.day::DATE
+        ORDER BY series.day
+        "#,
+        article.article_id,
+        q.days(),
+    )
+    .fetch_all(&ctx.db)
+    .await?;
+
+    Ok(Json(ArticleStatsPayload {
+        stats: ArticleStats {
+            views: totals.views as u64,
+            reads: totals.reads as u64,
+            favorites_count: totals.favorites_count as u64,
+            comments_count: totals.comments_count as u64,
+            daily: daily.into_iter().map(Into::into).collect(),
+        },
+    }))
+}
+
+/// Read article through.
+///
+/// Let us know that the reader has reached the end of the article. This
+/// is counted towards the article's read-throughs, see `article_stats`.
+/// Authentication is _optional_.
+#[utoipa::path(
+    post,
+    path = "/{slug}/read",
+    tags = ["Articles"],
+    params(
+        (
+            "slug" = String, Path,
+            format = "slug",
+            description = "Article's slug identifier.",
+            example = "how-to-design-a-programming-language"
+        ),
+    ),
+    responses(
+        (status = 204, description = "Read-through successfully recorded."),
+        (status = 401, description = "Token missing or invalid (in case authenicated access has been used)"),
+        (status = 404, description = "Article not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        (),
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "READ THROUGH ARTICLE", skip(ctx))]
+pub async fn read_through(
+    ctx: State<Arc<AppContext>>,
+    Path(slug): Path<String>,
+    uid: MaybeUserID,
+    ip: ClientIp,
+) -> Result<StatusCode, Error> {
+    let article_id = sqlx::query_scalar!("SELECT article_id FROM articles WHERE slug = $1", slug)
+        .fetch_optional(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+    record_signal(&ctx, Signal::Read, article_id, uid.0.as_deref(), ip).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Record visitor's `signal` for the article.
+///
+/// Authors visiting their own articles are not counted, and neither are
+/// the visitors we cannot identify, see `utils::visitor_id`.
+pub(super) async fn record_signal(
+    ctx: &AppContext,
+    signal: Signal,
+    article_id: Uuid,
+    uid: Option<&Uuid>,
+    ClientIp(ip): ClientIp,
+) -> anyhow::Result<()> {
+    let Some(visitor) = utils::visitor_id(&ctx.secret_key, uid, ip) else {
+        return Ok(());
+    };
+    if let Some(uid) = uid {
+        let is_author = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM article_authors
+                WHERE article_id = $1 AND user_id = $2 AND accepted_at IS NOT NULL
+            ) AS "is_author!"
+            "#,
+            article_id,
+            uid,
+        )
+        .fetch_one(&ctx.db)
+        .await?;
+        if is_author {
+            return Ok(());
+        }
+    }
+    ctx.analytics.record(signal, article_id, &visitor).await?;
+    Ok(())
+}
@@ -1,10 +1,112 @@
-use crate::http::errors::Error;
+use super::related;
+use crate::http::errors::{Error, ResultExt as _, Validation};
+use crate::http::extractors::{MaybeUserID, UserID};
 use crate::state::AppContext;
-use axum::{Json, extract::State};
+use axum::Json;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use sqlx::PgPool;
+use sqlx::types::Json as SqlxJson;
 use std::sync::Arc;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+use validator_derive::Validate;
+
+const DEFAULT_OFFSET: usize = 0;
+const DEFAULT_LIMIT: usize = 100;
+const DEFAULT_RELATED_LIMIT: usize = 10;
+
+/// Maximum length of a tag (in characters).
+const MAX_TAG_LENGTH: usize = 50;
+
+/// Normalize a tag: trim, collapse inner whitespace, and case-fold.
+fn normalize(tag: &str) -> String {
+    tag.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Normalize tags and resolve their aliases.
+///
+/// The returned tags are sorted and deduplicated. Any empty or too long tag,
+/// or a tag containing a comma (which is what separates tags in the articles
+/// list filters) will result in a validation error for the `field`.
+pub(super) async fn canonicalize(
+    db: &PgPool,
+    tags: &[String],
+    field: &str,
+) -> Result<Vec<String>, Error> {
+    if tags.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut normalized = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = normalize(tag);
+        if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(Error::unprocessable_entity([(
+                field.to_owned(),
+                format!("tags should be at least 1 and at max {MAX_TAG_LENGTH} characters long"),
+            )]));
+        }
+        if tag.contains(',') {
+            return Err(Error::unprocessable_entity([(
+                field.to_owned(),
+                "tags cannot contain commas".to_owned(),
+            )]));
+        }
+        normalized.push(tag);
+    }
+    let mut tags = sqlx::query_scalar!(
+        r#"
+        SELECT coalesce(alias.tag, input.tag) AS "tag!"
+        FROM UNNEST($1::TEXT[]) input (tag) LEFT JOIN tag_aliases alias ON alias.alias = input.tag
+        "#,
+        &normalized,
+    )
+    .fetch_all(db)
+    .await?;
+    // Realworld end-to-end test suite expects tags to be sorted
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Tag {
+    /// Tag's name.
+    #[schema(example = "language design")]
+    name: String,
+
+    /// Tag's description.
+    #[schema(example = "How programming languages are made")]
+    description: String,
+
+    /// Alternative spellings of the tag.
+    ///
+    /// Whenever an alias is used, it is replaced with the tag's name.
+    #[schema(example = json!(vec!["lang design".to_string()]))]
+    aliases: Vec<String>,
+
+    /// Number of articles having this tag.
+    articles_count: u64,
+
+    /// Whether the current user is following this tag.
+    ///
+    /// Articles having the tags the user is following can be included
+    /// in their personal feed, see `personal_feed`.
+    following: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub(crate) struct TagPayload<U> {
+    tag: U,
+}
 
 #[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct TagsList {
     /// List of tags.
     #[schema(
@@ -12,6 +114,35 @@ pub(crate) struct TagsList {
         min_items = 0,
     )]
     tags: Vec<String>,
+
+    /// Details of the tags, in the same order as `tags`.
+    details: Vec<Tag>,
+
+    /// Total number of tags in use.
+    tags_count: usize,
+}
+
+#[derive(Debug, Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct TagsQuery {
+    /// Only return tags starting with this prefix (e.g. for autocompletion).
+    ///
+    /// The prefix is normalized the same way tags are.
+    #[param(nullable = false, example = "lang")]
+    #[validate(length(max = 50, message = "prefix should be at max 50 characters long"))]
+    prefix: Option<String>,
+
+    /// Limit number of returned tags.
+    ///
+    /// All of the tags are returned by default, unless `prefix` is provided,
+    /// in which case the default is `100`.
+    #[param(nullable = false, maximum = 1000)]
+    #[validate(range(max = 1000, message = "limit too large"))]
+    limit: Option<usize>,
+
+    /// Offset/skip number of tags.
+    #[param(nullable = false, default = 0)]
+    offset: Option<usize>,
 }
 
 /// List tags.
@@ -22,13 +153,20 @@ pub(crate) struct TagsList {
 /// Note that if there are no articles in the system (or at least no articles
 /// visible to the currently logged in user) the list can be empty.
 ///
-/// No authentication required.
+/// Use `prefix` to only get the tags starting with it, e.g. to suggest tags
+/// while the user is typing.
+///
+/// Authentication is _optional_, but needed to learn if the user is following
+/// the tags.
 #[utoipa::path(
     get,
     path = "/",
-    tags = ["Articles"],
+    tags = ["Tags"],
+    params(TagsQuery),
     responses(
         (status = 200, description = "Tags list successfully retrieved", body = TagsList),
+        (status = 401, description = "Token missing or invalid (in case authenicated access has been used)"),
+        (status = 422, description = "Invalid query", body = Validation),
         (status = 500, description = "Internal server error."),
     ),
     security(
@@ -37,16 +175,556 @@ pub(crate) struct TagsList {
     ),
 )]
 #[instrument(name = "TAGS LIST", skip_all)]
-pub async fn list_tags(ctx: State<Arc<AppContext>>) -> Result<Json<TagsList>, Error> {
+pub async fn list_tags(
+    ctx: State<Arc<AppContext>>,
+    q: Result<Query<TagsQuery>, QueryRejection>,
+    uid: MaybeUserID,
+) -> Result<Json<TagsList>, Error> {
+    let Query(q) = q?;
+    q.validate()?;
+    // escaping the wildcards so that the prefix is matched literally
+    let pattern = q.prefix.as_deref().map(|prefix| {
+        let prefix = normalize(prefix)
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        format!("{prefix}%")
+    });
+    // all the tags in use are listed unless the client is paginating, or is
+    // autocompleting, in which case only the most popular ones are needed
+    let limit = q.limit.or(q.prefix.is_some().then_some(DEFAULT_LIMIT));
+    let list = sqlx::query!(
+        r#"
+        WITH matching AS (
+            SELECT name, articles_count FROM tags
+            WHERE articles_count > 0 AND ($4::TEXT IS NULL OR name LIKE $4)
+        ),
+        page AS (
+            SELECT name, articles_count FROM matching
+            ORDER BY articles_count DESC, name
+            OFFSET $1
+            LIMIT $2
+        )
+        SELECT
+            (SELECT count(*) FROM matching) AS "tags_count!",
+            (
+                SELECT coalesce(
+                    jsonb_agg(
+                        jsonb_build_object(
+                            'name', tag.name,
+                            'description', tag.description,
+                            'aliases', ARRAY(
+                                SELECT alias FROM tag_aliases
+                                WHERE tag_aliases.tag = tag.name
+                                ORDER BY alias
+                            ),
+                            'articlesCount', tag.articles_count,
+                            'following', $3::UUID IS NOT NULL AND EXISTS(
+                                SELECT 1 FROM tag_follows WHERE user_id = $3 AND tag = tag.name
+                            )
+                        )
+                        ORDER BY tag.articles_count DESC, tag.name
+                    ),
+                    '[]'::JSONB
+                )
+                FROM page JOIN tags tag USING (name)
+            ) AS "details!: SqlxJson<Vec<Tag>>"
+        "#,
+        q.offset.unwrap_or(DEFAULT_OFFSET) as i64,
+        limit.map(|limit| limit as i64),
+        uid.0.as_deref(),
+        pattern,
+    )
+    .fetch_one(&ctx.db)
+    .await?;
+    let SqlxJson(details) = list.details;
+    Ok(Json(TagsList {
+        tags: details.iter().map(|tag| tag.name.clone()).collect(),
+        details,
+        tags_count: list.tags_count as usize,
+    }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RelatedTag {
+    /// Tag's name.
+    #[schema(example = "compilers")]
+    name: String,
+
+    /// Number of articles having both this and the original tag.
+    articles_count: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct RelatedTagsList {
+    /// Related tags, the most frequently co-occurring ones first.
+    tags: Vec<RelatedTag>,
+}
+
+#[derive(Debug, Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct RelatedTagsQuery {
+    /// Limit number of returned tags.
+    #[param(nullable = false, default = 10, maximum = 100)]
+    #[validate(range(max = 100, message = "limit too large"))]
+    limit: Option<usize>,
+}
+
+/// Related tags.
+///
+/// Tags that are most often used together with the given tag, e.g. to suggest
+/// tags while the user is editing an article.
+///
+/// No authentication required.
+#[utoipa::path(
+    get,
+    path = "/{tag}/related",
+    tags = ["Tags"],
+    params(
+        (
+            "tag" = String, Path,
+            description = "Tag's name.",
+            example = "language design"
+        ),
+        RelatedTagsQuery,
+    ),
+    responses(
+        (status = 200, description = "Related tags successfully retrieved", body = RelatedTagsList),
+        (status = 404, description = "Tag not found"),
+        (status = 422, description = "Invalid query", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(
+        (),
+        ("HttpAuthBearerJWT" = []),
+    ),
+)]
+#[instrument(name = "RELATED TAGS", skip(ctx))]
+pub async fn related_tags(
+    ctx: State<Arc<AppContext>>,
+    Path(tag): Path<String>,
+    q: Result<Query<RelatedTagsQuery>, QueryRejection>,
+) -> Result<Json<RelatedTagsList>, Error> {
+    let Query(q) = q?;
+    q.validate()?;
+    let tag = canonicalize(&ctx.db, &[tag], "tag")
+        .await?
+        .pop()
+        .ok_or(Error::NotFound)?;
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM tags WHERE name = $1) AS "exists!""#,
+        tag
+    )
+    .fetch_one(&ctx.db)
+    .await?;
+    if !exists {
+        return Err(Error::NotFound);
+    }
     let tags = sqlx::query!(
         r#"
-        SELECT COUNT(*) as "count", UNNEST(tags) AS "tag!"
-        FROM articles GROUP BY "tag!" ORDER BY "count" DESC;
-        "#
+        SELECT other AS "name!", count(*) AS "articles_count!"
+        FROM articles, UNNEST(tags) other
+        WHERE tags @> ARRAY[$1] AND other <> $1
+        GROUP BY other
+        ORDER BY 2 DESC, 1
+        LIMIT $2
+        "#,
+        tag,
+        q.limit.unwrap_or(DEFAULT_RELATED_LIMIT) as i64,
     )
     .fetch_all(&ctx.db)
     .await?;
-    Ok(Json(TagsList {
-        tags: tags.into_iter().map(|row| row.tag).collect(),
+    Ok(Json(RelatedTagsList {
+        tags: tags
+            .into_iter()
+            .map(|row| RelatedTag {
+                name: row.name,
+                articles_count: row.articles_count as u64,
+            })
+            .collect(),
     }))
 }
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct TagUpdate {
+    /// Tag's description.
+    #[schema(example = "How programming languages are made", max_length = 500)]
+    #[validate(length(
+        max = 500,
+        message = "description should be at max 500 characters long"
+    ))]
+    description: String,
+}
+
+/// Update tag.
+///
+/// Only available to administrators.
+#[utoipa::path(
+    put,
+    path = "/{tag}",
+    tags = ["Tags"],
+    params(
+        (
+            "tag" = String, Path,
+            description = "Tag's name.",
+            example = "language design"
+        ),
+    ),
+    responses(
+        (status = 200, description = "Tag successfully updated", body = TagPayload<Tag>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 403, description = "User is not an administrator."),
+        (status = 404, description = "Tag not found"),
+        (status = 422, description = "Missing or invalid tag attributes", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "UPDATE TAG", skip(ctx, input))]
+pub async fn update_tag(
+    ctx: State<Arc<AppContext>>,
+    Path(tag): Path<String>,
+    uid: UserID,
+    input: Result<Json<TagPayload<TagUpdate>>, JsonRejection>,
+) -> Result<Json<TagPayload<Tag>>, Error> {
+    let TagPayload { tag: patch } = input?.0;
+    patch.validate()?;
+    db::ensure_admin(&ctx.db, &uid).await?;
+    let name = sqlx::query_scalar!(
+        "UPDATE tags SET description = $2 WHERE name = $1 RETURNING name",
+        normalize(&tag),
+        patch.description,
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+    let tag = db::read_tag(&ctx.db, &name, &uid).await?;
+    Ok(Json(TagPayload { tag }))
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct AliasCreate {
+    /// Alternative spelling of the tag.
+    #[schema(example = "lang design", min_length = 1, max_length = 50)]
+    #[validate(length(
+        min = 1,
+        max = 50,
+        message = "alias should be at least 1 and at max 50 characters long"
+    ))]
+    #[validate(does_not_contain(pattern = ",", message = "alias cannot contain commas"))]
+    name: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub(crate) struct AliasPayload {
+    alias: AliasCreate,
+}
+
+/// Add tag alias.
+///
+/// Register an alternative spelling of the tag: from now on, the alias will
+/// be replaced with the tag whenever articles are created, updated, or filtered
+/// by tags. If the alias is itself a tag in use, the two tags are merged: articles
+/// having the alias will have it replaced with the tag (and so these articles'
+/// `updatedAt` will change), while the alias' own aliases and followers will
+/// be moved over to the tag.
+///
+/// Only available to administrators.
+#[utoipa::path(
+    post,
+    path = "/{tag}/aliases",
+    tags = ["Tags"],
+    params(
+        (
+            "tag" = String, Path,
+            description = "Tag's name.",
+            example = "language design"
+        ),
+    ),
+    request_body = AliasPayload,
+    responses(
+        (status = 201, description = "Alias successfully added", body = TagPayload<Tag>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 403, description = "User is not an administrator."),
+        (status = 404, description = "Tag not found"),
+        (status = 422, description = "Missing or invalid alias", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "ADD TAG ALIAS", skip(ctx, input))]
+pub async fn add_alias(
+    ctx: State<Arc<AppContext>>,
+    Path(tag): Path<String>,
+    uid: UserID,
+    input: Result<Json<AliasPayload>, JsonRejection>,
+) -> Result<(StatusCode, Json<TagPayload<Tag>>), Error> {
+    let AliasPayload { alias } = input?.0;
+    alias.validate()?;
+    db::ensure_admin(&ctx.db, &uid).await?;
+    let (tag, alias) = (normalize(&tag), normalize(&alias.name));
+    if alias.is_empty() {
+        return Err(Error::unprocessable_entity([(
+            "name",
+            "alias cannot be blank",
+        )]));
+    }
+    if alias == tag {
+        return Err(Error::unprocessable_entity([(
+            "name",
+            "alias should differ from the tag",
+        )]));
+    }
+    let mut tx = ctx.db.begin().await?;
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM tags WHERE name = $1) AS "exists!""#,
+        tag
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if !exists {
+        return Err(Error::NotFound);
+    }
+    // merging the alias (in case it is a tag itself) into the tag
+    sqlx::query!(
+        r#"
+        UPDATE articles
+        SET tags = ARRAY(
+            SELECT DISTINCT CASE WHEN t = $1 THEN $2 ELSE t END
+            FROM UNNEST(tags) t
+            ORDER BY 1
+        )
+        WHERE tags @> ARRAY[$1]
+        "#,
+        alias,
+        tag,
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO tag_follows (user_id, tag)
+        SELECT user_id, $2 FROM tag_follows WHERE tag = $1
+        ON CONFLICT DO NOTHING
+        "#,
+        alias,
+        tag,
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!("UPDATE tag_aliases SET tag = $2 WHERE tag = $1", alias, tag)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!("DELETE FROM tags WHERE name = $1", alias)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!(
+        "INSERT INTO tag_aliases (alias, tag) VALUES ($1, $2)",
+        alias,
+        tag
+    )
+    .execute(&mut *tx)
+    .await
+    .on_constraint("tag_aliases_pkey", |_| {
+        Error::unprocessable_entity([("name", "alias already registered")])
+    })?;
+    tx.commit().await?;
+    // articles having the alias have just been re-tagged
+    if let Err(e) = related::invalidate_tags(&ctx, &[alias, tag.clone()]).await {
+        warn!(error = ?e, "failed to invalidate related articles");
+    }
+    let tag = db::read_tag(&ctx.db, &tag, &uid).await?;
+    Ok((StatusCode::CREATED, Json(TagPayload { tag })))
+}
+
+/// Remove tag alias.
+///
+/// The alias will no longer be replaced with the tag. Note that the articles
+/// merged into the tag when the alias was added will not be restored.
+///
+/// Only available to administrators.
+#[utoipa::path(
+    delete,
+    path = "/{tag}/aliases/{alias}",
+    tags = ["Tags"],
+    params(
+        (
+            "tag" = String, Path,
+            description = "Tag's name.",
+            example = "language design"
+        ),
+        (
+            "alias" = String, Path,
+            description = "Tag's alias.",
+            example = "lang design"
+        ),
+    ),
+    responses(
+        (status = 204, description = "Alias successfully removed."),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 403, description = "User is not an administrator."),
+        (status = 404, description = "Tag or alias not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "REMOVE TAG ALIAS", skip(ctx))]
+pub async fn remove_alias(
+    ctx: State<Arc<AppContext>>,
+    Path((tag, alias)): Path<(String, String)>,
+    uid: UserID,
+) -> Result<StatusCode, Error> {
+    db::ensure_admin(&ctx.db, &uid).await?;
+    let result = sqlx::query!(
+        "DELETE FROM tag_aliases WHERE tag = $1 AND alias = $2",
+        normalize(&tag),
+        normalize(&alias),
+    )
+    .execute(&ctx.db)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Follow tag.
+///
+/// Articles having this tag can then be included in the user's personal feed,
+/// see `personal_feed`. If an alias is used, the tag it points to is followed.
+#[utoipa::path(
+    post,
+    path = "/{tag}/follow",
+    tags = ["Tags"],
+    params(
+        (
+            "tag" = String, Path,
+            description = "Tag's name.",
+            example = "language design"
+        ),
+    ),
+    responses(
+        (status = 200, description = "Tag successfully followed", body = TagPayload<Tag>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 404, description = "Tag not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "FOLLOW TAG", skip(ctx))]
+pub async fn follow_tag(
+    ctx: State<Arc<AppContext>>,
+    Path(tag): Path<String>,
+    uid: UserID,
+) -> Result<Json<TagPayload<Tag>>, Error> {
+    let tag = canonicalize(&ctx.db, &[tag], "tag")
+        .await?
+        .pop()
+        .ok_or(Error::NotFound)?;
+    sqlx::query!(
+        r#"
+        INSERT INTO tag_follows (user_id, tag)
+        SELECT $1, name FROM tags WHERE name = $2
+        ON CONFLICT DO NOTHING
+        "#,
+        *uid,
+        tag
+    )
+    .execute(&ctx.db)
+    .await?;
+    let tag = db::read_tag(&ctx.db, &tag, &uid).await?;
+    Ok(Json(TagPayload { tag }))
+}
+
+/// Unfollow tag.
+#[utoipa::path(
+    delete,
+    path = "/{tag}/follow",
+    tags = ["Tags"],
+    params(
+        (
+            "tag" = String, Path,
+            description = "Tag's name.",
+            example = "language design"
+        ),
+    ),
+    responses(
+        (status = 200, description = "Tag successfully unfollowed", body = TagPayload<Tag>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 404, description = "Tag not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "UNFOLLOW TAG", skip(ctx))]
+pub async fn unfollow_tag(
+    ctx: State<Arc<AppContext>>,
+    Path(tag): Path<String>,
+    uid: UserID,
+) -> Result<Json<TagPayload<Tag>>, Error> {
+    let tag = canonicalize(&ctx.db, &[tag], "tag")
+        .await?
+        .pop()
+        .ok_or(Error::NotFound)?;
+    sqlx::query!(
+        "DELETE FROM tag_follows WHERE user_id = $1 AND tag = $2",
+        *uid,
+        tag
+    )
+    .execute(&ctx.db)
+    .await?;
+    let tag = db::read_tag(&ctx.db, &tag, &uid).await?;
+    Ok(Json(TagPayload { tag }))
+}
+
+mod db {
+    use super::Tag;
+    use crate::http::errors::Error;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    /// Make sure the user is an administrator.
+    pub async fn ensure_admin(db: &PgPool, uid: &Uuid) -> Result<(), Error> {
+        let is_admin = sqlx::query_scalar!("SELECT is_admin FROM users WHERE user_id = $1", uid)
+            .fetch_optional(db)
+            .await?
+            .unwrap_or_default();
+        if !is_admin {
+            warn!("user tried to manage tags w/o proper permissions");
+            return Err(Error::Forbidden);
+        }
+        Ok(())
+    }
+
+    pub async fn read_tag(db: &PgPool, name: &str, uid: &Uuid) -> Result<Tag, Error> {
+        let tag = sqlx::query!(
+            r#"
+            SELECT
+                tag.name,
+                tag.description,
+                ARRAY(
+                    SELECT alias FROM tag_aliases WHERE tag_aliases.tag = tag.name ORDER BY alias
+                ) AS "aliases!",
+                tag.articles_count,
+                EXISTS(
+                    SELECT 1 FROM tag_follows WHERE user_id = $2 AND tag = tag.name
+                ) AS "following!"
+            FROM tags tag
+            WHERE tag.name = $1
+            "#,
+            name,
+            uid,
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or(Error::NotFound)?;
+        Ok(Tag {
+            name: tag.name,
+            description: tag.description,
+            aliases: tag.aliases,
+            articles_count: tag.articles_count as u64,
+            following: tag.following,
+        })
+    }
+}
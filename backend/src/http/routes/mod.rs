@@ -1,5 +1,6 @@
 pub(crate) mod articles;
 pub(crate) mod healthz;
+pub(crate) mod stream;
 pub(crate) mod users;
 
 #[cfg(debug_assertions)]
@@ -0,0 +1,114 @@
+use crate::AppContext;
+use crate::http::errors::{Error, Validation};
+use crate::http::extractors::UserID;
+use crate::services::events::Topic;
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Query, State};
+use axum::http::HeaderMap;
+use axum::response::sse::{Event as SseEvent, KeepAlive, Sse};
+use futures_util::{Stream, StreamExt as _};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::IntoParams;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+/// How often a comment line is sent to keep the connection alive.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct StreamQuery {
+    /// Slug of the article being viewed, if any.
+    ///
+    /// When provided, new comments under this article are streamed.
+    #[param(nullable = false, example = "why-memory-safety-matters")]
+    article: Option<String>,
+}
+
+/// Stream events.
+///
+/// Server-sent events stream delivering, as they happen:
+///
+/// - `notification` events with the new (or updated) notification and
+///   the current user's unread notifications count;
+/// - `article` events with articles published by the authors the current user
+///   is following;
+/// - `comment` events with new comments under the article being viewed, if
+///   `article` has been provided.
+///
+/// Articles and comments are the same for all of the recipients, and so their
+/// viewer-specific attributes (e.g. `following`) are not to be relied upon.
+///
+/// Each event comes with an `id`, and so the client can send the last received
+/// one in the `Last-Event-ID` header when reconnecting to get the events it has
+/// missed (as long as they are still retained by the server). A comment line is
+/// sent periodically to keep the connection alive.
+#[utoipa::path(
+    get,
+    path = "/stream",
+    tags = ["Stream"],
+    params(
+        StreamQuery,
+        (
+            "Last-Event-ID" = Option<u64>, Header,
+            description = "Identifier of the last received event.",
+        ),
+    ),
+    responses(
+        (status = 200, description = "Events stream", content_type = "text/event-stream", body = String),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 404, description = "Article not found."),
+        (status = 422, description = "Invalid query or Last-Event-ID header.", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "STREAM EVENTS", skip(ctx, headers))]
+pub(in crate::http) async fn stream_events(
+    ctx: State<Arc<AppContext>>,
+    q: Result<Query<StreamQuery>, QueryRejection>,
+    headers: HeaderMap,
+    uid: UserID,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, Error> {
+    let Query(q) = q?;
+    let last_event_id = headers
+        .get("last-event-id")
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .ok_or_else(|| {
+                    Error::unprocessable_entity([("header", "Last-Event-ID is not a valid id")])
+                })
+        })
+        .transpose()?;
+
+    let mut topics = vec![Topic::User(*uid)];
+    if let Some(slug) = q.article {
+        let article_id =
+            sqlx::query_scalar!("SELECT article_id FROM articles WHERE slug = $1", slug)
+                .fetch_optional(&ctx.db)
+                .await?
+                .ok_or(Error::NotFound)?;
+        topics.push(Topic::Article(article_id));
+    }
+
+    let events = ctx.events.subscribe(&topics, last_event_id).await?;
+    let events = events.map(|event| {
+        Ok(SseEvent::default()
+            .id(event.id.to_string())
+            .event(event.name)
+            .data(event.data.to_string()))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::new().interval(HEARTBEAT_INTERVAL)))
+}
+
+// ------------------------------- ROUTER --------------------------------------
+pub(crate) fn router(ctx: Arc<AppContext>) -> OpenApiRouter {
+    OpenApiRouter::new()
+        .routes(routes!(stream_events))
+        .with_state(ctx)
+}
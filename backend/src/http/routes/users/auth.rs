@@ -54,8 +54,12 @@ pub(crate) async fn login(
 
     let user_row = sqlx::query!(
         r#"
-            SELECT user_id, username, email, bio, image, status, password_hash 
-            FROM users 
+            SELECT
+                user_id, username, email, bio, image, status, password_hash,
+                ARRAY(
+                    SELECT tag FROM tag_follows WHERE user_id = users.user_id ORDER BY tag
+                ) AS "followed_tags!"
+            FROM users
             WHERE email = $1
         "#,
         &user.email
@@ -83,6 +87,7 @@ pub(crate) async fn login(
             username: user_row.username,
             bio: user_row.bio,
             image: utils::parse_image_url(user_row.image.as_deref())?,
+            followed_tags: user_row.followed_tags,
         },
     };
 
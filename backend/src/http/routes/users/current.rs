@@ -37,7 +37,13 @@ pub(crate) async fn read_current_user(
 ) -> Result<Json<UserPayload<User>>, Error> {
     let user = sqlx::query!(
         r#"
-        SELECT email, username, bio, image FROM users WHERE user_id = $1
+        SELECT
+            email, username, bio, image,
+            ARRAY(
+                SELECT tag FROM tag_follows WHERE user_id = users.user_id ORDER BY tag
+            ) AS "followed_tags!"
+        FROM users
+        WHERE user_id = $1
         "#,
         *uid
     )
@@ -52,6 +58,7 @@ pub(crate) async fn read_current_user(
             username: user.username,
             bio: user.bio,
             image: utils::parse_image_url(user.image.as_deref())?,
+            followed_tags: user.followed_tags,
         },
     };
     Ok(Json(payload))
@@ -70,6 +77,7 @@ pub struct UserUpdate {
     /// in the system.
     #[schema(nullable = false, example = "rob.pike1984")]
     #[validate(length(min = 1, message = "username cannot be empty"))]
+    #[validate(does_not_contain(pattern = ",", message = "username cannot contain commas"))]
     username: Option<String>,
 
     /// User's biography.
@@ -139,7 +147,10 @@ pub(crate) async fn update_current_user(
                 password_hash = coalesce($4, "users".password_hash),
                 image = coalesce($5, "users".image)
             WHERE user_id = $6
-            returning email, username, bio, image
+            returning email, username, bio, image,
+                ARRAY(
+                    SELECT tag FROM tag_follows WHERE user_id = "users".user_id ORDER BY tag
+                ) AS "followed_tags!"
         "#,
         user.email,
         user.username,
@@ -166,6 +177,7 @@ pub(crate) async fn update_current_user(
             username: updated_user.username,
             bio: updated_user.bio,
             image: utils::parse_image_url(updated_user.image.as_deref())?,
+            followed_tags: updated_user.followed_tags,
         },
     };
 
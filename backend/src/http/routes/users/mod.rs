@@ -6,8 +6,11 @@ use utoipa_axum::router::OpenApiRouter;
 
 mod auth;
 mod current;
+pub(in crate::http) mod notifications;
+mod preferences;
 mod profiles;
 mod register;
+mod stats;
 pub(crate) mod utils;
 
 // ---------------------------- SHARED TYPES -----------------------------------
@@ -36,6 +39,11 @@ pub(crate) struct User {
     /// Location of user's image (if any).
     #[schema(required = true)]
     image: Option<Url>,
+
+    /// Tags the user is following, in alphabetical order.
+    #[serde(rename = "followedTags")]
+    #[schema(example = json!(vec!["rust".to_string(), "language design".to_string()]))]
+    followed_tags: Vec<String>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -75,16 +83,27 @@ pub(crate) struct UserProfilePayload<U> {
 
 // ------------------------------- ROUTER --------------------------------------
 pub(crate) fn router(ctx: Arc<AppContext>) -> OpenApiRouter {
-    let user_router = OpenApiRouter::new().routes(routes!(
-        current::read_current_user,
-        current::update_current_user,
-    ));
-
-    let user_profile = OpenApiRouter::new().routes(routes!(
-        profiles::profile,
-        profiles::follow_profile,
-        profiles::unfollow_profile,
-    ));
+    let user_router = OpenApiRouter::new()
+        .routes(routes!(
+            current::read_current_user,
+            current::update_current_user,
+        ))
+        .routes(routes!(stats::author_stats))
+        .routes(routes!(notifications::list_notifications))
+        .routes(routes!(notifications::mark_notification_read))
+        .routes(routes!(notifications::mark_all_notifications_read))
+        .routes(routes!(
+            preferences::read_email_preferences,
+            preferences::update_email_preferences,
+        ));
+
+    let user_profile = OpenApiRouter::new()
+        .routes(routes!(
+            profiles::profile,
+            profiles::follow_profile,
+            profiles::unfollow_profile,
+        ))
+        .routes(routes!(profiles::block_profile, profiles::unblock_profile));
 
     let auth_router = OpenApiRouter::new()
         .routes(routes!(register::register_user))
@@ -92,7 +111,11 @@ pub(crate) fn router(ctx: Arc<AppContext>) -> OpenApiRouter {
         // got `POST` user registration, this route should be attached via
         // a separate `routes!` call: https://stackoverflow.com/a/79303329
         .routes(routes!(auth::login))
-        .routes(routes!(register::confirm_email));
+        .routes(routes!(register::confirm_email))
+        .routes(routes!(
+            preferences::confirm_unsubscription,
+            preferences::unsubscribe,
+        ));
 
     OpenApiRouter::new()
         .nest("/user", user_router)
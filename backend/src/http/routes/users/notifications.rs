@@ -0,0 +1,489 @@
+use super::UserProfile;
+use crate::AppContext;
+use crate::http::errors::{Error, Validation};
+use crate::http::extractors::UserID;
+use crate::http::routes::users::utils::parse_image_url;
+use crate::http::utils;
+use crate::services::events::Topic;
+use axum::Json;
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use sqlx::types::Json as SqlxJson;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::Validate;
+use validator_derive::Validate;
+
+const DEFAULT_LIMIT: usize = 20;
+
+/// How many of the latest actors are listed with a notification.
+const MAX_ACTORS: i64 = 3;
+
+/// What happened.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum NotificationKind {
+    /// Someone followed the user.
+    Follow,
+
+    /// Someone favorited the user's article, see `article`.
+    Favorite,
+
+    /// Someone commented on the user's article, see `article`.
+    Comment,
+
+    /// Someone replied to the user's comment, see `commentId`.
+    Reply,
+
+    /// Someone mentioned the user in an article or a comment, see `article`
+    /// and `commentId`.
+    Mention,
+}
+
+/// Article a notification is about.
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct NotificationArticle {
+    /// Article's slug.
+    #[schema(example = "how-to-train-your-dragon", format = "slug")]
+    slug: String,
+
+    /// Article's title.
+    #[schema(example = "How to train your dragon")]
+    title: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Notification {
+    /// Notification's identifier.
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    id: Uuid,
+
+    /// What happened.
+    kind: NotificationKind,
+
+    /// The latest (up to 3) users behind this notification.
+    actors: Vec<UserProfile>,
+
+    /// How many users are behind this notification.
+    ///
+    /// Similar unread notifications are aggregated, e.g. with `kind` being
+    /// `favorite` and `actorsCount` being `5`, this reads as "5 people
+    /// favorited your article".
+    #[schema(examples(5))]
+    actors_count: u64,
+
+    /// Article this notification is about, if any.
+    #[schema(required = true)]
+    article: Option<NotificationArticle>,
+
+    /// Comment this notification is about, if any.
+    ///
+    /// For replies, this is the user's comment that has been replied to.
+    #[schema(required = true)]
+    comment_id: Option<Uuid>,
+
+    /// Whether the user has read this notification.
+    read: bool,
+
+    /// When the latest of the actors acted.
+    notified_at: DateTime<Utc>,
+}
+
+/// Container for the notifications list endpoint.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NotificationsList {
+    /// List of notifications, the latest first.
+    notifications: Vec<Notification>,
+
+    /// How many unread notifications the user has got.
+    #[schema(examples(3))]
+    unread_count: u64,
+
+    /// Cursor to fetch the next page of notifications, if any.
+    #[schema(required = true)]
+    next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct NotificationsQuery {
+    /// Only list unread notifications.
+    #[param(nullable = false, default = false)]
+    unread: Option<bool>,
+
+    /// Limit number of returned notifications.
+    #[param(nullable = false, default = 20, maximum = 100)]
+    #[validate(range(min = 1, max = 100, message = "limit should be between 1 and 100"))]
+    limit: Option<usize>,
+
+    /// Cursor to fetch notifications starting from.
+    ///
+    /// Use `nextCursor` from a previously returned page.
+    #[param(nullable = false)]
+    cursor: Option<String>,
+}
+
+/// Position in notifications list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NotificationsCursor {
+    #[serde(rename = "t")]
+    notified_at: DateTime<Utc>,
+
+    #[serde(rename = "id")]
+    notification_id: Uuid,
+}
+
+/// Notification's actor as fetched from the database.
+#[derive(Debug, Deserialize)]
+struct ActorRow {
+    username: String,
+    bio: String,
+    image: Option<String>,
+    following: bool,
+}
+
+/// List notifications.
+///
+/// Notifications about the current user's followers, favorites of and
+/// comments under their articles, replies to their comments and mentions,
+/// the latest first. An unread notification is aggregated with the similar
+/// ones (e.g. all the favorites of an article), and goes to the top of the
+/// list once there is a new actor.
+#[utoipa::path(
+    get,
+    path = "/notifications",
+    tags = ["Users"],
+    params(NotificationsQuery),
+    responses(
+        (status = 200, description = "Notifications successfully retrieved", body = NotificationsList),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 422, description = "Invalid query", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "LIST NOTIFICATIONS", skip(ctx))]
+pub(crate) async fn list_notifications(
+    ctx: State<Arc<AppContext>>,
+    q: Result<Query<NotificationsQuery>, QueryRejection>,
+    uid: UserID,
+) -> Result<Json<NotificationsList>, Error> {
+    let Query(q) = q?;
+    q.validate()?;
+    let cursor = q
+        .cursor
+        .as_deref()
+        .map(utils::decode_cursor::<NotificationsCursor>)
+        .transpose()?;
+    let limit = q.limit.unwrap_or(DEFAULT_LIMIT);
+
+    // NB: we are fetching one extra row to tell if there is a next page
+    let mut notifications =
+        fetch_notifications(&ctx.db, &uid, None, q.unread, cursor.as_ref(), limit + 1).await?;
+    let next_cursor = if notifications.len() > limit {
+        notifications.truncate(limit);
+        notifications.last().map(|notification| {
+            utils::encode_cursor(&NotificationsCursor {
+                notified_at: notification.notified_at,
+                notification_id: notification.id,
+            })
+        })
+    } else {
+        None
+    };
+    let unread_count = count_unread(&ctx.db, &uid).await?;
+
+    let payload = NotificationsList {
+        notifications,
+        unread_count,
+        next_cursor,
+    };
+    Ok(Json(payload))
+}
+
+/// Mark notification as read.
+///
+/// Note that this operation is idempotent. Once read, the notification is no
+/// longer aggregated with the new similar ones.
+#[utoipa::path(
+    post,
+    path = "/notifications/{notification_id}/read",
+    tags = ["Users"],
+    params(
+        (
+            "notification_id" = String, Path,
+            format = Uuid,
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ),
+    responses(
+        (status = 204, description = "Notification marked as read"),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 404, description = "Notification not found"),
+        (status = 422, description = "Malformed notification_id in path", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "MARK NOTIFICATION READ", skip(ctx))]
+pub(crate) async fn mark_notification_read(
+    ctx: State<Arc<AppContext>>,
+    Path(notification_id): Path<String>,
+    uid: UserID,
+) -> Result<StatusCode, Error> {
+    let notification_id = Uuid::parse_str(&notification_id).map_err(|_| {
+        Error::unprocessable_entity([("path", "notification_id is not a valid UUID")])
+    })?;
+    sqlx::query_scalar!(
+        r#"
+        UPDATE notifications SET read_at = COALESCE(read_at, NOW())
+        WHERE notification_id = $1 AND user_id = $2
+        RETURNING notification_id
+        "#,
+        notification_id,
+        *uid,
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Mark all notifications as read.
+///
+/// Note that this operation is idempotent.
+#[utoipa::path(
+    post,
+    path = "/notifications/read",
+    tags = ["Users"],
+    responses(
+        (status = 204, description = "Notifications marked as read"),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "MARK ALL NOTIFICATIONS READ", skip(ctx))]
+pub(crate) async fn mark_all_notifications_read(
+    ctx: State<Arc<AppContext>>,
+    uid: UserID,
+) -> Result<StatusCode, Error> {
+    sqlx::query!(
+        "UPDATE notifications SET read_at = NOW() WHERE user_id = $1 AND read_at IS NULL",
+        *uid,
+    )
+    .execute(&ctx.db)
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ------------------------------- UTILS ---------------------------------------
+
+/// Payload of the `notification` event.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NotificationEvent {
+    notification: Notification,
+    unread_count: u64,
+}
+
+/// Stream the notifications produced by the actor's action to their recipients
+/// (and email them, if they want to be emailed about such things right away).
+///
+/// Only the notifications the actor has just been added to are delivered, so
+/// that repeating an action (e.g. following the user again after unfollowing
+/// them) does not deliver the notification over again, see `notify` function
+/// in the migrations.
+///
+/// To be called after an action which might have notified someone (once it
+/// has been committed). Since this is best-effort, failures are logged rather
+/// than returned.
+pub(in crate::http) async fn publish(ctx: &Arc<AppContext>, actor_id: &Uuid) {
+    let deliveries = match sqlx::query!(
+        r#"
+        DELETE FROM notification_deliveries delivery
+        USING notifications notification
+        WHERE
+            delivery.notification_id = notification.notification_id AND
+            delivery.actor_id = $1
+        RETURNING notification.notification_id, notification.user_id
+        "#,
+        actor_id,
+    )
+    .fetch_all(&ctx.db)
+    .await
+    {
+        Ok(deliveries) => deliveries,
+        Err(e) => {
+            warn!(error = ?e, "failed to claim notifications");
+            return;
+        }
+    };
+    for delivery in deliveries {
+        let recipient = &delivery.user_id;
+        let outcome = async {
+            let Some(notification) = fetch_notifications(
+                &ctx.db,
+                recipient,
+                Some(&delivery.notification_id),
+                None,
+                None,
+                1,
+            )
+            .await?
+            .pop() else {
+                return Ok(());
+            };
+            // emailing takes a while, and the actor should not be waiting
+            let notifier_ctx = Arc::clone(ctx);
+            let notification_id = notification.id;
+            tokio::spawn(async move {
+                let db = &notifier_ctx.db;
+                if let Err(e) = notifier_ctx.notifier.notify(db, notification_id).await {
+                    warn!(error = ?e, "failed to email notification");
+                }
+            });
+            let unread_count = count_unread(&ctx.db, recipient).await?;
+            let event = NotificationEvent {
+                notification,
+                unread_count,
+            };
+            ctx.events
+                .publish(&[Topic::User(*recipient)], "notification", &event)
+                .await?;
+            Ok::<_, Error>(())
+        };
+        if let Err(e) = outcome.await {
+            warn!(error = ?e, "failed to publish notification");
+        }
+    }
+}
+
+/// Fetch the user's notifications (or just the one), the latest first.
+async fn fetch_notifications(
+    db: &PgPool,
+    user_id: &Uuid,
+    notification_id: Option<&Uuid>,
+    unread: Option<bool>,
+    cursor: Option<&NotificationsCursor>,
+    limit: usize,
+) -> Result<Vec<Notification>, Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            notification.notification_id,
+            to_jsonb(notification.kind) AS "kind!: SqlxJson<NotificationKind>",
+            notification.comment_id,
+            notification.read_at IS NOT NULL AS "read!",
+            notification.notified_at,
+            article.slug AS "article_slug?",
+            article.title AS "article_title?",
+            (
+                SELECT count(*) FROM notification_actors
+                WHERE notification_id = notification.notification_id
+            ) AS "actors_count!",
+            (
+                SELECT coalesce(jsonb_agg(actor ORDER BY acted_at DESC), '[]')
+                FROM (
+                    SELECT
+                        actor.username,
+                        actor.bio,
+                        actor.image,
+                        EXISTS(
+                            SELECT 1 FROM follows
+                            WHERE followed_user_id = actor.user_id AND following_user_id = $1
+                        ) AS following,
+                        notification_actor.created_at AS acted_at
+                    FROM notification_actors notification_actor JOIN users actor USING (user_id)
+                    WHERE notification_actor.notification_id = notification.notification_id
+                    ORDER BY notification_actor.created_at DESC
+                    LIMIT $6
+                ) actor
+            ) AS "actors!: SqlxJson<Vec<ActorRow>>"
+        FROM notifications notification LEFT JOIN articles article USING (article_id)
+        WHERE
+            notification.user_id = $1 AND
+            ($7::UUID IS NULL OR notification.notification_id = $7) AND
+            ($2::BOOLEAN IS NOT TRUE OR notification.read_at IS NULL) AND
+            (
+                $3::TIMESTAMPTZ IS NULL OR
+                (notification.notified_at, notification.notification_id) < ($3, $4::UUID)
+            ) AND
+            -- all of the actors might have been deleted
+            EXISTS(
+                SELECT 1 FROM notification_actors
+                WHERE notification_id = notification.notification_id
+            )
+        ORDER BY notification.notified_at DESC, notification.notification_id DESC
+        LIMIT $5
+        "#,
+        user_id,
+        unread,
+        cursor.map(|c| c.notified_at),
+        cursor.map(|c| c.notification_id),
+        limit as i64,
+        MAX_ACTORS,
+        notification_id,
+    )
+    .fetch_all(db)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let actors = row
+                .actors
+                .0
+                .into_iter()
+                .map(|actor| {
+                    Ok(UserProfile {
+                        username: actor.username,
+                        bio: actor.bio,
+                        image: parse_image_url(actor.image.as_deref())?,
+                        following: actor.following,
+                    })
+                })
+                .collect::<Result<_, Error>>()?;
+            let article = row
+                .article_slug
+                .zip(row.article_title)
+                .map(|(slug, title)| NotificationArticle { slug, title });
+            Ok(Notification {
+                id: row.notification_id,
+                kind: row.kind.0,
+                actors,
+                actors_count: row.actors_count as u64,
+                article,
+                comment_id: row.comment_id,
+                read: row.read,
+                notified_at: row.notified_at,
+            })
+        })
+        .collect()
+}
+
+/// Count the user's unread notifications.
+async fn count_unread(db: &PgPool, user_id: &Uuid) -> Result<u64, Error> {
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT count(*) AS "count!"
+        FROM notifications notification
+        WHERE
+            user_id = $1 AND
+            read_at IS NULL AND
+            EXISTS(
+                SELECT 1 FROM notification_actors
+                WHERE notification_id = notification.notification_id
+            )
+        "#,
+        user_id,
+    )
+    .fetch_one(db)
+    .await?;
+    Ok(count as u64)
+}
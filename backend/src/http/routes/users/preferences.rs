@@ -0,0 +1,293 @@
+use crate::AppContext;
+use crate::http::errors::{Error, ResultExt as _, Validation};
+use crate::http::extractors::UserID;
+use crate::services::notifier::{Delivery, EmailTopic, verify_unsubscribe_token};
+use crate::templates::UnsubscribePage;
+use axum::Json;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Query, State};
+use axum::response::Html;
+use sqlx::types::Json as SqlxJson;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+/// How the user wants to be emailed about each topic.
+///
+/// Nothing is emailed about a topic unless the user has opted in to it.
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct EmailPreferences {
+    /// New followers.
+    follow: Delivery,
+
+    /// Favorites of the user's articles.
+    favorite: Delivery,
+
+    /// Comments under the user's articles.
+    comment: Delivery,
+
+    /// Replies to the user's comments.
+    reply: Delivery,
+
+    /// Mentions of the user in articles and comments.
+    mention: Delivery,
+
+    /// New articles of the authors the user is following.
+    article: Delivery,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub(crate) struct EmailPreferencesUpdate {
+    /// New followers.
+    #[schema(nullable = false)]
+    follow: Option<Delivery>,
+
+    /// Favorites of the user's articles.
+    #[schema(nullable = false)]
+    favorite: Option<Delivery>,
+
+    /// Comments under the user's articles.
+    #[schema(nullable = false)]
+    comment: Option<Delivery>,
+
+    /// Replies to the user's comments.
+    #[schema(nullable = false)]
+    reply: Option<Delivery>,
+
+    /// Mentions of the user in articles and comments.
+    #[schema(nullable = false)]
+    mention: Option<Delivery>,
+
+    /// New articles of the authors the user is following.
+    #[schema(nullable = false)]
+    article: Option<Delivery>,
+}
+
+/// Container for email preferences endpoints.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub(crate) struct EmailPreferencesPayload<P> {
+    preferences: P,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct UnsubscribeQuery {
+    /// Unsubscribe token, as found in the link of a notifications email.
+    token: String,
+}
+
+/// Read email preferences.
+///
+/// How (and whether) the current user wants to be emailed about each topic:
+/// right away, in the daily or weekly digest, or not at all (which is
+/// the default for the topics the user has not opted in to).
+#[utoipa::path(
+    get,
+    path = "/email-preferences",
+    tags = ["Users"],
+    responses(
+        (status = 200, description = "Email preferences successfully retrieved", body = EmailPreferencesPayload<EmailPreferences>),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "READ EMAIL PREFERENCES", skip(ctx))]
+pub(crate) async fn read_email_preferences(
+    ctx: State<Arc<AppContext>>,
+    uid: UserID,
+) -> Result<Json<EmailPreferencesPayload<EmailPreferences>>, Error> {
+    let preferences = fetch_preferences(&ctx, &uid).await?;
+    Ok(Json(EmailPreferencesPayload { preferences }))
+}
+
+/// Update email preferences.
+///
+/// Only the provided topics are updated.
+#[utoipa::path(
+    put,
+    path = "/email-preferences",
+    tags = ["Users"],
+    request_body(
+        content = EmailPreferencesPayload<EmailPreferencesUpdate>,
+        description = "Email preferences to update",
+    ),
+    responses(
+        (status = 200, description = "Email preferences successfully updated", body = EmailPreferencesPayload<EmailPreferences>),
+        (status = 400, description = "Malformed JSON"),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 415, description = "Method not allow / Content-Type is incorrect"),
+        (status = 422, description = "Invalid email preferences", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "UPDATE EMAIL PREFERENCES", skip(ctx, input))]
+pub(crate) async fn update_email_preferences(
+    ctx: State<Arc<AppContext>>,
+    uid: UserID,
+    input: Result<Json<EmailPreferencesPayload<EmailPreferencesUpdate>>, JsonRejection>,
+) -> Result<Json<EmailPreferencesPayload<EmailPreferences>>, Error> {
+    let EmailPreferencesPayload { preferences } = input?.0;
+    let (topics, deliveries): (Vec<_>, Vec<_>) = [
+        (EmailTopic::Follow, preferences.follow),
+        (EmailTopic::Favorite, preferences.favorite),
+        (EmailTopic::Comment, preferences.comment),
+        (EmailTopic::Reply, preferences.reply),
+        (EmailTopic::Mention, preferences.mention),
+        (EmailTopic::Article, preferences.article),
+    ]
+    .into_iter()
+    .filter_map(|(topic, delivery)| Some((topic.as_str(), delivery?.as_str())))
+    .unzip();
+    store_preferences(&ctx, &uid, &topics, &deliveries).await?;
+    let preferences = fetch_preferences(&ctx, &uid).await?;
+    Ok(Json(EmailPreferencesPayload { preferences }))
+}
+
+/// Confirm unsubscription.
+///
+/// Renders a page asking the user to confirm that they want to unsubscribe
+/// (this is where the links in the emails lead to, and the mere visit should
+/// not unsubscribe the user, since the links are visited by the mail scanners
+/// as well).
+#[utoipa::path(
+    get,
+    path = "/unsubscribe",
+    tags = ["Users"],
+    params(UnsubscribeQuery),
+    responses(
+        (status = 200, description = "Unsubscribe confirmation page", content_type = "text/html", body = String),
+        (status = 422, description = "Invalid token", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(/* authentication NOT required */),
+)]
+#[instrument(name = "CONFIRM UNSUBSCRIPTION", skip_all)]
+pub(crate) async fn confirm_unsubscription(
+    ctx: State<Arc<AppContext>>,
+    q: Result<Query<UnsubscribeQuery>, QueryRejection>,
+) -> Result<Html<String>, Error> {
+    let Query(q) = q?;
+    let (_, topic) = verify(&ctx, &q.token)?;
+    let action_url = format!("?token={}", q.token);
+    let page = UnsubscribePage {
+        topic: describe(topic),
+        unsubscribed: false,
+        action_url: &action_url,
+        app_url: &ctx.frontend_url,
+    };
+    Ok(Html(page.to_string()))
+}
+
+/// Unsubscribe.
+///
+/// Turns the emails about the token's topic (or all the notification emails)
+/// off. This endpoint supports one-click unsubscription (see RFC 8058), and
+/// the operation is idempotent.
+#[utoipa::path(
+    post,
+    path = "/unsubscribe",
+    tags = ["Users"],
+    params(UnsubscribeQuery),
+    responses(
+        (status = 200, description = "Successfully unsubscribed", content_type = "text/html", body = String),
+        (status = 422, description = "Invalid token", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(/* authentication NOT required */),
+)]
+#[instrument(name = "UNSUBSCRIBE", skip_all)]
+pub(crate) async fn unsubscribe(
+    ctx: State<Arc<AppContext>>,
+    q: Result<Query<UnsubscribeQuery>, QueryRejection>,
+) -> Result<Html<String>, Error> {
+    let Query(q) = q?;
+    let (user_id, topic) = verify(&ctx, &q.token)?;
+    let topics: Vec<_> = match topic {
+        Some(topic) => vec![topic.as_str()],
+        None => EmailTopic::ALL.iter().map(|topic| topic.as_str()).collect(),
+    };
+    let deliveries = vec![Delivery::Off.as_str(); topics.len()];
+    store_preferences(&ctx, &user_id, &topics, &deliveries)
+        .await
+        .map_err(|e| match e {
+            // the user has been deleted in the meantime
+            Error::Unauthorized => {
+                Error::unprocessable_entity([("token", "invalid unsubscribe token")])
+            }
+            e => e,
+        })?;
+    let page = UnsubscribePage {
+        topic: describe(topic),
+        unsubscribed: true,
+        action_url: "",
+        app_url: &ctx.frontend_url,
+    };
+    Ok(Html(page.to_string()))
+}
+
+// ------------------------------- UTILS ---------------------------------------
+
+fn verify(ctx: &AppContext, token: &str) -> Result<(uuid::Uuid, Option<EmailTopic>), Error> {
+    verify_unsubscribe_token(token, &ctx.dec_key)
+        .map_err(|_| Error::unprocessable_entity([("token", "invalid unsubscribe token")]))
+}
+
+fn describe(topic: Option<EmailTopic>) -> &'static str {
+    topic.map_or("anything", |topic| topic.describe())
+}
+
+async fn fetch_preferences(
+    ctx: &AppContext,
+    user_id: &uuid::Uuid,
+) -> Result<EmailPreferences, Error> {
+    let topics: Vec<_> = EmailTopic::ALL.iter().map(|topic| topic.as_str()).collect();
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            to_jsonb(topic) AS "topic!: SqlxJson<EmailTopic>",
+            to_jsonb(email_delivery($1, topic)) AS "delivery!: SqlxJson<Delivery>"
+        FROM unnest($2::TEXT[]) topic
+        "#,
+        user_id,
+        &topics as &[&str],
+    )
+    .fetch_all(&ctx.db)
+    .await?;
+    let delivery = |topic: EmailTopic| {
+        rows.iter()
+            .find(|row| row.topic.0 == topic)
+            .map(|row| row.delivery.0)
+            .ok_or_else(|| anyhow!("no delivery for {}", topic.as_str()))
+    };
+    Ok(EmailPreferences {
+        follow: delivery(EmailTopic::Follow)?,
+        favorite: delivery(EmailTopic::Favorite)?,
+        comment: delivery(EmailTopic::Comment)?,
+        reply: delivery(EmailTopic::Reply)?,
+        mention: delivery(EmailTopic::Mention)?,
+        article: delivery(EmailTopic::Article)?,
+    })
+}
+
+async fn store_preferences(
+    ctx: &AppContext,
+    user_id: &uuid::Uuid,
+    topics: &[&str],
+    deliveries: &[&str],
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO email_preferences (user_id, kind, delivery)
+        SELECT $1, kind, delivery FROM unnest($2::TEXT[], $3::TEXT[]) AS preference (kind, delivery)
+        ON CONFLICT (user_id, kind) DO UPDATE SET delivery = EXCLUDED.delivery
+        "#,
+        user_id,
+        topics as &[&str],
+        deliveries as &[&str],
+    )
+    .execute(&ctx.db)
+    .await
+    .on_constraint("email_preferences_user_id_fkey", |_| Error::Unauthorized)?;
+    Ok(())
+}
@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
-use super::{UserProfile, UserProfilePayload};
+use super::{UserProfile, UserProfilePayload, notifications};
 use crate::AppContext;
 use crate::http::errors::{Error, ResultExt, Validation};
 use crate::http::extractors::{MaybeUserID, UserID};
 use crate::http::routes::users::utils::parse_image_url;
 use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
 
 /// Get user profile.
 ///
@@ -110,13 +111,15 @@ pub(crate) async fn follow_profile(
                 SELECT $2, target.user_id, NOW()
                 FROM target
                 ON CONFLICT DO NOTHING
+                RETURNING followed_user_id
             )
             SELECT
                 target.username,
                 target.bio,
                 target.image,
                 target.user_id,
-                TRUE AS "user_following!"
+                TRUE AS "user_following!",
+                EXISTS(SELECT 1 FROM inserted) AS "newly_following!"
             FROM target
             "#,
         username,
@@ -127,6 +130,10 @@ pub(crate) async fn follow_profile(
     .on_constraint("follows_no_self_follow", |_| Error::BadRequest)?
     .ok_or(Error::NotFound)?;
 
+    if target_user.newly_following {
+        notifications::publish(&ctx, &uid).await;
+    }
+
     let payload = UserProfilePayload {
         profile: UserProfile {
             username: target_user.username,
@@ -200,3 +207,101 @@ pub(crate) async fn unfollow_profile(
 
     Ok(Json(payload))
 }
+
+/// Block user profile.
+///
+/// The blocked user's mentions of the current user will no longer notify them.
+#[utoipa::path(
+    post,
+    path = "/{username}/block",
+    tags = ["Profiles"],
+    params(
+        (
+            "username" = String, Path
+        ),
+    ),
+    responses(
+        (status = 204, description = "User successfully blocked"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized", body = Validation),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "BLOCK USER PROFILE", skip(ctx))]
+pub(crate) async fn block_profile(
+    ctx: State<Arc<AppContext>>,
+    Path(username): Path<String>,
+    uid: UserID,
+) -> Result<StatusCode, Error> {
+    sqlx::query_scalar!(
+        r#"
+            WITH
+                target AS (
+                    SELECT user_id FROM users WHERE username = $1
+                ),
+                _inserted AS (
+                    INSERT INTO blocks (blocking_user_id, blocked_user_id)
+                    SELECT $2, target.user_id
+                    FROM target
+                    ON CONFLICT DO NOTHING
+                )
+            SELECT user_id FROM target
+            "#,
+        username,
+        uid.0,
+    )
+    .fetch_optional(&ctx.db)
+    .await
+    .on_constraint("blocks_no_self_block", |_| Error::BadRequest)?
+    .ok_or(Error::NotFound)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Unblock user profile.
+#[utoipa::path(
+    delete,
+    path = "/{username}/block",
+    tags = ["Profiles"],
+    params(
+        (
+            "username" = String, Path
+        ),
+    ),
+    responses(
+        (status = 204, description = "User successfully unblocked"),
+        (status = 401, description = "Unauthorized", body = Validation),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "UNBLOCK USER PROFILE", skip(ctx))]
+pub(crate) async fn unblock_profile(
+    ctx: State<Arc<AppContext>>,
+    Path(username): Path<String>,
+    uid: UserID,
+) -> Result<StatusCode, Error> {
+    sqlx::query_scalar!(
+        r#"
+            WITH
+                target AS (
+                    SELECT user_id FROM users WHERE username = $1
+                ),
+                _deleted AS (
+                    DELETE FROM blocks
+                    WHERE blocking_user_id = $2 AND blocked_user_id = (SELECT user_id FROM target)
+                )
+            SELECT user_id FROM target
+            "#,
+        username,
+        uid.0,
+    )
+    .fetch_optional(&ctx.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -39,6 +39,7 @@ pub struct Registration {
     /// in the system.
     #[schema(example = "rob.pike1984")]
     #[validate(length(min = 1, message = "username cannot be empty"))]
+    #[validate(does_not_contain(pattern = ",", message = "username cannot contain commas"))]
     username: String,
 
     /// User's password.
@@ -151,6 +152,7 @@ pub(crate) async fn register_user(
             username: user.username,
             bio: "".into(),
             image: None,
+            followed_tags: Vec::new(),
         },
     };
 
@@ -233,6 +235,7 @@ pub(crate) async fn confirm_email(
             username: user_row.username,
             bio: "".into(),
             image: None,
+            followed_tags: Vec::new(),
         },
     };
 
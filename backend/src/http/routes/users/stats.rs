@@ -0,0 +1,184 @@
+use crate::AppContext;
+use crate::http::errors::{Error, Validation};
+use crate::http::extractors::UserID;
+use crate::http::routes::articles::{DailyStats, DailyStatsRow, StatsQuery};
+use axum::Json;
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Query, State};
+use std::sync::Arc;
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArticleSummary {
+    /// Article's slug.
+    #[schema(example = "how-to-train-your-dragon", format = "slug")]
+    slug: String,
+
+    /// Article's title.
+    #[schema(example = "How to train your dragon")]
+    title: String,
+
+    /// Total number of views.
+    views: u64,
+
+    /// Total number of read-throughs.
+    reads: u64,
+
+    /// How many users favorited this article.
+    favorites_count: u64,
+
+    /// How many comments have been left.
+    comments_count: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuthorStats {
+    /// Total number of views across all of the author's articles.
+    views: u64,
+
+    /// Total number of read-throughs across all of the author's articles.
+    reads: u64,
+
+    /// How many times the author's articles have been favorited.
+    favorites_count: u64,
+
+    /// How many comments have been left under the author's articles.
+    comments_count: u64,
+
+    /// Day-by-day stats across all of the author's articles, the earliest
+    /// day first.
+    daily: Vec<DailyStats>,
+
+    /// Per-article stats, the most viewed articles first.
+    articles: Vec<ArticleSummary>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct AuthorStatsPayload {
+    stats: AuthorStats,
+}
+
+/// Author dashboard.
+///
+/// Views, read-throughs, favorites and comments across all of the current
+/// user's articles, both day-by-day and per article.
+///
+/// Note that views and read-throughs are written to the database periodically,
+/// and so the stats might be lagging behind a bit.
+#[utoipa::path(
+    get,
+    path = "/stats",
+    tags = ["Users"],
+    params(StatsQuery),
+    responses(
+        (status = 200, description = "Author stats successfully retrieved", body = AuthorStatsPayload),
+        (status = 401, description = "Token missing or invalid."),
+        (status = 422, description = "Invalid query", body = Validation),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("HttpAuthBearerJWT" = [])),
+)]
+#[instrument(name = "AUTHOR STATS", skip(ctx))]
+pub(crate) async fn author_stats(
+    ctx: State<Arc<AppContext>>,
+    q: Result<Query<StatsQuery>, QueryRejection>,
+    uid: UserID,
+) -> Result<Json<AuthorStatsPayload>, Error> {
+    let Query(q) = q?;
+    q.validate()?;
+
+    let articles = sqlx::query!(
+        r#"
+        SELECT
+            article.slug,
+            article.title,
+            (
+                SELECT coalesce(sum(views), 0)::BIGINT FROM article_stats
+                WHERE article_id = article.article_id
+            ) AS "views!",
+            (
+                SELECT coalesce(sum(reads), 0)::BIGINT FROM article_stats
+                WHERE article_id = article.article_id
+            ) AS "reads!",
+            article.favorites_count,
+            article.comments_count
+        FROM articles article
+        WHERE article.user_id = $1
+        ORDER BY 3 DESC, article.created_at DESC
+        "#,
+        *uid
+    )
+    .fetch_all(&ctx.db)
+    .await?;
+
+    let daily = sqlx::query_as!(
+        DailyStatsRow,
+        r#"
+        SELECT
+            series.day::DATE AS "day!",
+            (
+                SELECT coalesce(sum(views), 0)::BIGINT
+                FROM article_stats JOIN articles USING (article_id)
+                WHERE articles.user_id = $1 AND day = series.day::DATE
+            ) AS "views!",
+            (
+                SELECT coalesce(sum(reads), 0)::BIGINT
+                FROM article_stats JOIN articles USING (article_id)
+                WHERE articles.user_id = $1 AND day = series.day::DATE
+            ) AS "reads!",
+            (
+                SELECT count(*)
+                FROM favorites JOIN articles USING (article_id)
+                WHERE
+                    articles.user_id = $1 AND
+                    favorites.created_at >= series.day AT TIME ZONE 'UTC' AND
+                    favorites.created_at < (series.day + INTERVAL '1 day') AT TIME ZONE 'UTC'
+            ) AS "favorites!",
+            (
+                SELECT count(*)
+                FROM comments JOIN articles USING (article_id)
+                WHERE
+                    articles.user_id = $1 AND
+                    comments.deleted_at IS NULL AND
+                    comments.created_at >= series.day AT TIME ZONE 'UTC' AND
+                    comments.created_at < (series.day + INTERVAL '1 day') AT TIME ZONE 'UTC'
+            ) AS "comments!"
+        FROM
+            generate_series(
+                ((NOW() AT TIME ZONE 'UTC')::DATE - ($2::INT - 1))::TIMESTAMP,
+                (NOW() AT TIME ZONE 'UTC')::DATE::TIMESTAMP,
+                INTERVAL '1 day'
+            ) series (day)
+        ORDER BY series.day
+        "#,
+        *uid,
+        q.days(),
+    )
+    .fetch_all(&ctx.db)
+    .await?;
+
+    let articles: Vec<_> = articles
+        .into_iter()
+        .map(|row| ArticleSummary {
+            slug: row.slug,
+            title: row.title,
+            views: row.views as u64,
+            reads: row.reads as u64,
+            favorites_count: row.favorites_count as u64,
+            comments_count: row.comments_count as u64,
+        })
+        .collect();
+    Ok(Json(AuthorStatsPayload {
+        stats: AuthorStats {
+            views: articles.iter().map(|a| a.views).sum(),
+            reads: articles.iter().map(|a| a.reads).sum(),
+            favorites_count: articles.iter().map(|a| a.favorites_count).sum(),
+            comments_count: articles.iter().map(|a| a.comments_count).sum(),
+            daily: daily.into_iter().map(Into::into).collect(),
+            articles,
+        },
+    }))
+}
@@ -1,14 +1,26 @@
 use crate::AppContext;
 use crate::http::errors::Error;
 use crate::services::moderator::Verdict;
+use crate::services::moderator::utils::parse_mentions;
+use crate::services::renderer::{self, Rendered};
 use crate::utils;
+use axum::http::HeaderMap;
+use base64::Engine as _;
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use chrono::Utc;
 use deadpool_redis::redis::ErrorKind as RedisErrorKind;
 use deadpool_redis::redis::FromRedisValue;
 use deadpool_redis::redis::RedisResult;
 use deadpool_redis::redis::Value as RedisValue;
+use secrecy::{ExposeSecret as _, SecretString};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::net::IpAddr;
 use std::time::Duration;
+use uuid::Uuid;
 
 const VERDICT_CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+const RENDERED_CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 7);
 
 pub async fn moderate_content(ctx: &AppContext, content: &str, field: &str) -> Result<(), Error> {
     if ctx.skip_content_moderation {
@@ -57,6 +69,102 @@ pub async fn moderate_content(ctx: &AppContext, content: &str, field: &str) -> R
     Ok(())
 }
 
+/// Render markdown `content` as sanitized HTML with a table of contents.
+///
+/// Rendering is deterministic, so we are caching the output by content hash:
+/// an article that has not been edited is only rendered once per cache TTL.
+/// Only the mentions of existing users are linked, and so these are hashed
+/// along with the content.
+pub async fn render_markdown(ctx: &AppContext, content: &str) -> Result<Rendered, Error> {
+    let users = sqlx::query_scalar!(
+        "SELECT username FROM users WHERE username = ANY($1) ORDER BY username",
+        &parse_mentions(content),
+    )
+    .fetch_all(&ctx.db)
+    .await?;
+    // usernames cannot contain commas (see `users_username_check` constraint);
+    // the version is to be bumped whenever the renderer's output changes, so
+    // that no stale renderings are served
+    let content_key = format!(
+        "markdown:v2:{}",
+        utils::md5_hash(format!("{content}\n{}", users.join(",")))
+    );
+    if let Some(rendered) = ctx.cache.get_json::<Rendered>(&content_key).await? {
+        return Ok(rendered);
+    }
+    let rendered = renderer::render(content, &users.into_iter().collect());
+    ctx.cache
+        .set(&content_key, &rendered, Some(RENDERED_CACHE_TTL))
+        .await?;
+    Ok(rendered)
+}
+
+/// Client's address as forwarded by the reverse proxy (if any).
+///
+/// The app is sitting behind `Kamal-Proxy` which appends the address of
+/// the client it is talking to to the `x-forwarded-for` header, and so only
+/// the last entry can be trusted, while anything preceding it is whatever
+/// the client has sent us: <https://kamal-deploy.org/docs/configuration/proxy/#forward-headers>
+pub fn forwarded_for(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|header| header.to_str().ok())
+        .and_then(|ips| ips.rsplit(',').next())
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+}
+
+/// Identify the visitor for the purposes of analytics.
+///
+/// Authenticated users are identified by their ID, and anonymous ones by their
+/// IP address (see [`ClientIp`]) hashed with the app's `secret_key`, since
+/// there are few enough addresses for a plain hash to be reversed. The day is
+/// hashed along, so that an anonymous visitor cannot be tracked across days.
+/// Returns `None` for an anonymous visitor whose address is unknown, since
+/// they cannot be told apart from other anonymous visitors.
+///
+/// [`ClientIp`]: crate::http::extractors::ClientIp
+pub fn visitor_id(
+    secret_key: &SecretString,
+    uid: Option<&Uuid>,
+    ip: Option<IpAddr>,
+) -> Option<String> {
+    match (uid, ip) {
+        (Some(uid), _) => Some(uid.to_string()),
+        (None, Some(ip)) => {
+            let data = format!("{}:{ip}", Utc::now().date_naive());
+            Some(format!(
+                "ip:{}",
+                utils::hmac_hash(secret_key.expose_secret(), data)
+            ))
+        }
+        (None, None) => None,
+    }
+}
+
+/// Encode pagination cursor as an opaque (to the API consumers) string.
+pub fn encode_cursor<T>(cursor: &T) -> String
+where
+    T: Serialize,
+{
+    let json = serde_json::to_vec(cursor).expect("cursor to be serializable");
+    BASE64_URL_SAFE_NO_PAD.encode(json)
+}
+
+/// Decode pagination cursor previously issued with [`encode_cursor`].
+///
+/// Will return unprocessable entity error if the cursor has been tampered with.
+pub fn decode_cursor<T>(raw: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    BASE64_URL_SAFE_NO_PAD
+        .decode(raw)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(|| Error::unprocessable_entity([("cursor", "invalid cursor")]))
+}
+
 struct MaybeVerdict(Option<Verdict>);
 
 impl FromRedisValue for MaybeVerdict {
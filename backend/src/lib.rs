@@ -31,6 +31,7 @@ use secrecy::ExposeSecret;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::OnceLock;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tower_http::catch_panic::CatchPanicLayer;
 use tower_http::compression::CompressionLayer;
@@ -49,6 +50,8 @@ pub use telemetry::init_tracing;
 
 static OPENAPI_JSON: OnceLock<&'static str> = OnceLock::new();
 
+const ANALYTICS_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
 pub async fn api(config: Config) -> anyhow::Result<Router> {
     // ------------------------- PREPARE CONTEXT -------------------------------
     let ctx = Arc::new(AppContext::try_build(&config).await?);
@@ -59,6 +62,7 @@ pub async fn api(config: Config) -> anyhow::Result<Router> {
         .with_state(Arc::clone(&ctx))
         .nest("/api", routes::users::router(Arc::clone(&ctx)))
         .nest("/api", routes::articles::router(Arc::clone(&ctx)))
+        .nest("/api", routes::stream::router(Arc::clone(&ctx)))
         .layer(rate_limit_layer(ctx.redis.clone(), ctx.skip_rate_limiting)?)
         .layer(CompressionLayer::new())
         .layer(RequestBodyLimitLayer::new(1024 * 1024 * 10))
@@ -106,10 +110,31 @@ pub async fn api(config: Config) -> anyhow::Result<Router> {
             .context("failed to run migrations")?;
     }
 
+    // ------------------------ LAUNCH BACKGROUND JOBS -------------------------
+    let flush_interval = config
+        .analytics_flush_interval
+        .map(Duration::from_secs)
+        .unwrap_or(ANALYTICS_FLUSH_INTERVAL);
+    let flusher_ctx = Arc::clone(&ctx);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(flush_interval);
+        loop {
+            interval.tick().await;
+            match flusher_ctx.analytics.flush(&flusher_ctx.db).await {
+                Ok(0) => {}
+                Ok(nwritten) => debug!(nwritten, "flushed article stats"),
+                Err(e) => error!(error = ?e, "failed to flush article stats"),
+            }
+        }
+    });
+
     // --------------------------- LAUNCH WORKER -------------------------------
     if let Some(url) = &config.temporal_url {
         let mut client = temporal::init_client(url.to_owned()).await?;
         let _resp = temporal::create_maintenance_schedule(&mut client).await?;
+        let _resp = temporal::create_trending_schedule(&mut client).await?;
+        let _resp = temporal::create_digests_schedule(&mut client).await?;
+        let notifier = ctx.notifier.clone();
         std::thread::spawn(move || {
             let tokio_rt = tokio::runtime::Builder::new_current_thread()
                 .enable_all()
@@ -124,6 +149,7 @@ pub async fn api(config: Config) -> anyhow::Result<Router> {
                     &rt,
                     config.database_url.expose_secret(),
                     client,
+                    notifier,
                 )
                 .await
                 .context("failed to initialize temporal worker")?;
@@ -141,9 +167,12 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
     let listener = TcpListener::bind(addr).await?;
     let app = api(config).await?;
     info!("Launching application at {:?}", &addr);
-    Ok(axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?)
+    Ok(axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?)
 }
 
 /// Graceful shutdown signal.
@@ -0,0 +1,264 @@
+use anyhow::Context as _;
+use chrono::{NaiveDate, Utc};
+use deadpool_redis::Connection;
+use deadpool_redis::Pool as RedisPool;
+use deadpool_redis::redis::{Script, cmd as redis_cmd};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Hash with the counters that have not made it to the database yet.
+const PENDING_KEY: &str = "analytics:pending";
+
+/// Prefix of the hashes with the counters being flushed to the database.
+const FLUSHING_KEY_PREFIX: &str = "analytics:flushing:";
+
+/// Lock ensuring only one app instance is flushing the counters at a time.
+const FLUSH_LOCK_KEY: &str = "analytics:flush-lock";
+
+/// For how long the flush lock is held unless released.
+const FLUSH_LOCK_TTL: Duration = Duration::from_secs(60 * 5);
+
+/// Move the pending counters (if any) to a hash of their own.
+const MOVE_PENDING_SCRIPT: &str = r#"
+if redis.call("EXISTS", KEYS[1]) == 1 then
+    redis.call("RENAME", KEYS[1], KEYS[2])
+end
+"#;
+
+/// Release the lock, unless it has expired and been taken by someone else.
+const RELEASE_LOCK_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    redis.call("DEL", KEYS[1])
+end
+"#;
+
+/// Signals from the same visitor are only counted once within this window.
+const DEDUP_WINDOW: Duration = Duration::from_secs(60 * 30);
+
+/// Reader's interaction with an article.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    /// Article has been opened.
+    View,
+
+    /// Article has been read through to the end.
+    Read,
+}
+
+impl Signal {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::View => "view",
+            Self::Read => "read",
+        }
+    }
+}
+
+/// Article views and read-throughs tracker.
+///
+/// Signals are deduplicated and counted in Redis, and then periodically
+/// flushed to the database (see [`Analytics::flush`]), so that reading an
+/// article does not result in a write to `articles` related tables.
+pub struct Analytics {
+    pool: RedisPool,
+}
+
+impl Analytics {
+    async fn connection(&self) -> anyhow::Result<Connection> {
+        self.pool
+            .get()
+            .await
+            .context("failed to acquire Redis connection from pool")
+    }
+
+    pub fn new(pool: RedisPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record `signal` for the article from the `visitor`.
+    ///
+    /// Visitor is anything identifying the reader, e.g. their user ID or
+    /// IP address. Will return `false` if this visitor has already been
+    /// counted recently.
+    pub async fn record(
+        &self,
+        signal: Signal,
+        article_id: Uuid,
+        visitor: &str,
+    ) -> anyhow::Result<bool> {
+        let mut conn = self.connection().await?;
+        let seen_key = format!(
+            "analytics:seen:{}:{}:{}",
+            signal.as_str(),
+            article_id,
+            visitor
+        );
+        let fresh: Option<String> = redis_cmd("SET")
+            .arg(seen_key)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(DEDUP_WINDOW.as_secs())
+            .query_async(&mut conn)
+            .await
+            .context("Redis command failed")?;
+        if fresh.is_none() {
+            return Ok(false);
+        }
+        let field = format!(
+            "{}:{}:{}",
+            signal.as_str(),
+            article_id,
+            Utc::now().date_naive()
+        );
+        redis_cmd("HINCRBY")
+            .arg(PENDING_KEY)
+            .arg(field)
+            .arg(1)
+            .exec_async(&mut conn)
+            .await
+            .context("Redis command failed")?;
+        Ok(true)
+    }
+
+    /// Move the counters accumulated in Redis to the database.
+    ///
+    /// Only one app instance is flushing at a time, see `FLUSH_LOCK_KEY`.
+    /// The pending counters are first atomically moved aside to a hash of
+    /// their own, which is only deleted once written to the database, and so
+    /// the counters which failed to make it there (including because the
+    /// instance has gone away mid-flush) will be retried during the next
+    /// flush. Returns the number of daily stats entries updated.
+    pub async fn flush(&self, db: &PgPool) -> anyhow::Result<usize> {
+        let mut conn = self.connection().await?;
+        let lock = Uuid::new_v4().to_string();
+        let locked: Option<String> = redis_cmd("SET")
+            .arg(FLUSH_LOCK_KEY)
+            .arg(&lock)
+            .arg("NX")
+            .arg("EX")
+            .arg(FLUSH_LOCK_TTL.as_secs())
+            .query_async(&mut conn)
+            .await
+            .context("Redis command failed")?;
+        if locked.is_none() {
+            // some other instance is flushing right now
+            return Ok(0);
+        }
+        let written = self.flush_locked(&mut conn, db).await;
+        let released = Script::new(RELEASE_LOCK_SCRIPT)
+            .key(FLUSH_LOCK_KEY)
+            .arg(&lock)
+            .invoke_async::<()>(&mut conn)
+            .await
+            .context("Redis command failed");
+        let written = written?;
+        released?;
+        Ok(written)
+    }
+
+    async fn flush_locked(&self, conn: &mut Connection, db: &PgPool) -> anyhow::Result<usize> {
+        // signals recorded while we are flushing will land in a fresh hash
+        // and be picked up during the next flush
+        let flushing_key = format!("{FLUSHING_KEY_PREFIX}{}", Uuid::new_v4());
+        Script::new(MOVE_PENDING_SCRIPT)
+            .key(PENDING_KEY)
+            .key(&flushing_key)
+            .invoke_async::<()>(conn)
+            .await
+            .context("Redis command failed")?;
+
+        // this is also picking up whatever is left over from earlier flushes
+        let mut flushing_keys = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis_cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(format!("{FLUSHING_KEY_PREFIX}*"))
+                .query_async(conn)
+                .await
+                .context("Redis command failed")?;
+            flushing_keys.extend(keys);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        flushing_keys.sort();
+        flushing_keys.dedup();
+
+        let mut written = 0;
+        for key in flushing_keys {
+            let counters: HashMap<String, i64> = redis_cmd("HGETALL")
+                .arg(&key)
+                .query_async(conn)
+                .await
+                .context("Redis command failed")?;
+            written += write_counters(db, &counters)
+                .await
+                .context("failed to write article stats")?;
+            redis_cmd("DEL")
+                .arg(&key)
+                .exec_async(conn)
+                .await
+                .context("Redis command failed")?;
+        }
+        Ok(written)
+    }
+}
+
+/// Add the `counters` to the article stats in the database.
+async fn write_counters(db: &PgPool, counters: &HashMap<String, i64>) -> sqlx::Result<usize> {
+    let mut daily: BTreeMap<(Uuid, NaiveDate), (i64, i64)> = BTreeMap::new();
+    for (field, count) in counters {
+        let mut parts = field.splitn(3, ':');
+        let (Some(signal), Some(article_id), Some(day)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            warn!(field, "unexpected analytics counter");
+            continue;
+        };
+        let (Ok(article_id), Ok(day)) = (article_id.parse(), day.parse()) else {
+            warn!(field, "unexpected analytics counter");
+            continue;
+        };
+        let entry = daily.entry((article_id, day)).or_default();
+        match signal {
+            "view" => entry.0 += count,
+            "read" => entry.1 += count,
+            _ => warn!(field, "unexpected analytics counter"),
+        }
+    }
+
+    let (mut article_ids, mut days, mut views, mut reads) = (vec![], vec![], vec![], vec![]);
+    for ((article_id, day), (nviews, nreads)) in daily {
+        article_ids.push(article_id);
+        days.push(day);
+        views.push(nviews);
+        reads.push(nreads);
+    }
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO article_stats (article_id, day, views, reads)
+        SELECT article_id, day, views, reads
+        FROM
+            UNNEST($1::UUID[], $2::DATE[], $3::BIGINT[], $4::BIGINT[])
+                AS pending (article_id, day, views, reads)
+            -- article might have been deleted in the meantime
+            JOIN articles USING (article_id)
+        ON CONFLICT (article_id, day) DO UPDATE SET
+            views = article_stats.views + EXCLUDED.views,
+            reads = article_stats.reads + EXCLUDED.reads
+        "#,
+        &article_ids,
+        &days,
+        &views,
+        &reads,
+    )
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() as usize)
+}
@@ -4,7 +4,9 @@ use deadpool_redis::Pool as RedisPool;
 use deadpool_redis::redis::Cmd as RedisCmd;
 use deadpool_redis::redis::FromRedisValue;
 use deadpool_redis::redis::Value as RedisValue;
+use deadpool_redis::redis::pipe as redis_pipe;
 use serde::Serialize;
+use serde::de::DeserializeOwned;
 use std::time::Duration;
 
 pub struct Cache {
@@ -52,4 +54,81 @@ impl Cache {
         let result: T = FromRedisValue::from_redis_value(&value)?;
         Ok(result)
     }
+
+    /// Store the entry just like [`Cache::set`] does, but also tag it, so that
+    /// it can be removed together with all the other entries sharing any of
+    /// the `tags`, see [`Cache::delete_tagged`].
+    pub async fn set_tagged<T>(
+        &self,
+        key: &str,
+        value: &T,
+        tags: &[String],
+        ttl: Duration,
+    ) -> anyhow::Result<()>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_string(&value)?;
+        let mut pipe = redis_pipe();
+        pipe.atomic().set_ex(key, value, ttl.as_secs()).ignore();
+        for tag in tags {
+            // the tag is outliving the entries it is pointing to, and
+            // the expired entries are left dangling in the tag's set
+            pipe.sadd(tag_key(tag), key)
+                .ignore()
+                .expire(tag_key(tag), ttl.as_secs() as i64)
+                .ignore();
+        }
+        let mut conn = self.connection().await?;
+        pipe.exec_async(&mut conn)
+            .await
+            .context("Redis command failed")?;
+        Ok(())
+    }
+
+    /// Remove all the entries tagged with any of the `tags`.
+    pub async fn delete_tagged(&self, tags: &[String]) -> anyhow::Result<()> {
+        if tags.is_empty() {
+            return Ok(());
+        }
+        let tag_keys: Vec<_> = tags.iter().map(|tag| tag_key(tag)).collect();
+        let mut conn = self.connection().await?;
+        let keys: Vec<String> = RedisCmd::sunion(&tag_keys)
+            .query_async(&mut conn)
+            .await
+            .context("Redis command failed")?;
+        RedisCmd::del([keys, tag_keys].concat())
+            .exec_async(&mut conn)
+            .await
+            .context("Redis command failed")?;
+        Ok(())
+    }
+
+    /// Remove the entry for the `key` (if any).
+    pub async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let mut conn = self.connection().await?;
+        RedisCmd::del(key)
+            .exec_async(&mut conn)
+            .await
+            .context("Redis command failed")?;
+        Ok(())
+    }
+
+    /// Get a JSON-encoded value previously stored with [`Cache::set`].
+    ///
+    /// Will return `None` if there is no entry for the `key` (or it expired).
+    pub async fn get_json<T>(&self, key: &str) -> anyhow::Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let value: Option<String> = self.get(key).await?;
+        value
+            .map(|value| serde_json::from_str(&value))
+            .transpose()
+            .context("failed to deserialize cached value")
+    }
+}
+
+fn tag_key(tag: &str) -> String {
+    format!("cache:tag:{tag}")
 }
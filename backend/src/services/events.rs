@@ -0,0 +1,371 @@
+use anyhow::Context as _;
+use chrono::Utc;
+use deadpool_redis::Connection;
+use deadpool_redis::Pool as RedisPool;
+use deadpool_redis::redis::Client as RedisClient;
+use deadpool_redis::redis::{Pipeline, Script, cmd as redis_cmd, pipe as redis_pipe};
+use futures_util::{Stream, StreamExt as _, stream};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+/// Counter the events' identifiers are drawn from.
+const SEQUENCE_KEY: &str = "events:sequence";
+
+/// Pattern matching channels of all the topics, see [`Topic::channel`].
+const CHANNEL_PATTERN: &str = "events:*";
+
+/// How many events can be buffered for a subscriber lagging behind, before
+/// the subscriber is dropped (it can then resume, see [`Events::subscribe`]).
+const FANOUT_CAPACITY: usize = 1024;
+
+/// Draw the event's identifier, keep the event in the histories (if any)
+/// and publish it to the channels, see `Events::dispatch`.
+///
+/// This is done atomically, so that the events are delivered in the order
+/// of their identifiers, which is what the subscribers are relying on when
+/// skipping the events they have already replayed. The event's JSON is put
+/// together here, since the identifier is only known here.
+const PUBLISH_SCRIPT: &str = r#"
+local id = redis.call("INCR", KEYS[1])
+local event = '{"id":' .. id .. ',"name":' .. ARGV[1] .. ',"data":' .. ARGV[2] .. '}'
+for i = 2, #KEYS do
+    redis.call("ZADD", KEYS[i], id, event)
+    redis.call("ZREMRANGEBYRANK", KEYS[i], 0, -tonumber(ARGV[3]) - 1)
+    redis.call("EXPIRE", KEYS[i], ARGV[4])
+end
+for i = 5, #ARGV do
+    redis.call("PUBLISH", ARGV[i], event)
+end
+"#;
+
+/// How many of the latest events are kept per topic for resumption.
+const HISTORY_LEN: isize = 100;
+
+/// For how long the events are kept for resumption.
+const HISTORY_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// For how long a subscriber is considered present unless they refresh
+/// their presence, see [`Events::enter`].
+pub const PRESENCE_TTL: Duration = Duration::from_secs(60);
+
+/// What the events are about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Topic {
+    /// Events addressed to the user, e.g. new notifications.
+    User(Uuid),
+
+    /// Events regarding the article, e.g. new comments.
+    Article(Uuid),
+
+    /// Who is present on the article's live channel, see [`Events::enter`].
+    ///
+    /// Presence is only relevant at the moment, and so these events are
+    /// not kept for resumption, see [`Events::announce`].
+    Presence(Uuid),
+}
+
+impl Topic {
+    fn channel(&self) -> String {
+        match self {
+            Self::User(user_id) => format!("events:user:{user_id}"),
+            Self::Article(article_id) => format!("events:article:{article_id}"),
+            Self::Presence(article_id) => format!("events:presence:{article_id}"),
+        }
+    }
+
+    fn history_key(&self) -> String {
+        format!("{}:history", self.channel())
+    }
+
+    fn presence_key(&self) -> String {
+        format!("{}:presence", self.channel())
+    }
+}
+
+/// Event as delivered to the subscribers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    /// Event's identifier, increasing across all of the topics.
+    pub id: u64,
+
+    /// Event's name, e.g. `comment`.
+    pub name: String,
+
+    /// Event's payload.
+    pub data: serde_json::Value,
+}
+
+/// Event as received from the topic's channel.
+struct Published {
+    channel: String,
+    event: Event,
+}
+
+/// Real-time events broker.
+///
+/// Events are fanned out via Redis pub/sub, and so a subscriber connected
+/// to one app instance receives the events published by any other instance.
+/// The latest events of each topic are additionally kept for a while, so
+/// that a subscriber can catch up after reconnecting, see [`Events::subscribe`].
+pub struct Events {
+    pool: RedisPool,
+    client: RedisClient,
+
+    /// Fan-out of the events received over the app instance's subscription
+    /// (if there is one at the moment), see [`Events::listen`].
+    fanout: Arc<Mutex<Option<broadcast::Sender<Arc<Published>>>>>,
+}
+
+impl Events {
+    async fn connection(&self) -> anyhow::Result<Connection> {
+        self.pool
+            .get()
+            .await
+            .context("failed to acquire Redis connection from pool")
+    }
+
+    /// Create broker.
+    ///
+    /// The subscription is given a dedicated connection (a connection in
+    /// subscribed state cannot be used for anything else), which is why
+    /// we need a `client` alongside the pool.
+    pub fn new(pool: RedisPool, client: RedisClient) -> Self {
+        Self {
+            pool,
+            client,
+            fanout: Default::default(),
+        }
+    }
+
+    /// Publish event with the given `name` and `data` to each of the `topics`.
+    pub async fn publish<T>(&self, topics: &[Topic], name: &str, data: &T) -> anyhow::Result<()>
+    where
+        T: Serialize,
+    {
+        self.dispatch(topics, name, data, true).await
+    }
+
+    /// Publish event just like [`Events::publish`] does, but without keeping
+    /// it for resumption, i.e. for the current subscribers only.
+    pub async fn announce<T>(&self, topics: &[Topic], name: &str, data: &T) -> anyhow::Result<()>
+    where
+        T: Serialize,
+    {
+        self.dispatch(topics, name, data, false).await
+    }
+
+    async fn dispatch<T>(
+        &self,
+        topics: &[Topic],
+        name: &str,
+        data: &T,
+        keep: bool,
+    ) -> anyhow::Result<()>
+    where
+        T: Serialize,
+    {
+        if topics.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.connection().await?;
+        let script = Script::new(PUBLISH_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        invocation.key(SEQUENCE_KEY);
+        for topic in topics.iter().filter(|_| keep) {
+            invocation.key(topic.history_key());
+        }
+        invocation
+            .arg(serde_json::to_string(name)?)
+            .arg(serde_json::to_string(data)?)
+            .arg(HISTORY_LEN)
+            .arg(HISTORY_TTL.as_secs());
+        for topic in topics {
+            invocation.arg(topic.channel());
+        }
+        invocation
+            .invoke_async::<()>(&mut conn)
+            .await
+            .context("Redis command failed")?;
+        Ok(())
+    }
+
+    /// Receive events published to any of the topics.
+    ///
+    /// All of the app instance's subscribers are sharing a single Redis
+    /// subscription, which is established on demand. Once the subscription's
+    /// connection is lost, the receivers are closed (so that the subscribers
+    /// can resume, see [`Events::subscribe`]), and the next call reconnects.
+    async fn listen(&self) -> anyhow::Result<broadcast::Receiver<Arc<Published>>> {
+        let mut fanout = self.fanout.lock().await;
+        if let Some(sender) = fanout.as_ref() {
+            return Ok(sender.subscribe());
+        }
+        let mut pubsub = self
+            .client
+            .get_async_pubsub()
+            .await
+            .context("failed to connect to Redis")?;
+        pubsub
+            .psubscribe(CHANNEL_PATTERN)
+            .await
+            .context("Redis command failed")?;
+        let (sender, receiver) = broadcast::channel(FANOUT_CAPACITY);
+        *fanout = Some(sender.clone());
+        let registry = Arc::clone(&self.fanout);
+        tokio::spawn(async move {
+            let mut messages = pubsub.into_on_message();
+            while let Some(msg) = messages.next().await {
+                let Some(event) = msg.get_payload::<String>().ok().and_then(|e| parse(&e)) else {
+                    continue;
+                };
+                let published = Published {
+                    channel: msg.get_channel_name().to_owned(),
+                    event,
+                };
+                // there might be no subscribers at the moment, which is fine
+                let _ = sender.send(Arc::new(published));
+            }
+            warn!("lost Redis subscription, dropping subscribers");
+            // receivers are closed once both this and the registered
+            // sender are dropped
+            registry.lock().await.take();
+        });
+        Ok(receiver)
+    }
+
+    /// Subscribe to events published to any of the `topics`.
+    ///
+    /// With `last_event_id` provided, the events published after that one
+    /// (and still kept, see `HISTORY_LEN` and `HISTORY_TTL`) are replayed
+    /// first.
+    pub async fn subscribe(
+        &self,
+        topics: &[Topic],
+        last_event_id: Option<u64>,
+    ) -> anyhow::Result<impl Stream<Item = Event> + use<>> {
+        // subscribing before reading the history so that nothing published
+        // in between gets lost (but may be delivered twice, see below)
+        let receiver = self.listen().await?;
+        let channels: HashSet<_> = topics.iter().map(Topic::channel).collect();
+
+        let mut replay = Vec::new();
+        if let Some(last_event_id) = last_event_id {
+            let mut conn = self.connection().await?;
+            for topic in topics {
+                let events: Vec<String> = redis_cmd("ZRANGE")
+                    .arg(topic.history_key())
+                    .arg(format!("({last_event_id}"))
+                    .arg("+inf")
+                    .arg("BYSCORE")
+                    .query_async(&mut conn)
+                    .await
+                    .context("Redis command failed")?;
+                replay.extend(events.iter().filter_map(|event| parse(event)));
+            }
+            replay.sort_by_key(|event| event.id);
+            replay.dedup_by_key(|event| event.id);
+        }
+        let replayed = replay.last().map(|event| event.id).or(last_event_id);
+
+        let live = stream::unfold(receiver, |mut receiver| async move {
+            match receiver.recv().await {
+                Ok(published) => Some((published, receiver)),
+                // the subscriber is expected to resume from the last event
+                // they have received, just like after a lost connection
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "dropping subscriber lagging behind");
+                    None
+                }
+                Err(RecvError::Closed) => None,
+            }
+        })
+        .filter_map(move |published| {
+            let event = Some(published)
+                .filter(|published| channels.contains(&published.channel))
+                .map(|published| published.event.clone())
+                .filter(|event| replayed.is_none_or(|replayed| event.id > replayed));
+            async move { event }
+        });
+        Ok(stream::iter(replay).chain(live))
+    }
+
+    /// Register (or refresh) the user's connection as present on the `topic`,
+    /// returning how many distinct users are currently present.
+    ///
+    /// Presence expires after `PRESENCE_TTL`, and so a connection should be
+    /// refreshed periodically for as long as it is alive. This way, users of an
+    /// app instance which went away without calling [`Events::leave`] do not
+    /// stay present forever.
+    pub async fn enter(
+        &self,
+        topic: Topic,
+        user_id: Uuid,
+        connection_id: Uuid,
+    ) -> anyhow::Result<u64> {
+        let expires_at = Utc::now().timestamp() + PRESENCE_TTL.as_secs() as i64;
+        let mut pipe = redis_pipe();
+        pipe.cmd("ZADD")
+            .arg(topic.presence_key())
+            .arg(expires_at)
+            .arg(format!("{user_id}:{connection_id}"))
+            .ignore()
+            .cmd("EXPIRE")
+            .arg(topic.presence_key())
+            .arg(PRESENCE_TTL.as_secs())
+            .ignore();
+        self.present(topic, pipe).await
+    }
+
+    /// Unregister the user's connection, returning how many distinct users
+    /// are still present on the `topic`.
+    pub async fn leave(
+        &self,
+        topic: Topic,
+        user_id: Uuid,
+        connection_id: Uuid,
+    ) -> anyhow::Result<u64> {
+        let mut pipe = redis_pipe();
+        pipe.cmd("ZREM")
+            .arg(topic.presence_key())
+            .arg(format!("{user_id}:{connection_id}"))
+            .ignore();
+        self.present(topic, pipe).await
+    }
+
+    /// Execute `pipe`, and then count users present on the `topic`.
+    async fn present(&self, topic: Topic, mut pipe: Pipeline) -> anyhow::Result<u64> {
+        let mut conn = self.connection().await?;
+        let (connections,): (Vec<String>,) = pipe
+            .atomic()
+            .cmd("ZREMRANGEBYSCORE")
+            .arg(topic.presence_key())
+            .arg("-inf")
+            .arg(Utc::now().timestamp())
+            .ignore()
+            .cmd("ZRANGE")
+            .arg(topic.presence_key())
+            .arg(0)
+            .arg(-1)
+            .query_async(&mut conn)
+            .await
+            .context("Redis command failed")?;
+        // a user may be present via a few connections, e.g. browser tabs
+        let users: HashSet<_> = connections
+            .iter()
+            .filter_map(|connection| connection.split_once(':'))
+            .map(|(user_id, _)| user_id)
+            .collect();
+        Ok(users.len() as u64)
+    }
+}
+
+fn parse(event: &str) -> Option<Event> {
+    serde_json::from_str(event)
+        .inspect_err(|e| warn!(error = ?e, "unexpected event"))
+        .ok()
+}
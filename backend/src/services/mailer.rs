@@ -51,7 +51,35 @@ impl ResendMailer {
         let email = CreateEmailBaseOptions::new(&*self.sender, [to], subject)
             .with_html(html)
             .with_text(text);
+        self.send(email).await
+    }
+
+    /// Send email the recipient can unsubscribe from.
+    ///
+    /// The `unsubscribe_url` is advertised via `List-Unsubscribe` headers,
+    /// including for one-click unsubscription, see RFC 8058.
+    #[instrument(
+        name = "SEND UNSUBSCRIBABLE EMAIL",
+        fields(email_id),
+        skip(self, html, text)
+    )]
+    pub async fn send_unsubscribable_email(
+        &self,
+        to: &str,
+        subject: &str,
+        html: &str,
+        text: &str,
+        unsubscribe_url: &Url,
+    ) -> Result<EmailId, anyhow::Error> {
+        let email = CreateEmailBaseOptions::new(&*self.sender, [to], subject)
+            .with_html(html)
+            .with_text(text)
+            .with_header("List-Unsubscribe", &format!("<{unsubscribe_url}>"))
+            .with_header("List-Unsubscribe-Post", "List-Unsubscribe=One-Click");
+        self.send(email).await
+    }
 
+    async fn send(&self, email: CreateEmailBaseOptions) -> Result<EmailId, anyhow::Error> {
         #[cfg(debug_assertions)]
         if let MailerTransport::Stdout = self.transport {
             use crate::utils::gen_alphanum_string;
@@ -1,4 +1,8 @@
+pub(crate) mod analytics;
 pub(crate) mod cache;
 pub(crate) mod captcha;
+pub(crate) mod events;
 pub(crate) mod mailer;
 pub(crate) mod moderator;
+pub(crate) mod notifier;
+pub(crate) mod renderer;
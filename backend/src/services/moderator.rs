@@ -123,42 +123,94 @@ impl Moderator {
     }
 }
 
-mod utils {
-    pub(super) fn parse_content(content: &str) -> Vec<url::Url> {
+pub(crate) mod utils {
+    use comrak::nodes::{AstNode, NodeValue};
+    use regex::Regex;
+    use std::ops::Range;
+    use std::sync::LazyLock;
+
+    /// `@username` preceded by start of text or a separator, so that emails
+    /// and the like are not treated as mentions.
+    static MENTION: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?:^|[^\w@.])@(\w(?:[\w.-]*\w)?)").expect("valid regex"));
+
+    /// Parse markdown `content` and `visit` each of its AST nodes.
+    fn walk(content: &str, mut visit: impl for<'a> FnMut(&'a AstNode<'a>)) {
         let arena = comrak::Arena::new();
         let root = comrak::parse_document(&arena, content, &comrak::Options::default());
-        let mut image_urls = Vec::new();
         for node in root.descendants() {
-            match node.data.borrow().value {
-                comrak::nodes::NodeValue::Image(ref link) => {
-                    if let Ok(url) = url::Url::parse(&link.url) {
-                        image_urls.push(url);
-                    } else {
-                        // TODO: consider singalling back to user
-                        continue;
-                    };
+            visit(node);
+        }
+    }
+
+    pub(super) fn parse_content(content: &str) -> Vec<url::Url> {
+        let mut image_urls = Vec::new();
+        walk(content, |node| match node.data.borrow().value {
+            NodeValue::Image(ref link) => {
+                if let Ok(url) = url::Url::parse(&link.url) {
+                    image_urls.push(url);
                 }
-                comrak::nodes::NodeValue::HtmlInline(ref literal)
-                | comrak::nodes::NodeValue::HtmlBlock(comrak::nodes::NodeHtmlBlock {
-                    ref literal,
-                    ..
-                }) => {
-                    let fragment = scraper::Html::parse_fragment(literal);
-                    for img in
-                        fragment.select(&scraper::Selector::parse("img").expect("valid selector"))
+                // TODO: consider singalling back to user
+            }
+            NodeValue::HtmlInline(ref literal)
+            | NodeValue::HtmlBlock(comrak::nodes::NodeHtmlBlock { ref literal, .. }) => {
+                let fragment = scraper::Html::parse_fragment(literal);
+                for img in
+                    fragment.select(&scraper::Selector::parse("img").expect("valid selector"))
+                {
+                    if let Some(url) = img
+                        .value()
+                        .attr("src")
+                        .and_then(|src| url::Url::parse(src).ok())
                     {
-                        if let Some(url) = img
-                            .value()
-                            .attr("src")
-                            .and_then(|src| url::Url::parse(src).ok())
-                        {
-                            image_urls.push(url);
-                        }
+                        image_urls.push(url);
                     }
                 }
-                _ => continue,
             }
-        }
+            _ => {}
+        });
         image_urls
     }
+
+    /// Usernames mentioned in markdown `content`, deduplicated.
+    ///
+    /// Mentions in code and in the text of links are not taken into account.
+    pub(crate) fn parse_mentions(content: &str) -> Vec<String> {
+        let mut usernames = Vec::new();
+        walk(content, |node| {
+            if let NodeValue::Text(ref text) = node.data.borrow().value {
+                if in_link(node) {
+                    return;
+                }
+                for range in find_mentions(text) {
+                    let username = &text[range][1..];
+                    if !usernames
+                        .iter()
+                        .any(|u: &String| u.eq_ignore_ascii_case(username))
+                    {
+                        usernames.push(username.to_owned());
+                    }
+                }
+            }
+        });
+        usernames
+    }
+
+    /// Byte ranges of mentions (including the `@` sign) in plain `text`.
+    pub(crate) fn find_mentions(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+        MENTION.captures_iter(text).map(|captures| {
+            let username = captures.get(1).expect("group to participate in match");
+            username.start() - 1..username.end()
+        })
+    }
+
+    /// Whether the `node` is (a descendant of) a link or an image.
+    pub(crate) fn in_link<'a>(node: &'a AstNode<'a>) -> bool {
+        node.ancestors().any(|ancestor| {
+            matches!(
+                ancestor.data.borrow().value,
+                NodeValue::Link(_) | NodeValue::Image(_)
+            )
+        })
+    }
 }
@@ -0,0 +1,580 @@
+use crate::services::mailer::ResendMailer;
+use crate::templates::{DigestEmailHtml, DigestEmailText, EmailEntry};
+use anyhow::Context as _;
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, TokenData, Validation, decode, encode};
+use sqlx::PgPool;
+use sqlx::types::Json as SqlxJson;
+use std::time::Duration;
+use url::Url;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Audience of the unsubscribe tokens.
+///
+/// Authentication tokens have got no audience, which is why they cannot be
+/// used to unsubscribe and vice versa.
+const UNSUBSCRIBE_AUDIENCE: &str = "unsubscribe";
+
+/// How many entries of each section are listed in an email.
+const MAX_ENTRIES: i64 = 20;
+
+/// Digest is considered due a bit earlier than strictly necessary, so that
+/// it does not drift relative to the schedule, see `send_digests`.
+const DIGEST_GRACE_PERIOD: Duration = Duration::from_secs(60 * 10);
+
+/// What a user can be emailed about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailTopic {
+    /// New followers.
+    Follow,
+
+    /// Favorites of the user's articles.
+    Favorite,
+
+    /// Comments under the user's articles.
+    Comment,
+
+    /// Replies to the user's comments.
+    Reply,
+
+    /// Mentions of the user in articles and comments.
+    Mention,
+
+    /// New articles of the authors the user is following.
+    Article,
+}
+
+impl EmailTopic {
+    pub const ALL: [Self; 6] = [
+        Self::Follow,
+        Self::Favorite,
+        Self::Comment,
+        Self::Reply,
+        Self::Mention,
+        Self::Article,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Follow => "follow",
+            Self::Favorite => "favorite",
+            Self::Comment => "comment",
+            Self::Reply => "reply",
+            Self::Mention => "mention",
+            Self::Article => "article",
+        }
+    }
+
+    /// Human-readable description, e.g. for the unsubscribe page.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Follow => "new followers",
+            Self::Favorite => "favorites of your articles",
+            Self::Comment => "comments under your articles",
+            Self::Reply => "replies to your comments",
+            Self::Mention => "mentions of you",
+            Self::Article => "new articles of the authors you follow",
+        }
+    }
+}
+
+/// How a user wants to be emailed about a topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Delivery {
+    /// Send an email as soon as something happens.
+    Immediate,
+
+    /// Include in the daily digest.
+    Daily,
+
+    /// Include in the weekly digest.
+    Weekly,
+
+    /// Do not email (which is the default).
+    Off,
+}
+
+impl Delivery {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Immediate => "immediate",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Off => "off",
+        }
+    }
+
+    /// How often digests of this kind are sent, if this is a digest.
+    fn period(&self) -> Option<Duration> {
+        match self {
+            Self::Daily => Some(Duration::from_secs(60 * 60 * 24)),
+            Self::Weekly => Some(Duration::from_secs(60 * 60 * 24 * 7)),
+            Self::Immediate | Self::Off => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UnsubscribeClaims {
+    /// Whom token refers to.
+    sub: Uuid,
+
+    /// Always `UNSUBSCRIBE_AUDIENCE`.
+    aud: String,
+
+    /// What to unsubscribe from, with `None` meaning all of the emails.
+    topic: Option<EmailTopic>,
+}
+
+/// Issue token to unsubscribe the user from emails about the `topic` (or
+/// from all of the notification emails, if `None`).
+///
+/// These tokens do not expire, so that the links in the older emails keep
+/// working.
+pub fn issue_unsubscribe_token(
+    user_id: Uuid,
+    topic: Option<EmailTopic>,
+    key: &EncodingKey,
+) -> anyhow::Result<String> {
+    let claims = UnsubscribeClaims {
+        sub: user_id,
+        aud: UNSUBSCRIBE_AUDIENCE.to_owned(),
+        topic,
+    };
+    encode(&Header::default(), &claims, key)
+        .map_err(|e| anyhow!(e))
+        .context("failed to issue unsubscribe token")
+}
+
+/// Verify unsubscribe token, returning the user and the topic, see
+/// [`issue_unsubscribe_token`].
+pub fn verify_unsubscribe_token(
+    token: &str,
+    key: &DecodingKey,
+) -> anyhow::Result<(Uuid, Option<EmailTopic>)> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[UNSUBSCRIBE_AUDIENCE]);
+    validation.set_required_spec_claims(&["aud", "sub"]);
+    validation.validate_exp = false;
+    let TokenData {
+        claims: UnsubscribeClaims { sub, topic, .. },
+        ..
+    } = decode::<UnsubscribeClaims>(token, key, &validation)?;
+    Ok((sub, topic))
+}
+
+/// Notification as needed for an email.
+#[derive(Debug)]
+struct ActivityRow {
+    kind: SqlxJson<EmailTopic>,
+    actor: Option<String>,
+    actors_count: i64,
+    article_slug: Option<String>,
+    article_title: Option<String>,
+}
+
+/// Article as needed for an email.
+#[derive(Debug)]
+struct ArticleRow {
+    slug: String,
+    title: String,
+    author: String,
+}
+
+/// Notifications sender.
+///
+/// Emails users about what has happened in accordance with their preferences
+/// (see `email_preferences` table): either right away (see [`EmailNotifier::notify`]
+/// and [`EmailNotifier::announce_article`]) or in daily and weekly digests (see
+/// [`EmailNotifier::send_digests`]).
+#[derive(Clone)]
+pub struct EmailNotifier {
+    mailer: ResendMailer,
+    key: EncodingKey,
+    app_url: Url,
+    api_url: Url,
+}
+
+impl EmailNotifier {
+    pub fn new(mailer: ResendMailer, key: EncodingKey, app_url: Url, api_url: Url) -> Self {
+        Self {
+            mailer,
+            key,
+            app_url,
+            api_url,
+        }
+    }
+
+    /// Email the recipient of the notification about it, if they want to be
+    /// emailed about such things right away.
+    ///
+    /// Returns whether the email has been sent.
+    pub async fn notify(&self, db: &PgPool, notification_id: Uuid) -> anyhow::Result<bool> {
+        let Some(recipient) = sqlx::query!(
+            r#"
+            SELECT
+                recipient.user_id,
+                recipient.email,
+                recipient.username
+            FROM notifications notification JOIN users recipient USING (user_id)
+            WHERE
+                notification.notification_id = $1 AND
+                recipient.status = 'ACTIVE' AND
+                email_delivery(recipient.user_id, notification.kind) = 'immediate'
+            "#,
+            notification_id,
+        )
+        .fetch_optional(db)
+        .await?
+        else {
+            return Ok(false);
+        };
+        let activity = sqlx::query_as!(
+            ActivityRow,
+            r#"
+            SELECT
+                to_jsonb(notification.kind) AS "kind!: SqlxJson<EmailTopic>",
+                (
+                    SELECT actor.username
+                    FROM notification_actors notification_actor JOIN users actor USING (user_id)
+                    WHERE notification_actor.notification_id = notification.notification_id
+                    ORDER BY notification_actor.created_at DESC
+                    LIMIT 1
+                ) AS actor,
+                (
+                    SELECT count(*) FROM notification_actors
+                    WHERE notification_id = notification.notification_id
+                ) AS "actors_count!",
+                article.slug AS "article_slug?",
+                article.title AS "article_title?"
+            FROM notifications notification LEFT JOIN articles article USING (article_id)
+            WHERE notification.notification_id = $1
+            "#,
+            notification_id,
+        )
+        .fetch_one(db)
+        .await?;
+        let Some(entry) = self.summarize(&activity) else {
+            return Ok(false);
+        };
+        let topic = activity.kind.0;
+        self.send(
+            recipient.user_id,
+            &recipient.email,
+            &recipient.username,
+            &entry.summary.clone(),
+            &[entry],
+            &[],
+            Some(topic),
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Email the author's followers who want to know about their new articles
+    /// right away about the new article.
+    ///
+    /// Returns the number of emails sent.
+    pub async fn announce_article(&self, db: &PgPool, article_id: Uuid) -> anyhow::Result<u64> {
+        let Some(article) = sqlx::query_as!(
+            ArticleRow,
+            r#"
+            SELECT article.slug, article.title, author.username AS author
+            FROM articles article JOIN users author USING (user_id)
+            WHERE article.article_id = $1
+            "#,
+            article_id,
+        )
+        .fetch_optional(db)
+        .await?
+        else {
+            return Ok(0);
+        };
+        let followers = sqlx::query!(
+            r#"
+            SELECT follower.user_id, follower.email, follower.username
+            FROM
+                articles article
+                JOIN follows follow ON follow.followed_user_id = article.user_id
+                JOIN users follower ON follower.user_id = follow.following_user_id
+            WHERE
+                article.article_id = $1 AND
+                follower.status = 'ACTIVE' AND
+                email_delivery(follower.user_id, 'article') = 'immediate'
+            "#,
+            article_id,
+        )
+        .fetch_all(db)
+        .await?;
+
+        let entries = [self.entry(&article)];
+        let subject = format!("New article by {}", article.author);
+        let mut nsent = 0;
+        for follower in followers {
+            let sent = self
+                .send(
+                    follower.user_id,
+                    &follower.email,
+                    &follower.username,
+                    &subject,
+                    &[],
+                    &entries,
+                    Some(EmailTopic::Article),
+                )
+                .await;
+            match sent {
+                Ok(()) => nsent += 1,
+                Err(e) => warn!(error = ?e, "failed to announce article"),
+            }
+        }
+        Ok(nsent)
+    }
+
+    /// Send digests of the given kind (daily or weekly) which are due.
+    ///
+    /// A digest lists unread notifications (and new articles of the followed
+    /// authors) that have happened since the previous digest of this kind,
+    /// and only covers the topics the user wants to receive in this digest.
+    /// The users who have got nothing to be told about are not emailed.
+    ///
+    /// This is meant to be called periodically (e.g. hourly), and returns
+    /// the number of digests sent. Each user's digest is claimed before being
+    /// sent, so that a run that is retried (or overlaps with the previous one)
+    /// does not email the same digest twice.
+    pub async fn send_digests(&self, db: &PgPool, delivery: Delivery) -> anyhow::Result<u64> {
+        let Some(period) = delivery.period() else {
+            bail!("{} is not a digest", delivery.as_str());
+        };
+        let topics: Vec<_> = EmailTopic::ALL.iter().map(|t| t.as_str()).collect();
+        let recipients = sqlx::query!(
+            r#"
+            SELECT
+                recipient.user_id,
+                recipient.email,
+                recipient.username,
+                COALESCE(digest.sent_at, NOW() - make_interval(secs => $2)) AS "since!"
+            FROM
+                users recipient
+                LEFT JOIN email_digests digest
+                ON digest.user_id = recipient.user_id AND digest.delivery = $1
+            WHERE
+                recipient.status = 'ACTIVE' AND
+                (digest.sent_at IS NULL OR digest.sent_at <= NOW() - make_interval(secs => $3)) AND
+                EXISTS(
+                    SELECT 1 FROM unnest($4::TEXT[]) topic
+                    WHERE email_delivery(recipient.user_id, topic) = $1
+                )
+            "#,
+            delivery.as_str(),
+            period.as_secs_f64(),
+            (period - DIGEST_GRACE_PERIOD).as_secs_f64(),
+            &topics as &[&str],
+        )
+        .fetch_all(db)
+        .await?;
+
+        let heading = match delivery {
+            Delivery::Weekly => "Here is what has happened this week",
+            _ => "Here is what has happened today",
+        };
+        let mut nsent = 0;
+        for recipient in recipients {
+            // the row stays locked until we are done with this recipient, and
+            // so a concurrent run will wait and then find the digest not due
+            let mut tx = db.begin().await?;
+            let claimed = sqlx::query_scalar!(
+                r#"
+                INSERT INTO email_digests AS digest (user_id, delivery, sent_at)
+                VALUES ($1, $2, NOW())
+                ON CONFLICT (user_id, delivery) DO UPDATE SET sent_at = EXCLUDED.sent_at
+                WHERE digest.sent_at <= NOW() - make_interval(secs => $3)
+                RETURNING user_id
+                "#,
+                recipient.user_id,
+                delivery.as_str(),
+                (period - DIGEST_GRACE_PERIOD).as_secs_f64(),
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+            if claimed.is_none() {
+                continue;
+            }
+            let activity = sqlx::query_as!(
+                ActivityRow,
+                r#"
+                SELECT
+                    to_jsonb(notification.kind) AS "kind!: SqlxJson<EmailTopic>",
+                    (
+                        SELECT actor.username
+                        FROM notification_actors notification_actor JOIN users actor USING (user_id)
+                        WHERE notification_actor.notification_id = notification.notification_id
+                        ORDER BY notification_actor.created_at DESC
+                        LIMIT 1
+                    ) AS actor,
+                    (
+                        SELECT count(*) FROM notification_actors
+                        WHERE notification_id = notification.notification_id
+                    ) AS "actors_count!",
+                    article.slug AS "article_slug?",
+                    article.title AS "article_title?"
+                FROM notifications notification LEFT JOIN articles article USING (article_id)
+                WHERE
+                    notification.user_id = $1 AND
+                    notification.read_at IS NULL AND
+                    notification.notified_at > $2 AND
+                    email_delivery($1, notification.kind) = $3
+                ORDER BY notification.notified_at DESC
+                LIMIT $4
+                "#,
+                recipient.user_id,
+                recipient.since,
+                delivery.as_str(),
+                MAX_ENTRIES,
+            )
+            .fetch_all(db)
+            .await?;
+            let articles = sqlx::query_as!(
+                ArticleRow,
+                r#"
+                SELECT article.slug, article.title, author.username AS author
+                FROM
+                    articles article
+                    JOIN follows follow ON follow.followed_user_id = article.user_id
+                    JOIN users author ON author.user_id = article.user_id
+                WHERE
+                    follow.following_user_id = $1 AND
+                    article.created_at > $2 AND
+                    email_delivery($1, 'article') = $3
+                ORDER BY article.created_at DESC
+                LIMIT $4
+                "#,
+                recipient.user_id,
+                recipient.since,
+                delivery.as_str(),
+                MAX_ENTRIES,
+            )
+            .fetch_all(db)
+            .await?;
+
+            let activity: Vec<_> = activity
+                .iter()
+                .filter_map(|row| self.summarize(row))
+                .collect();
+            let articles: Vec<_> = articles.iter().map(|row| self.entry(row)).collect();
+            if !activity.is_empty() || !articles.is_empty() {
+                let sent = self
+                    .send(
+                        recipient.user_id,
+                        &recipient.email,
+                        &recipient.username,
+                        heading,
+                        &activity,
+                        &articles,
+                        None,
+                    )
+                    .await;
+                if let Err(e) = sent {
+                    // the claim is rolled back, and so this is to be
+                    // retried with the next run
+                    warn!(error = ?e, "failed to send digest");
+                    continue;
+                }
+                nsent += 1;
+            }
+            tx.commit().await?;
+        }
+        Ok(nsent)
+    }
+
+    /// Render and send notifications email, with an unsubscribe link for
+    /// the `topic` (or for all the notification emails).
+    #[allow(clippy::too_many_arguments)]
+    async fn send(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        username: &str,
+        subject: &str,
+        activity: &[EmailEntry],
+        articles: &[EmailEntry],
+        topic: Option<EmailTopic>,
+    ) -> anyhow::Result<()> {
+        let token = issue_unsubscribe_token(user_id, topic, &self.key)?;
+        let mut unsubscribe_url = self.api_url.join("api/users/unsubscribe")?;
+        unsubscribe_url
+            .query_pairs_mut()
+            .append_pair("token", &token);
+        let html = DigestEmailHtml {
+            username,
+            heading: subject,
+            activity,
+            articles,
+            app_url: &self.app_url,
+            unsubscribe_url: &unsubscribe_url,
+        }
+        .to_string();
+        let text = DigestEmailText {
+            username,
+            heading: subject,
+            activity,
+            articles,
+            app_url: &self.app_url,
+            unsubscribe_url: &unsubscribe_url,
+        }
+        .to_string();
+        self.mailer
+            .send_unsubscribable_email(email, subject, &html, &text, &unsubscribe_url)
+            .await?;
+        Ok(())
+    }
+
+    /// Describe notification, e.g. "alice and 2 others favorited your article".
+    ///
+    /// Returns `None` if there is nothing to describe, e.g. all of the actors
+    /// have been deleted.
+    fn summarize(&self, row: &ActivityRow) -> Option<EmailEntry> {
+        let actor = row.actor.as_deref()?;
+        let actors = match row.actors_count {
+            ..=1 => actor.to_owned(),
+            2 => format!("{actor} and 1 other"),
+            n => format!("{actor} and {} others", n - 1),
+        };
+        let (summary, path) = match (row.kind.0, &row.article_slug, &row.article_title) {
+            (EmailTopic::Follow, _, _) => (
+                format!("{actors} started following you"),
+                format!("profile/{actor}"),
+            ),
+            (EmailTopic::Favorite, Some(slug), Some(title)) => (
+                format!("{actors} favorited your article \"{title}\""),
+                format!("article/{slug}"),
+            ),
+            (EmailTopic::Comment, Some(slug), Some(title)) => (
+                format!("{actors} commented on your article \"{title}\""),
+                format!("article/{slug}"),
+            ),
+            (EmailTopic::Reply, Some(slug), Some(title)) => (
+                format!("{actors} replied to your comment under \"{title}\""),
+                format!("article/{slug}"),
+            ),
+            (EmailTopic::Mention, Some(slug), Some(title)) => (
+                format!("{actors} mentioned you in \"{title}\""),
+                format!("article/{slug}"),
+            ),
+            _ => return None,
+        };
+        let url = self.app_url.join(&path).ok()?;
+        Some(EmailEntry { summary, url })
+    }
+
+    fn entry(&self, article: &ArticleRow) -> EmailEntry {
+        EmailEntry {
+            summary: format!("\"{}\" by {}", article.title, article.author),
+            url: self
+                .app_url
+                .join(&format!("article/{}", article.slug))
+                .unwrap_or_else(|_| self.app_url.clone()),
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use crate::services::moderator::utils::{find_mentions, in_link};
+use comrak::nodes::{AstNode, NodeLink, NodeValue};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+
+/// Entry in article's table of contents.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TocEntry {
+    /// Heading level, `1` through `6`.
+    #[schema(example = 2, minimum = 1, maximum = 6)]
+    pub level: u8,
+
+    /// Heading anchor, i.e. the `id` of the heading element in rendered HTML.
+    #[schema(example = "user-content-getting-started")]
+    pub id: String,
+
+    /// Heading's text content.
+    #[schema(example = "Getting started")]
+    pub title: String,
+}
+
+/// Prefix of the elements' `id`s in rendered HTML.
+///
+/// Same as GitHub does, so that the ids neither clash with the ones of the page
+/// the article is displayed on, nor clobber the DOM (e.g. `window.config`).
+const ID_PREFIX: &str = "user-content-";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rendered {
+    pub html: String,
+    pub toc: Vec<TocEntry>,
+}
+
+/// Render markdown `content` as sanitized HTML.
+///
+/// We are allowing raw HTML when rendering with comrak (authors do embed
+/// images and the like), and then passing the output through an allow-list
+/// sanitizer, so that scripts, event handlers, iframes and other nasty things
+/// never make it to the reader's browser. Headings are given anchors (using
+/// the same algorithm GitHub does) and collected into a table of contents,
+/// while `@username` mentions of the `users` (i.e. the ones known to exist)
+/// are turned into links to the users' profiles.
+pub fn render(content: &str, users: &HashSet<String>) -> Rendered {
+    let mut options = comrak::Options::default();
+    options.extension.strikethrough = true;
+    options.extension.table = true;
+    options.extension.autolink = true;
+    options.extension.tasklist = true;
+    options.extension.footnotes = true;
+    options.extension.header_ids = Some(ID_PREFIX.to_owned());
+    options.render.r#unsafe = true;
+
+    let arena = comrak::Arena::new();
+    let root = comrak::parse_document(&arena, content, &options);
+
+    // comrak is anchorizing headings while rendering them in document order,
+    // so we are doing the same here to arrive at identical identifiers
+    let mut anchorizer = comrak::Anchorizer::new();
+    let mut toc = Vec::new();
+    for node in root.descendants() {
+        if let NodeValue::Heading(ref heading) = node.data.borrow().value {
+            let title = comrak::html::collect_text(node);
+            toc.push(TocEntry {
+                level: heading.level,
+                id: format!("{ID_PREFIX}{}", anchorizer.anchorize(&title)),
+                title,
+            });
+        }
+    }
+
+    link_mentions(&arena, root, users);
+
+    let mut unsafe_html = String::new();
+    comrak::format_html(root, &options, &mut unsafe_html).expect("writing to string");
+
+    Rendered {
+        html: sanitizer().clean(&unsafe_html).to_string(),
+        toc,
+    }
+}
+
+/// Replace `@username` mentions of the `users` in text nodes with links
+/// to profiles.
+fn link_mentions<'a>(arena: &'a comrak::Arena<'a>, root: &'a AstNode<'a>, users: &HashSet<String>) {
+    let text_nodes: Vec<_> = root
+        .descendants()
+        .filter(|node| matches!(node.data.borrow().value, NodeValue::Text(_)) && !in_link(node))
+        .collect();
+    let text_node = |text: &str| arena.alloc(NodeValue::Text(text.to_owned().into()).into());
+    for node in text_nodes {
+        let text = match node.data.borrow().value {
+            NodeValue::Text(ref text) => text.to_string(),
+            _ => unreachable!("only text nodes collected"),
+        };
+        let mut linked = 0;
+        for mention in find_mentions(&text) {
+            let username = &text[mention.start + 1..mention.end];
+            if !users.contains(username) {
+                continue;
+            }
+            if mention.start > linked {
+                node.insert_before(text_node(&text[linked..mention.start]));
+            }
+            let link = arena.alloc(
+                NodeValue::Link(Box::new(NodeLink {
+                    url: format!("/profile/{username}"),
+                    title: String::new(),
+                }))
+                .into(),
+            );
+            link.append(text_node(&text[mention.clone()]));
+            node.insert_before(link);
+            linked = mention.end;
+        }
+        if linked == 0 {
+            continue;
+        }
+        if linked < text.len() {
+            node.data.borrow_mut().value = NodeValue::Text(text[linked..].to_owned().into());
+        } else {
+            node.detach();
+        }
+    }
+}
+
+/// Strip any markup from search `snippet` except for `<mark>` highlights.
+pub fn sanitize_snippet(snippet: &str) -> String {
+    ammonia::Builder::empty()
+        .add_tags(["mark"])
+        .clean(snippet)
+        .to_string()
+}
+
+fn sanitizer() -> ammonia::Builder<'static> {
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_tags(["input", "section"])
+        .add_tag_attributes("a", ["id", "aria-hidden", "data-footnote-ref"])
+        .add_tag_attributes("input", ["checked", "disabled"])
+        .add_tag_attributes("li", ["id"])
+        .add_tag_attributes("section", ["data-footnotes"])
+        .add_tag_attributes("th", ["align"])
+        .add_tag_attributes("td", ["align"])
+        .allowed_classes(HashMap::from([
+            ("a", HashSet::from(["anchor", "footnote-backref"])),
+            ("section", HashSet::from(["footnotes"])),
+            ("sup", HashSet::from(["footnote-ref"])),
+        ]))
+        // task lists' checkboxes are the only inputs we are rendering
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .set_tag_attribute_value("input", "disabled", "")
+        .attribute_filter(prefix_ids);
+    builder
+}
+
+/// Make sure all the `id`s in rendered HTML have got the [`ID_PREFIX`].
+///
+/// Headings' ids are prefixed by comrak already, while footnotes' ones are
+/// not, and so we are prefixing those here. Any other id is coming from
+/// the author's raw HTML and is dropped. In-document links are pointed to
+/// the prefixed ids accordingly.
+fn prefix_ids<'u>(element: &str, attribute: &str, value: &'u str) -> Option<Cow<'u, str>> {
+    match (element, attribute) {
+        (_, "id") if value.starts_with(ID_PREFIX) => Some(value.into()),
+        ("li", "id") if value.starts_with("fn-") => Some(format!("{ID_PREFIX}{value}").into()),
+        ("a", "id") if value.starts_with("fnref-") => Some(format!("{ID_PREFIX}{value}").into()),
+        (_, "id") => None,
+        ("a", "href") => match value.strip_prefix('#') {
+            Some(fragment) if !fragment.starts_with(ID_PREFIX) => {
+                Some(format!("#{ID_PREFIX}{fragment}").into())
+            }
+            _ => Some(value.into()),
+        },
+        _ => Some(value.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use std::collections::HashSet;
+
+    #[test]
+    fn render_strips_unsafe_html() {
+        let content = r#"Hello <script>alert("pwned")</script><img src="https://example.com/cat.png" onerror="alert(1)">"#;
+        let rendered = render(content, &HashSet::new());
+        assert!(!rendered.html.contains("<script"));
+        assert!(!rendered.html.contains("onerror"));
+        assert!(
+            rendered
+                .html
+                .contains(r#"<img src="https://example.com/cat.png">"#)
+        );
+    }
+
+    #[test]
+    fn render_links_mentions() {
+        let content = "Thanks @alice and @bob.builder! Reach me at carol@example.com, \
+            not `@dave` or [@erin](https://example.com), and @frank is no one";
+        let users = ["alice", "bob.builder", "dave", "erin"]
+            .map(String::from)
+            .into();
+        let rendered = render(content, &users);
+        assert!(
+            rendered
+                .html
+                .contains(r#"Thanks <a href="/profile/alice" rel="noopener noreferrer">@alice</a> and <a href="/profile/bob.builder" rel="noopener noreferrer">@bob.builder</a>!"#)
+        );
+        assert!(!rendered.html.contains("/profile/example.com"));
+        assert!(!rendered.html.contains("/profile/dave"));
+        assert!(!rendered.html.contains("/profile/erin"));
+        assert!(rendered.html.contains("and @frank is no one"));
+    }
+
+    #[test]
+    fn render_collects_table_of_contents() {
+        let content = "# Intro\n\ntext\n\n## Getting `started`\n\nmore text\n\n## Intro\n";
+        let rendered = render(content, &HashSet::new());
+        let toc: Vec<_> = rendered
+            .toc
+            .iter()
+            .map(|entry| (entry.level, entry.id.as_str(), entry.title.as_str()))
+            .collect();
+        assert_eq!(
+            toc,
+            [
+                (1, "user-content-intro", "Intro"),
+                (2, "user-content-getting-started", "Getting started"),
+                (2, "user-content-intro-1", "Intro"),
+            ]
+        );
+        // anchors in the rendered HTML match the ones in table of contents
+        for entry in rendered.toc {
+            assert!(rendered.html.contains(&format!(r##"id="{}""##, entry.id)));
+            assert!(
+                rendered
+                    .html
+                    .contains(&format!(r##"href="#{}""##, entry.id))
+            );
+        }
+    }
+
+    #[test]
+    fn render_prefixes_ids() {
+        let content = "See[^note] the [intro](#intro).\n\n# Intro\n\n\
+            <a id=\"config\">clobber</a> <a id=\"user-content-mine\">mine</a>\n\n\
+            [^note]: A footnote.\n";
+        let rendered = render(content, &HashSet::new());
+        assert!(!rendered.html.contains(r#"id="config""#));
+        assert!(rendered.html.contains(r#"id="user-content-mine""#));
+        assert!(
+            rendered
+                .html
+                .contains(r##"<a href="#user-content-intro" rel="noopener noreferrer">intro</a>"##)
+        );
+        assert!(rendered.html.contains(r#"<li id="user-content-fn-note">"#));
+        assert!(
+            rendered
+                .html
+                .contains(r##"href="#user-content-fn-note" id="user-content-fnref-note""##)
+        );
+        assert!(
+            rendered
+                .html
+                .contains(r##"href="#user-content-fnref-note""##)
+        );
+    }
+
+    #[test]
+    fn render_keeps_checkboxes_only() {
+        let content = "- [x] done\n- [ ] todo\n\n<input type=\"password\" name=\"secret\">";
+        let rendered = render(content, &HashSet::new());
+        assert!(!rendered.html.contains("password"));
+        assert_eq!(rendered.html.matches("<input").count(), 3);
+        assert_eq!(
+            rendered.html.matches(r#"type="checkbox""#).count(),
+            rendered.html.matches("<input").count()
+        );
+    }
+}
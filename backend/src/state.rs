@@ -1,23 +1,31 @@
+use crate::services::analytics::Analytics;
 use crate::services::cache::Cache;
+use crate::services::events::Events;
 use crate::services::mailer::ResendMailer;
 use crate::services::moderator::Moderator;
+use crate::services::notifier::EmailNotifier;
 use crate::{config::Config, services::captcha::Captcha};
 use anyhow::Context;
+use deadpool_redis::redis::Client as RedisClient;
 use deadpool_redis::{Config as DeadpoolConfig, Pool as RedisPool, Runtime};
 use jsonwebtoken::{DecodingKey, EncodingKey};
-use secrecy::ExposeSecret;
+use secrecy::{ExposeSecret, SecretString};
 use sqlx::{PgPool, postgres::PgPoolOptions};
 use url::Url;
 
 pub(crate) struct AppContext {
+    pub secret_key: SecretString,
     pub enc_key: EncodingKey,
     pub dec_key: DecodingKey,
     pub db: PgPool,
     pub redis: RedisPool,
     pub cache: Cache,
+    pub analytics: Analytics,
+    pub events: Events,
     pub mailer: ResendMailer,
     pub captcha: Captcha,
     pub moderator: Moderator,
+    pub notifier: EmailNotifier,
     pub frontend_url: Url,
     pub skip_email_verification: bool,
     pub skip_captcha_verification: bool,
@@ -35,6 +43,8 @@ impl AppContext {
         let redis_pool = cfg
             .create_pool(Some(Runtime::Tokio1))
             .context("failed to create pool")?;
+        let redis_client = RedisClient::open(config.redis_url.expose_secret())
+            .context("failed to create Redis client")?;
         let secret = config.secret_key.expose_secret();
         let resend = ResendMailer::new(
             config.mailer_from.clone(),
@@ -49,15 +59,27 @@ impl AppContext {
             config.openai_base_url.clone(),
         );
 
+        let enc_key = EncodingKey::from_base64_secret(secret)?;
+        let notifier = EmailNotifier::new(
+            resend.clone(),
+            enc_key.clone(),
+            config.frontend_url.clone(),
+            config.backend_url.clone(),
+        );
+
         let ctx = AppContext {
-            enc_key: EncodingKey::from_base64_secret(secret)?,
+            secret_key: config.secret_key.clone(),
+            enc_key,
             dec_key: DecodingKey::from_base64_secret(secret)?,
             db: postgres_pool,
             redis: redis_pool.clone(),
-            cache: Cache::new(redis_pool),
+            cache: Cache::new(redis_pool.clone()),
+            analytics: Analytics::new(redis_pool.clone()),
+            events: Events::new(redis_pool, redis_client),
             mailer: resend,
             captcha,
             moderator,
+            notifier,
             frontend_url: config.frontend_url.clone(),
             skip_email_verification: config.skip_email_verification.unwrap_or_default(),
             skip_captcha_verification: config.skip_captcha_verification.unwrap_or_default(),
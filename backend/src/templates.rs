@@ -18,3 +18,45 @@ pub struct OTPEmailText<'a> {
     pub otp_code: &'a str,
     pub app_url: &'a Url,
 }
+
+/// Entry of a notifications email, e.g. "alice favorited your article".
+pub struct EmailEntry {
+    pub summary: String,
+    pub url: Url,
+}
+
+/// HTML template for notifications email, be it a digest or a single
+/// notification sent right away.
+#[derive(Template)]
+#[template(path = "email_digest.html")]
+pub struct DigestEmailHtml<'a> {
+    pub username: &'a str,
+    pub heading: &'a str,
+    pub activity: &'a [EmailEntry],
+    pub articles: &'a [EmailEntry],
+    pub app_url: &'a Url,
+    pub unsubscribe_url: &'a Url,
+}
+
+/// Text companion for notifications email.
+#[derive(Template)]
+#[template(path = "email_digest.txt")]
+pub struct DigestEmailText<'a> {
+    pub username: &'a str,
+    pub heading: &'a str,
+    pub activity: &'a [EmailEntry],
+    pub articles: &'a [EmailEntry],
+    pub app_url: &'a Url,
+    pub unsubscribe_url: &'a Url,
+}
+
+/// HTML page to confirm unsubscription from emails.
+#[derive(Template)]
+#[template(path = "unsubscribe.html")]
+pub struct UnsubscribePage<'a> {
+    /// What the user is unsubscribing from, e.g. "replies to your comments".
+    pub topic: &'a str,
+    pub unsubscribed: bool,
+    pub action_url: &'a str,
+    pub app_url: &'a Url,
+}
@@ -1,3 +1,4 @@
+use crate::services::notifier::{Delivery, EmailNotifier};
 use anyhow::Context as _;
 use sqlx::{PgPool, postgres::PgPoolOptions};
 use std::{sync::Arc, time::Duration};
@@ -26,6 +27,9 @@ use temporal_sdk_core::{CoreRuntime, RuntimeOptionsBuilder, WorkerConfigBuilder,
 use url::Url;
 
 const SCHEDULE_ID: &str = "scheduled_maintenance_id_001";
+const TRENDING_SCHEDULE_ID: &str = "trending_refresh_id_001";
+const DIGESTS_SCHEDULE_ID: &str = "email_digests_id_001";
+const TASK_QUEUE: &str = "scheduled_maintenance";
 
 pub(crate) type TemporalClient = RetryClient<Client>;
 
@@ -45,15 +49,49 @@ pub(crate) async fn init_runtime() -> anyhow::Result<CoreRuntime> {
 
 pub(crate) async fn create_maintenance_schedule(
     client: &mut TemporalClient,
+) -> Result<Option<CreateScheduleResponse>, Status> {
+    create_schedule(client, SCHEDULE_ID, "@every 24h", "scheduled_maintenance").await
+}
+
+/// Schedule periodic re-computation of trending articles.
+///
+/// See `trending_articles_refresh` activity for details.
+pub(crate) async fn create_trending_schedule(
+    client: &mut TemporalClient,
+) -> Result<Option<CreateScheduleResponse>, Status> {
+    create_schedule(
+        client,
+        TRENDING_SCHEDULE_ID,
+        "@every 15m",
+        "trending_refresh",
+    )
+    .await
+}
+
+/// Schedule delivery of daily and weekly email digests.
+///
+/// The schedule runs hourly, and each run only emails those users whose
+/// digest is due, see `EmailNotifier::send_digests`.
+pub(crate) async fn create_digests_schedule(
+    client: &mut TemporalClient,
+) -> Result<Option<CreateScheduleResponse>, Status> {
+    create_schedule(client, DIGESTS_SCHEDULE_ID, "@every 1h", "email_digests").await
+}
+
+async fn create_schedule(
+    client: &mut TemporalClient,
+    schedule_id: &str,
+    cron: &str,
+    workflow: &str,
 ) -> Result<Option<CreateScheduleResponse>, Status> {
     let response = client
         .create_schedule(tonic::Request::new(CreateScheduleRequest {
-            schedule_id: SCHEDULE_ID.into(),
-            request_id: format!("{}_create_request_dedup", SCHEDULE_ID),
+            schedule_id: schedule_id.into(),
+            request_id: format!("{}_create_request_dedup", schedule_id),
             namespace: "default".into(),
             schedule: Some(Schedule {
                 spec: Some(ScheduleSpec {
-                    cron_string: vec!["@every 24h".into()],
+                    cron_string: vec![cron.into()],
                     ..Default::default()
                 }),
                 policies: Some(SchedulePolicies {
@@ -62,12 +100,12 @@ pub(crate) async fn create_maintenance_schedule(
                 }),
                 action: Some(ScheduleAction {
                     action: Some(Action::StartWorkflow(NewWorkflowExecutionInfo {
-                        workflow_id: "scheduled_maintenance_workflow".into(),
+                        workflow_id: format!("{}_workflow", workflow),
                         workflow_type: Some(WorkflowType {
-                            name: "scheduled_maintenance".into(),
+                            name: workflow.into(),
                         }),
                         task_queue: Some(TaskQueue {
-                            name: "scheduled_maintenance".into(),
+                            name: TASK_QUEUE.into(),
                             kind: TaskQueueKind::Unspecified as i32,
                             ..Default::default()
                         }),
@@ -88,7 +126,7 @@ pub(crate) async fn create_maintenance_schedule(
         Err(e) => {
             let grpc_status = Status::from_error(e.into_boxed_dyn_error());
             if grpc_status.code() == tonic::Code::AlreadyExists {
-                info!(schedule_id = SCHEDULE_ID, "schedule already exists");
+                info!(schedule_id, "schedule already exists");
                 Ok(None)
             } else {
                 Err(grpc_status)
@@ -101,16 +139,17 @@ pub(crate) async fn create_maintenance_worker(
     rt: &CoreRuntime,
     db_url: &str,
     client: TemporalClient,
+    notifier: EmailNotifier,
 ) -> anyhow::Result<Worker> {
     let config = WorkerConfigBuilder::default()
         .namespace("default")
         .task_types(WorkerTaskTypes::all())
-        .task_queue("scheduled_maintenance")
+        .task_queue(TASK_QUEUE)
         .versioning_strategy(WorkerVersioningStrategy::default())
         .client_identity_override(Some("scheduled_maintenance_worker_001".into()))
         .build()?;
     let core_worker = init_worker(rt, config, client)?;
-    let mut worker = Worker::new_from_core(Arc::new(core_worker), "scheduled_maintenance");
+    let mut worker = Worker::new_from_core(Arc::new(core_worker), TASK_QUEUE);
     let postgres_pool = PgPoolOptions::new()
         .connect(db_url)
         .await
@@ -129,9 +168,26 @@ pub(crate) async fn create_maintenance_worker(
             .ok_or(anyhow::anyhow!(
                 "Expected payload from 'confirmation_tokens_clean_up' activity"
             ))?;
-        let result = CleanUpResult::from_json_payload(&payload)
+        let CleanUpResult { naffected } = CleanUpResult::from_json_payload(&payload)
             .context("failed to deserialize activity result")?;
-        Ok(temporal_sdk::WfExitValue::Normal(result))
+        let payload = ctx
+            .activity(ActivityOptions {
+                activity_type: "article_counters_reconciliation".into(),
+                start_to_close_timeout: Some(Duration::from_secs(60)),
+                input: Empty.as_json_payload().expect("valid json"),
+                ..Default::default()
+            })
+            .await
+            .success_payload_or_error()?
+            .ok_or(anyhow::anyhow!(
+                "Expected payload from 'article_counters_reconciliation' activity"
+            ))?;
+        let ReconciliationResult { nrepaired } = ReconciliationResult::from_json_payload(&payload)
+            .context("failed to deserialize activity result")?;
+        Ok(temporal_sdk::WfExitValue::Normal(MaintenanceResult {
+            naffected,
+            nrepaired,
+        }))
     });
     worker.register_activity(
         "confirmation_tokens_clean_up",
@@ -146,11 +202,164 @@ pub(crate) async fn create_maintenance_worker(
             Ok(CleanUpResult { naffected })
         },
     );
+    worker.register_activity(
+        "article_counters_reconciliation",
+        |ctx: ActContext, _input: Empty| async move {
+            let pool: &PgPool = ctx.app_data().expect("PostgrSQL connection pool");
+            let nrepaired = reconcile_article_counters(pool).await?;
+            if nrepaired > 0 {
+                warn!(nrepaired, "repaired drifted article counters");
+            }
+            Ok(ReconciliationResult { nrepaired })
+        },
+    );
+    worker.register_wf("trending_refresh", move |ctx: WfContext| async move {
+        info!(task_queue = %ctx.task_queue(), "staring workflow execution");
+        let payload = ctx
+            .activity(ActivityOptions {
+                activity_type: "trending_articles_refresh".into(),
+                start_to_close_timeout: Some(Duration::from_secs(60)),
+                input: Empty.as_json_payload().expect("valid json"),
+                ..Default::default()
+            })
+            .await
+            .success_payload_or_error()?
+            .ok_or(anyhow::anyhow!(
+                "Expected payload from 'trending_articles_refresh' activity"
+            ))?;
+        let result = RefreshResult::from_json_payload(&payload)
+            .context("failed to deserialize activity result")?;
+        Ok(temporal_sdk::WfExitValue::Normal(result))
+    });
+    worker.register_activity(
+        "trending_articles_refresh",
+        |ctx: ActContext, _input: Empty| async move {
+            let pool: &PgPool = ctx.app_data().expect("PostgrSQL connection pool");
+            let nscored = refresh_trending_articles(pool).await?;
+            Ok(RefreshResult { nscored })
+        },
+    );
+    worker.register_wf("email_digests", move |ctx: WfContext| async move {
+        info!(task_queue = %ctx.task_queue(), "staring workflow execution");
+        let mut nsent = 0;
+        for delivery in [Delivery::Daily, Delivery::Weekly] {
+            let payload = ctx
+                .activity(ActivityOptions {
+                    activity_type: "email_digests_delivery".into(),
+                    start_to_close_timeout: Some(Duration::from_secs(30 * 60)),
+                    input: delivery.as_json_payload().expect("valid json"),
+                    ..Default::default()
+                })
+                .await
+                .success_payload_or_error()?
+                .ok_or(anyhow::anyhow!(
+                    "Expected payload from 'email_digests_delivery' activity"
+                ))?;
+            let DeliveryResult { nsent: n } = DeliveryResult::from_json_payload(&payload)
+                .context("failed to deserialize activity result")?;
+            nsent += n;
+        }
+        Ok(temporal_sdk::WfExitValue::Normal(DeliveryResult { nsent }))
+    });
+    worker.register_activity(
+        "email_digests_delivery",
+        |ctx: ActContext, delivery: Delivery| async move {
+            let pool: &PgPool = ctx.app_data().expect("PostgrSQL connection pool");
+            let notifier: &EmailNotifier = ctx.app_data().expect("email notifier");
+            let nsent = notifier.send_digests(pool, delivery).await?;
+            Ok(DeliveryResult { nsent })
+        },
+    );
     worker.insert_app_data(postgres_pool);
+    worker.insert_app_data(notifier);
 
     Ok(worker)
 }
 
+/// Re-compute trending articles scores for all the supported windows.
+///
+/// Each view, favorite and comment made within the window contributes to the
+/// article's score, with the contribution halving every half of the window,
+/// so that the recent activity weighs more. Comments are considered a stronger
+/// signal of engagement than favorites, which in their turn weigh way more
+/// than views.
+async fn refresh_trending_articles(pool: &PgPool) -> anyhow::Result<u64> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!("DELETE FROM trending_articles")
+        .execute(&mut *tx)
+        .await?;
+    let nscored = sqlx::query!(
+        r#"
+        INSERT INTO trending_articles (period, article_id, score)
+        SELECT
+            period,
+            article_id,
+            sum(
+                weight * power(
+                    0.5,
+                    extract(EPOCH FROM NOW() - activity.created_at) / (extract(EPOCH FROM span) / 2)
+                )
+            )
+        FROM
+            (
+                VALUES
+                    ('day', INTERVAL '1 day'),
+                    ('week', INTERVAL '7 days'),
+                    ('month', INTERVAL '30 days')
+            ) periods (period, span)
+            JOIN (
+                SELECT article_id, created_at, 1.0 AS weight FROM favorites
+                UNION ALL
+                SELECT article_id, created_at, 2.0 AS weight FROM comments WHERE deleted_at IS NULL
+                UNION ALL
+                -- views are only tracked on a daily basis
+                SELECT article_id, day::TIMESTAMP AT TIME ZONE 'UTC', 0.1 * views FROM article_stats
+            ) activity ON activity.created_at > NOW() - span
+        GROUP BY period, article_id
+        "#
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+    Ok(nscored)
+}
+
+/// Re-count favorites and comments of each article.
+///
+/// The counters are maintained by triggers and so should never drift, but
+/// if they do (e.g. someone disabled triggers while doing a bulk import),
+/// this will put them right. Returns the number of articles repaired.
+async fn reconcile_article_counters(pool: &PgPool) -> anyhow::Result<u64> {
+    let nrepaired = sqlx::query!(
+        r#"
+        UPDATE articles
+        SET
+            favorites_count = actual.favorites_count,
+            comments_count = actual.comments_count
+        FROM (
+            SELECT
+                article_id,
+                (SELECT count(*) FROM favorites WHERE article_id = articles.article_id)::INTEGER
+                    AS favorites_count,
+                (
+                    SELECT count(*) FROM comments
+                    WHERE article_id = articles.article_id AND deleted_at IS NULL
+                )::INTEGER AS comments_count
+            FROM articles
+        ) actual
+        WHERE
+            articles.article_id = actual.article_id AND
+            (articles.favorites_count, articles.comments_count)
+                IS DISTINCT FROM (actual.favorites_count, actual.comments_count)
+        "#
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+    Ok(nrepaired)
+}
+
 #[derive(Serialize, Deserialize)]
 struct Empty;
 
@@ -158,3 +367,24 @@ struct Empty;
 struct CleanUpResult {
     naffected: u64,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RefreshResult {
+    nscored: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReconciliationResult {
+    nrepaired: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MaintenanceResult {
+    naffected: u64,
+    nrepaired: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DeliveryResult {
+    nsent: u64,
+}
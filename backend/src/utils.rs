@@ -7,11 +7,13 @@ use argon2::password_hash::Salt;
 use argon2::password_hash::SaltString;
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
+use hmac::{Hmac, Mac as _};
 use md5::{Digest as _, Md5};
 use rand::Rng as _;
 use rand::TryRngCore;
 use rand::distr::Alphanumeric;
 use rand::rngs::OsRng;
+use sha2::Sha256;
 
 #[allow(unused)]
 pub fn gen_alphanum_string(length: usize) -> String {
@@ -73,6 +75,16 @@ pub fn md5_hash(data: impl AsRef<[u8]>) -> String {
     BASE64_STANDARD.encode(result)
 }
 
+/// Keyed hash of `data`, which (unlike [`md5_hash`]) cannot be reversed
+/// by brute-forcing a small input space without knowing the `key`.
+pub fn hmac_hash(key: impl AsRef<[u8]>, data: impl AsRef<[u8]>) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key.as_ref()).expect("HMAC to accept keys of any size");
+    mac.update(data.as_ref());
+    let result = mac.finalize().into_bytes();
+    BASE64_STANDARD.encode(result)
+}
+
 #[cfg(test)]
 mod tests {
 
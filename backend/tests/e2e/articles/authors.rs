@@ -0,0 +1,230 @@
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+use crate::utils::{TestContext, fake};
+
+async fn invite_accept_and_remove_coauthor(ctx: TestContext) {
+    let owner = fake::create_activated_user(&ctx).await;
+    let editor = fake::create_activated_user(&ctx).await;
+    let stranger = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &owner.token, 1, None).await;
+    let slug = &slugs[0];
+    let article_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{slug}"))
+        .unwrap();
+    let authors_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{slug}/authors"))
+        .unwrap();
+    let invitations_url = ctx.backend_url.join("/api/user/invitations").unwrap();
+
+    // the owner is listed among the article's authors right away
+    let article = read_article(&ctx, slug).await;
+    assert_eq!(article["authors"].as_array().unwrap().len(), 1);
+    assert_eq!(article["authors"][0]["username"], owner.username);
+    assert_eq!(article["authors"][0]["role"], "owner");
+
+    // only the owner can invite co-authors ...
+    let resp = ctx
+        .http_client
+        .post(authors_url.clone())
+        .json(&json!({"invitation": {"username": editor.username}}))
+        .bearer_auth(&stranger.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    // ... and only existing users can be invited
+    let resp = ctx
+        .http_client
+        .post(authors_url.clone())
+        .json(&json!({"invitation": {"username": "whoever-that-is"}}))
+        .bearer_auth(&owner.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // happy path
+    let resp = ctx
+        .http_client
+        .post(authors_url.clone())
+        .json(&json!({"invitation": {"username": editor.username}}))
+        .bearer_auth(&owner.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["invitation"]["username"], editor.username);
+    assert_eq!(payload["invitation"]["invitedBy"], owner.username);
+    assert_eq!(payload["invitation"]["role"], "editor");
+    assert_eq!(payload["invitation"]["article"]["slug"], slug.as_str());
+
+    // users cannot be invited twice
+    let resp = ctx
+        .http_client
+        .post(authors_url.clone())
+        .json(&json!({"invitation": {"username": editor.username}}))
+        .bearer_auth(&owner.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // pending invitations are not listed among the article's authors, ...
+    let article = read_article(&ctx, slug).await;
+    assert_eq!(article["authors"].as_array().unwrap().len(), 1);
+    // ... do not grant any permissions, ...
+    let resp = ctx
+        .http_client
+        .put(article_url.clone())
+        .json(&json!({"article": {"title": "Edited by co-author"}}))
+        .bearer_auth(&editor.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    // ... but are visible to the invitee
+    let resp = ctx
+        .http_client
+        .get(invitations_url.clone())
+        .bearer_auth(&editor.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    let invitations = payload["invitations"].as_array().unwrap();
+    assert_eq!(invitations.len(), 1);
+    assert_eq!(invitations[0]["article"]["slug"], slug.as_str());
+
+    // invitations can only be accepted by invitees
+    let accept_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{slug}/authors/accept"))
+        .unwrap();
+    let resp = ctx
+        .http_client
+        .post(accept_url.clone())
+        .bearer_auth(&stranger.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let resp = ctx
+        .http_client
+        .post(accept_url.clone())
+        .bearer_auth(&editor.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    let authors = payload["article"]["authors"].as_array().unwrap();
+    assert_eq!(authors.len(), 2);
+    assert_eq!(authors[0]["username"], owner.username);
+    assert_eq!(authors[1]["username"], editor.username);
+    assert_eq!(authors[1]["role"], "editor");
+    // the primary author is still the owner
+    assert_eq!(payload["article"]["author"]["username"], owner.username);
+
+    // accepted invitations are no longer pending
+    let resp = ctx
+        .http_client
+        .get(invitations_url)
+        .bearer_auth(&editor.token)
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    assert!(payload["invitations"].as_array().unwrap().is_empty());
+
+    // co-authors can edit the article ...
+    let resp = ctx
+        .http_client
+        .put(article_url.clone())
+        .json(&json!({"article": {"title": "Edited by co-author"}}))
+        .bearer_auth(&editor.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["article"]["title"], "Edited by co-author");
+    // ... but not delete it, ...
+    let resp = ctx
+        .http_client
+        .delete(article_url.clone())
+        .bearer_auth(&editor.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    // ... nor remove the owner
+    let resp = ctx
+        .http_client
+        .delete(
+            ctx.backend_url
+                .join(&format!("/api/articles/{slug}/authors/{}", owner.username))
+                .unwrap(),
+        )
+        .bearer_auth(&owner.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // strangers cannot remove co-authors, while the owner can
+    let editor_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{slug}/authors/{}", editor.username))
+        .unwrap();
+    let resp = ctx
+        .http_client
+        .delete(editor_url.clone())
+        .bearer_auth(&stranger.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = ctx
+        .http_client
+        .delete(editor_url.clone())
+        .bearer_auth(&owner.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let article = read_article(&ctx, slug).await;
+    assert_eq!(article["authors"].as_array().unwrap().len(), 1);
+    let resp = ctx
+        .http_client
+        .delete(editor_url)
+        .bearer_auth(&owner.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+async fn read_article(ctx: &TestContext, slug: &str) -> Value {
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join(&format!("/api/articles/{slug}"))
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let mut payload: Value = resp.json().await.unwrap();
+    payload["article"].take()
+}
+
+mod tests {
+    crate::async_test!(invite_accept_and_remove_coauthor);
+}
@@ -0,0 +1,149 @@
+use reqwest::{Method, StatusCode};
+use serde_json::Value;
+
+use crate::utils::{TestContext, fake};
+
+async fn bookmark_articles_and_list_reading_list(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let reader = fake::create_activated_user(&ctx).await;
+    let stranger = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &author.token, 3, None).await;
+    let bookmarks_url = ctx.backend_url.join("/api/user/bookmarks").unwrap();
+
+    // bookmarking requires authentication ...
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}/bookmark", slugs[0]))
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    // ... and an existing article
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join("/api/articles/whatever/bookmark")
+                .unwrap(),
+        )
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // reading list is private and so is not there for anonymous users
+    let resp = ctx
+        .http_client
+        .get(bookmarks_url.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // the reader bookmarks all the articles (twice, which is fine)
+    for slug in &slugs {
+        for _ in 0..2 {
+            let article = bookmark(&ctx, Method::POST, slug, &reader.token).await;
+            assert_eq!(article["bookmarked"], true);
+            assert_eq!(article["favorited"], false);
+        }
+    }
+    // and then removes one of the bookmarks
+    let article = bookmark(&ctx, Method::DELETE, &slugs[1], &reader.token).await;
+    assert_eq!(article["bookmarked"], false);
+
+    // the flag is only ever set for the user themselves
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}", slugs[0]))
+                .unwrap(),
+        )
+        .bearer_auth(&stranger.token)
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["article"]["bookmarked"], false);
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/articles").unwrap())
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["articles"][0]["bookmarked"], true);
+
+    // reading list has the most recently bookmarked articles first ...
+    let page = reading_list(&ctx, &reader.token, &[("limit", "1")]).await;
+    assert_eq!(page["articlesCount"], 2);
+    assert_eq!(page["articles"][0]["slug"], slugs[2].as_str());
+    assert_eq!(page["articles"][0]["bookmarked"], true);
+    assert!(page["prevCursor"].is_null());
+    let cursor = page["nextCursor"].as_str().unwrap().to_owned();
+    // ... and supports the same pagination as the articles list
+    let page = reading_list(&ctx, &reader.token, &[("cursor", cursor.as_str())]).await;
+    assert_eq!(page["articles"].as_array().unwrap().len(), 1);
+    assert_eq!(page["articles"][0]["slug"], slugs[0].as_str());
+    assert!(page["nextCursor"].is_null());
+    let page = reading_list(&ctx, &reader.token, &[("offset", "2")]).await;
+    assert_eq!(page["articlesCount"], 2);
+    assert!(page["articles"].as_array().unwrap().is_empty());
+    let resp = ctx
+        .http_client
+        .get(bookmarks_url)
+        .query(&[("cursor", cursor.as_str()), ("offset", "1")])
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // someone else's reading list is empty
+    let page = reading_list(&ctx, &stranger.token, &[("limit", "1")]).await;
+    assert_eq!(page["articlesCount"], 0);
+}
+
+/// Bookmark (`POST`) or unbookmark (`DELETE`) the article and return it.
+async fn bookmark(ctx: &TestContext, method: Method, slug: &str, token: &str) -> Value {
+    let resp = ctx
+        .http_client
+        .request(
+            method,
+            ctx.backend_url
+                .join(&format!("/api/articles/{slug}/bookmark"))
+                .unwrap(),
+        )
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    payload["article"].to_owned()
+}
+
+/// Fetch the user's reading list and return the entire payload.
+async fn reading_list(ctx: &TestContext, token: &str, query: &[(&str, &str)]) -> Value {
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/user/bookmarks").unwrap())
+        .query(query)
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    resp.json().await.unwrap()
+}
+
+mod tests {
+    crate::async_test!(bookmark_articles_and_list_reading_list);
+}
@@ -92,6 +92,20 @@ async fn create_read_and_delete_comments(ctx: TestContext) {
     );
     assert_eq!(payload["comment"]["author"]["following"], false);
 
+    // the comment is counted towards the article's comments
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}", &slugs[0]))
+                .expect("valid url"),
+        )
+        .send()
+        .await
+        .expect("http request to succeed");
+    let payload: Value = resp.json().await.expect("JSON payload");
+    assert_eq!(payload["article"]["commentsCount"], 1);
+
     // let's imagine a user got authenticated, but then deleted ...
     let another_user = fake::create_activated_user(&ctx).await;
     sqlx::query("DELETE FROM users WHERE username = $1")
@@ -215,8 +229,657 @@ async fn create_read_and_delete_comments(ctx: TestContext) {
             .await
             .unwrap();
     assert_eq!(ncomments, 0);
+    let ncomments: i32 = sqlx::query_scalar("SELECT comments_count FROM articles WHERE slug = $1")
+        .bind(&slugs[0])
+        .fetch_one(&ctx.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(ncomments, 0);
+}
+
+async fn reply_to_comments_in_threads(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let replier = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &author.token, 2, None).await;
+    let comments_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{}/comments", &slugs[0]))
+        .unwrap();
+    let comment = |token: &str, body: &str, parent_id: Option<&str>| {
+        let req = ctx
+            .http_client
+            .post(comments_url.clone())
+            .json(&json!({"comment": {"body": body, "parentId": parent_id}}))
+            .bearer_auth(token);
+        async move {
+            let resp = req.send().await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let mut payload: Value = resp.json().await.unwrap();
+            payload["comment"].take()
+        }
+    };
+    let list = |params: &'static [(&'static str, &'static str)]| {
+        let req = ctx.http_client.get(comments_url.clone()).query(params);
+        async move {
+            let resp = req.send().await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let mut payload: Value = resp.json().await.unwrap();
+            payload["comments"].take()
+        }
+    };
+
+    // let's start a discussion ...
+    let root = comment(&author.token, "Any questions?", None).await;
+    let root_id = root["id"].as_str().unwrap();
+    assert!(root["parentId"].is_null());
+    assert_eq!(root["depth"], 0);
+    let reply = comment(&replier.token, "Yes, one", Some(root_id)).await;
+    let reply_id = reply["id"].as_str().unwrap();
+    assert_eq!(reply["parentId"], root_id);
+    assert_eq!(reply["depth"], 1);
+    let answer = comment(&author.token, "Go ahead", Some(reply_id)).await;
+    assert_eq!(answer["depth"], 2);
+    // ... while another discussion is started later
+    let latest = comment(&replier.token, "Nice article", None).await;
+
+    // comments under other articles cannot be replied to
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}/comments", &slugs[1]))
+                .unwrap(),
+        )
+        .json(&json!({"comment": {"body": "Wrong thread", "parentId": root_id}}))
+        .bearer_auth(&replier.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // the flat view lists each comment followed by its replies, ...
+    let comments = list(&[]).await;
+    let ids: Vec<_> = comments
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| &c["id"])
+        .collect();
+    assert_eq!(
+        ids,
+        [&latest["id"], &root["id"], &reply["id"], &answer["id"]]
+    );
+    assert_eq!(comments[1]["repliesCount"], 1);
+    // ... while the nested one puts the replies into the comments
+    let comments = list(&[("view", "nested")]).await;
+    assert_eq!(comments.as_array().unwrap().len(), 2);
+    assert_eq!(comments[1]["replies"][0]["id"], reply["id"]);
+    assert_eq!(comments[1]["replies"][0]["replies"][0]["id"], answer["id"]);
+    // the threads can be cut
+    let comments = list(&[("view", "nested"), ("maxDepth", "1")]).await;
+    assert_eq!(comments[1]["replies"][0]["repliesCount"], 1);
+    assert_eq!(comments[1]["replies"][0]["replies"], json!([]));
+    // ... and the replies to a comment can be listed on their own
+    let resp = ctx
+        .http_client
+        .get(comments_url.clone())
+        .query(&[("parentId", root_id)])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    let ids: Vec<_> = payload["comments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| &c["id"])
+        .collect();
+    assert_eq!(ids, [&reply["id"], &answer["id"]]);
+    assert!(payload["nextCursor"].is_null());
+    // the parent comment should belong to the article though
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}/comments", &slugs[1]))
+                .unwrap(),
+        )
+        .query(&[("parentId", root_id)])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // a comment having replies is kept as a placeholder once deleted ...
+    let delete = |token: &str, comment_id: &str| {
+        let req = ctx
+            .http_client
+            .delete(
+                ctx.backend_url
+                    .join(&format!(
+                        "/api/articles/{}/comments/{comment_id}",
+                        &slugs[0]
+                    ))
+                    .unwrap(),
+            )
+            .bearer_auth(token);
+        async move { req.send().await.unwrap().status() }
+    };
+    assert_eq!(
+        delete(&replier.token, reply_id).await,
+        StatusCode::NO_CONTENT
+    );
+    let comments = list(&[]).await;
+    assert_eq!(comments.as_array().unwrap().len(), 4);
+    assert_eq!(comments[2]["deleted"], true);
+    assert_eq!(comments[2]["body"], "");
+    assert!(comments[2]["author"].is_null());
+    // ... and is not counted as a reply, ...
+    assert_eq!(comments[1]["repliesCount"], 0);
+    assert_eq!(comments[2]["repliesCount"], 1);
+    // ... which cannot be replied to or deleted again, ...
+    let resp = ctx
+        .http_client
+        .post(comments_url.clone())
+        .json(&json!({"comment": {"body": "Too late", "parentId": reply_id}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        delete(&replier.token, reply_id).await,
+        StatusCode::NOT_FOUND
+    );
+    // ... and does not count towards the article's comments
+    let ncomments: i32 = sqlx::query_scalar("SELECT comments_count FROM articles WHERE slug = $1")
+        .bind(&slugs[0])
+        .fetch_one(&ctx.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(ncomments, 3);
+
+    // once the last reply is gone, so is the placeholder
+    let answer_id = answer["id"].as_str().unwrap();
+    assert_eq!(
+        delete(&author.token, answer_id).await,
+        StatusCode::NO_CONTENT
+    );
+    let comments = list(&[]).await;
+    let ids: Vec<_> = comments
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| &c["id"])
+        .collect();
+    assert_eq!(ids, [&latest["id"], &root["id"]]);
+    assert_eq!(comments[1]["repliesCount"], 0);
+}
+
+async fn edit_comments(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let stranger = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &author.token, 1, None).await;
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}/comments", &slugs[0]))
+                .unwrap(),
+        )
+        .json(&json!({"comment": {"body": "There is a tpyo!"}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["comment"]["edited"], false);
+    let comment_id = payload["comment"]["id"].as_str().unwrap().to_owned();
+    let comment_url = ctx
+        .backend_url
+        .join(&format!(
+            "/api/articles/{}/comments/{comment_id}",
+            &slugs[0]
+        ))
+        .unwrap();
+
+    // only the author can edit the comment
+    let resp = ctx
+        .http_client
+        .put(comment_url.clone())
+        .json(&json!({"comment": {"body": "Hijacked!"}}))
+        .bearer_auth(&stranger.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // happy path
+    let resp = ctx
+        .http_client
+        .put(comment_url.clone())
+        .json(&json!({"comment": {"body": "There is a typo!"}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["comment"]["body"], "There is a typo!");
+    assert_eq!(payload["comment"]["edited"], true);
+    assert!(payload["comment"]["updatedAt"].as_str() > payload["comment"]["createdAt"].as_str());
+
+    // the prior text is kept in history
+    let history: Vec<String> =
+        sqlx::query_scalar("SELECT body FROM comment_edits WHERE comment_id = $1::UUID")
+            .bind(&comment_id)
+            .fetch_all(&ctx.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(history, ["There is a tpyo!"]);
+
+    // the comment is listed as edited
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}/comments", &slugs[0]))
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["comments"][0]["body"], "There is a typo!");
+    assert_eq!(payload["comments"][0]["edited"], true);
+
+    // let's pretend the comment was left a while ago (NB: creation
+    // timestamps are guarded against modifications)
+    for stmt in [
+        "ALTER TABLE comments DISABLE TRIGGER guard_creation_mutation_timestamps",
+        "UPDATE comments SET created_at = NOW() - INTERVAL '1 hour'",
+        "ALTER TABLE comments ENABLE TRIGGER guard_creation_mutation_timestamps",
+    ] {
+        sqlx::query(stmt).execute(&ctx.db_pool).await.unwrap();
+    }
+    let resp = ctx
+        .http_client
+        .put(comment_url)
+        .json(&json!({"comment": {"body": "There is no typo!"}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+async fn paginate_and_sort_comments(ctx: TestContext) {
+    let user = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &user.token, 1, None).await;
+    let comments_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{}/comments", &slugs[0]))
+        .unwrap();
+    let mut ids = Vec::new();
+    for n in 0..5 {
+        let resp = ctx
+            .http_client
+            .post(comments_url.clone())
+            .json(&json!({"comment": {"body": format!("Comment #{n}")}}))
+            .bearer_auth(&user.token)
+            .send()
+            .await
+            .unwrap();
+        let payload: Value = resp.json().await.unwrap();
+        ids.push(payload["comment"]["id"].as_str().unwrap().to_owned());
+    }
+    // the third comment gets upvoted, while the first one gets reactions
+    // which weigh less, and gets a reply (which counts towards the total)
+    let react = |comment_id: &str, reaction: &str| {
+        let url = format!(
+            "/api/articles/{}/comments/{comment_id}/reactions/{reaction}",
+            &slugs[0]
+        );
+        ctx.http_client
+            .post(ctx.backend_url.join(&url).unwrap())
+            .bearer_auth(&user.token)
+            .send()
+    };
+    for (comment_id, reaction) in [(&ids[2], "upvote"), (&ids[0], "eyes")] {
+        assert_eq!(
+            react(comment_id, reaction).await.unwrap().status(),
+            StatusCode::OK
+        );
+    }
+    let resp = ctx
+        .http_client
+        .post(comments_url.clone())
+        .json(&json!({"comment": {"body": "Reply", "parentId": &ids[0]}}))
+        .bearer_auth(&user.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // let's walk through all the pages
+    let walk = |sort: &'static str| {
+        let (ctx, comments_url) = (&ctx, &comments_url);
+        async move {
+            let mut seen = Vec::new();
+            let mut cursor: Option<String> = None;
+            loop {
+                let mut params = vec![("sort", sort.to_owned()), ("limit", "2".to_owned())];
+                if let Some(cursor) = cursor.take() {
+                    params.push(("cursor", cursor));
+                }
+                let resp = ctx
+                    .http_client
+                    .get(comments_url.clone())
+                    .query(&params)
+                    .query(&[("maxDepth", "0")])
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(resp.status(), StatusCode::OK);
+                let payload: Value = resp.json().await.unwrap();
+                assert_eq!(payload["commentsCount"], 6);
+                let page = payload["comments"].as_array().unwrap();
+                assert!(page.len() <= 2);
+                seen.extend(page.iter().map(|c| c["id"].as_str().unwrap().to_owned()));
+                match payload["nextCursor"].as_str() {
+                    Some(next) => cursor = Some(next.to_owned()),
+                    None => break seen,
+                }
+            }
+        }
+    };
+    let pick = |order: &[usize]| order.iter().map(|&i| ids[i].clone()).collect::<Vec<_>>();
+    assert_eq!(walk("newest").await, pick(&[4, 3, 2, 1, 0]));
+    assert_eq!(walk("oldest").await, pick(&[0, 1, 2, 3, 4]));
+    assert_eq!(walk("top").await, pick(&[2, 0, 4, 3, 1]));
+
+    // cursors are opaque, but they are still validated
+    let resp = ctx
+        .http_client
+        .get(comments_url)
+        .query(&[("cursor", "garbage")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+async fn react_to_comments(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let reader = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &author.token, 1, None).await;
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}/comments", &slugs[0]))
+                .unwrap(),
+        )
+        .json(&json!({"comment": {"body": "Upvote if you agree"}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["comment"]["reactions"], json!({}));
+    assert_eq!(payload["comment"]["myReactions"], json!([]));
+    let comment_id = payload["comment"]["id"].as_str().unwrap().to_owned();
+    let reaction_url = |reaction: &str| {
+        ctx.backend_url
+            .join(&format!(
+                "/api/articles/{}/comments/{comment_id}/reactions/{reaction}",
+                &slugs[0]
+            ))
+            .unwrap()
+    };
+
+    // reactions are idempotent, ...
+    for (user, reaction) in [
+        (&reader, "upvote"),
+        (&reader, "upvote"),
+        (&reader, "rocket"),
+        (&author, "upvote"),
+    ] {
+        let resp = ctx
+            .http_client
+            .post(reaction_url(reaction))
+            .bearer_auth(&user.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    // ... and only known reactions can be given
+    let resp = ctx
+        .http_client
+        .post(reaction_url("thumbsdown"))
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // reactions are counted, and each user sees their own reactions
+    let list = |token: &str| {
+        let req = ctx
+            .http_client
+            .get(
+                ctx.backend_url
+                    .join(&format!("/api/articles/{}/comments", &slugs[0]))
+                    .unwrap(),
+            )
+            .bearer_auth(token);
+        async move {
+            let mut payload: Value = req.send().await.unwrap().json().await.unwrap();
+            payload["comments"][0].take()
+        }
+    };
+    let comment = list(&reader.token).await;
+    assert_eq!(comment["reactions"], json!({"upvote": 2, "rocket": 1}));
+    assert_eq!(comment["myReactions"], json!(["rocket", "upvote"]));
+    let comment = list(&author.token).await;
+    assert_eq!(comment["myReactions"], json!(["upvote"]));
+
+    // reactions can be revoked
+    let resp = ctx
+        .http_client
+        .delete(reaction_url("upvote"))
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(
+        payload["comment"]["reactions"],
+        json!({"upvote": 1, "rocket": 1})
+    );
+    assert_eq!(payload["comment"]["myReactions"], json!(["rocket"]));
+}
+
+async fn moderate_comments(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let commenter = fake::create_activated_user(&ctx).await;
+    let reader = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &author.token, 1, None).await;
+    let comments_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{}/comments", &slugs[0]))
+        .unwrap();
+    let mut comment_ids = Vec::new();
+    for (i, user) in [&commenter, &reader, &commenter].into_iter().enumerate() {
+        let resp = ctx
+            .http_client
+            .post(comments_url.clone())
+            .json(&json!({"comment": {"body": format!("Comment #{i}")}}))
+            .bearer_auth(&user.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let payload: Value = resp.json().await.unwrap();
+        assert_eq!(payload["comment"]["pinned"], false);
+        assert_eq!(payload["comment"]["hidden"], false);
+        comment_ids.push(payload["comment"]["id"].as_str().unwrap().to_owned());
+    }
+    let moderation_url = |comment_id: &str, action: &str| {
+        ctx.backend_url
+            .join(&format!(
+                "/api/articles/{}/comments/{comment_id}/{action}",
+                &slugs[0]
+            ))
+            .unwrap()
+    };
+    let list = |token: &str| {
+        let req = ctx.http_client.get(comments_url.clone()).bearer_auth(token);
+        async move {
+            let mut payload: Value = req.send().await.unwrap().json().await.unwrap();
+            payload["comments"].take()
+        }
+    };
+
+    // only the article's authors can moderate comments
+    for action in ["pin", "hide"] {
+        let resp = ctx
+            .http_client
+            .post(moderation_url(&comment_ids[0], action))
+            .bearer_auth(&commenter.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    // the pinned comment goes first, even though it is the oldest one
+    let resp = ctx
+        .http_client
+        .post(moderation_url(&comment_ids[0], "pin"))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["comment"]["pinned"], true);
+    let comments = list(&reader.token).await;
+    let ids: Vec<_> = comments
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| &c["id"])
+        .collect();
+    assert_eq!(ids, [&comment_ids[0], &comment_ids[2], &comment_ids[1]]);
+
+    // only one comment can be pinned at a time
+    let resp = ctx
+        .http_client
+        .post(moderation_url(&comment_ids[1], "pin"))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let comments = list(&reader.token).await;
+    assert_eq!(comments[0]["id"], comment_ids[1].as_str());
+    assert_eq!(comments[0]["pinned"], true);
+    assert_eq!(comments[1]["id"], comment_ids[2].as_str());
+    assert_eq!(comments[2]["pinned"], false);
+
+    // the hidden comment's text and author are only revealed to the article's
+    // authors and the comment's author
+    let resp = ctx
+        .http_client
+        .post(moderation_url(&comment_ids[2], "hide"))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["comment"]["hidden"], true);
+    assert_eq!(payload["comment"]["body"], "Comment #2");
+    for (token, revealed) in [
+        (&author.token, true),
+        (&commenter.token, true),
+        (&reader.token, false),
+    ] {
+        let comments = list(token).await;
+        assert_eq!(comments[1]["hidden"], true);
+        assert_eq!(comments[1]["author"].is_object(), revealed);
+        assert_eq!(comments[1]["body"] == "Comment #2", revealed);
+    }
+    let resp = ctx
+        .http_client
+        .delete(moderation_url(&comment_ids[2], "hide"))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let comments = list(&reader.token).await;
+    assert_eq!(comments[1]["hidden"], false);
+    assert_eq!(comments[1]["body"], "Comment #2");
+
+    // the article's authors can delete someone else's comment
+    let resp = ctx
+        .http_client
+        .delete(
+            ctx.backend_url
+                .join(&format!(
+                    "/api/articles/{}/comments/{}",
+                    &slugs[0], &comment_ids[2]
+                ))
+                .unwrap(),
+        )
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    // once the comments are locked, only the article's authors can comment
+    let resp = ctx
+        .http_client
+        .put(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}", &slugs[0]))
+                .unwrap(),
+        )
+        .json(&json!({"article": {"commentsLocked": true}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["article"]["commentsLocked"], true);
+    for (user, status) in [
+        (&reader, StatusCode::FORBIDDEN),
+        (&author, StatusCode::CREATED),
+    ] {
+        let resp = ctx
+            .http_client
+            .post(comments_url.clone())
+            .json(&json!({"comment": {"body": "Closing the discussion"}}))
+            .bearer_auth(&user.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), status);
+    }
 }
 
 mod tests {
     crate::async_test!(create_read_and_delete_comments);
+    crate::async_test!(reply_to_comments_in_threads);
+    crate::async_test!(edit_comments);
+    crate::async_test!(paginate_and_sort_comments);
+    crate::async_test!(react_to_comments);
+    crate::async_test!(moderate_comments);
 }
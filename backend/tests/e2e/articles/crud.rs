@@ -296,6 +296,7 @@ async fn create_article_and_read_it(ctx: TestContext) {
     assert_eq!(article.get("body").unwrap(), body);
     assert_eq!(article.get("tagList").unwrap(), &json!(tags));
     assert_eq!(article.get("favoritesCount").unwrap(), 0);
+    assert_eq!(article.get("commentsCount").unwrap(), 0);
     assert_eq!(article.get("favorited").unwrap(), false);
 
     // ... its author - the current user (reminder: usernames are unique)
@@ -306,6 +307,87 @@ async fn create_article_and_read_it(ctx: TestContext) {
     assert_eq!(author.get("image").unwrap(), &Value::Null);
 }
 
+async fn read_article_rendered_as_html(ctx: TestContext) {
+    let user = fake::create_activated_user(&ctx).await;
+    let reader = fake::create_activated_user(&ctx).await;
+    let body = format!(
+        "## Intro\n\nHello <script>alert(1)</script>world and @{}!\n\n## Outro\n\nBye @nobody",
+        reader.username
+    );
+    let create_response = ctx
+        .http_client
+        .post(ctx.backend_url.join("/api/articles").unwrap())
+        .bearer_auth(&user.token)
+        .json(&json!({ "article": {
+            "title": "Rendering markdown on the server",
+            "description": "Markdown, but safe",
+            "body": body,
+            "tagList": ["markdown"]
+        }}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(create_response.status(), StatusCode::CREATED);
+    let url = ctx
+        .backend_url
+        .join("/api/articles/rendering-markdown-on-the-server")
+        .unwrap();
+
+    // by default, only the raw markdown is returned ...
+    let resp: Value = ctx
+        .http_client
+        .get(url.clone())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(resp["article"]["body"], body);
+    assert!(resp["article"].get("bodyHtml").is_none());
+    assert!(resp["article"].get("toc").is_none());
+
+    // ... and they need to explicitly ask for HTML
+    let response = ctx
+        .http_client
+        .get(url.clone())
+        .query(&[("render", "html")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let resp: Value = response.json().await.unwrap();
+    assert_eq!(resp["article"]["body"], body);
+    let html = resp["article"]["bodyHtml"].as_str().unwrap();
+    assert!(html.contains(r#"id="user-content-intro""#));
+    assert!(!html.contains("<script>"));
+    // mentions are linked to the users' profiles
+    assert!(html.contains(&format!(
+        r#"<a href="/profile/{0}" rel="noopener noreferrer">@{0}</a>"#,
+        reader.username
+    )));
+    // ... but only if there are such users
+    assert!(!html.contains("/profile/nobody"));
+    assert!(html.contains("Bye @nobody"));
+    assert_eq!(
+        resp["article"]["toc"],
+        json!([
+            {"level": 2, "id": "user-content-intro", "title": "Intro"},
+            {"level": 2, "id": "user-content-outro", "title": "Outro"},
+        ])
+    );
+
+    // unsupported formats are rejected
+    let response = ctx
+        .http_client
+        .get(url)
+        .query(&[("render", "pdf")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
 // ---------------------------- DELETE ----------------------------------------
 async fn delete_article(ctx: TestContext) {
     let user1 = fake::create_activated_user(&ctx).await;
@@ -613,6 +695,7 @@ mod tests {
     crate::async_test!(create_article_empty_payload);
     crate::async_test!(create_article_payload_issues);
     crate::async_test!(create_article_and_read_it);
+    crate::async_test!(read_article_rendered_as_html);
     crate::async_test!(delete_article);
     crate::async_test!(favorite_article);
 }
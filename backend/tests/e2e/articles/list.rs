@@ -21,6 +21,35 @@ async fn list_articles_query_issues(ctx: TestContext) {
         assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY, "{msg}");
     }
 
+    let cases = [
+        (&[("sort", "random"), ("limit", "1")], "unknown sort order"),
+        (
+            &[("tagMatch", "some"), ("limit", "1")],
+            "unknown tag matching",
+        ),
+        (
+            &[
+                ("since", "2025-01-01T00:00:00Z"),
+                ("until", "2024-01-01T00:00:00Z"),
+            ],
+            "empty date range",
+        ),
+        (
+            &[("sort", "favorites"), ("cursor", "e30")],
+            "cursor with sort",
+        ),
+    ];
+    for (q, msg) in cases {
+        let resp = ctx
+            .http_client
+            .get(ctx.backend_url.join("/api/articles").unwrap())
+            .query(q)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY, "{msg}");
+    }
+
     // just to pin it here as currently allowed case: they can set limit
     // to `0`, maybe they are only interested in the number of articles
     // matching a specific query, who knows ¯\_(ツ)_/¯
@@ -174,6 +203,383 @@ async fn list_articles_with_limit_and_offset(ctx: TestContext) {
     assert!(first_article_created_at >= second_article_created_at);
 }
 
+async fn list_articles_with_filters_and_sort(ctx: TestContext) {
+    let user1 = fake::create_activated_user(&ctx).await;
+    let user2 = fake::create_activated_user(&ctx).await;
+    let user3 = fake::create_activated_user(&ctx).await;
+    let authors = format!("{},{}", user1.username, user2.username);
+    let a1 = fake::create_article(
+        &ctx,
+        &user1.token,
+        "Filters One",
+        "body",
+        &["f-rust", "f-web"],
+    )
+    .await;
+    let a2 = fake::create_article(&ctx, &user1.token, "Filters Two", "body", &["f-rust"]).await;
+    let a3 = fake::create_article(&ctx, &user2.token, "Filters Three", "body", &["f-web"]).await;
+
+    // multiple authors
+    let (articles, count) = list_articles(&ctx, &[("author", authors.as_str())]).await;
+    assert_eq!(count, 3);
+    assert_eq!(articles[0]["slug"], a3["slug"]); // most recent one first
+
+    // all of the tags (default) vs any of the tags
+    let (articles, count) = list_articles(
+        &ctx,
+        &[("author", authors.as_str()), ("tag", "f-rust,f-web")],
+    )
+    .await;
+    assert_eq!(count, 1);
+    assert_eq!(articles[0]["slug"], a1["slug"]);
+    let (_, count) = list_articles(
+        &ctx,
+        &[
+            ("author", authors.as_str()),
+            ("tag", "f-rust,f-web"),
+            ("tagMatch", "any"),
+        ],
+    )
+    .await;
+    assert_eq!(count, 3);
+
+    // exclusions
+    let (articles, count) = list_articles(
+        &ctx,
+        &[("author", authors.as_str()), ("excludeTag", "f-web")],
+    )
+    .await;
+    assert_eq!(count, 1);
+    assert_eq!(articles[0]["slug"], a2["slug"]);
+    let (_, count) = list_articles(
+        &ctx,
+        &[
+            ("author", authors.as_str()),
+            ("excludeAuthor", user1.username.as_str()),
+        ],
+    )
+    .await;
+    assert_eq!(count, 1);
+
+    // date range
+    let created_at = a2["createdAt"].as_str().unwrap();
+    let (articles, count) =
+        list_articles(&ctx, &[("author", authors.as_str()), ("until", created_at)]).await;
+    assert_eq!(count, 1);
+    assert_eq!(articles[0]["slug"], a1["slug"]);
+    let (_, count) =
+        list_articles(&ctx, &[("author", authors.as_str()), ("since", created_at)]).await;
+    assert_eq!(count, 2);
+
+    // let's make the oldest article the most favorited one ...
+    for (user, slug) in [(&user2, &a1), (&user3, &a1), (&user3, &a2)] {
+        let url = format!("/api/articles/{}/favorite", slug["slug"].as_str().unwrap());
+        let resp = ctx
+            .http_client
+            .post(ctx.backend_url.join(&url).unwrap())
+            .bearer_auth(&user.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    // ... and check the ordering
+    let page = list_page(&ctx, &[("author", authors.as_str()), ("sort", "favorites")]).await;
+    assert_eq!(
+        slugs_of(&page),
+        [a1["slug"].clone(), a2["slug"].clone(), a3["slug"].clone()]
+    );
+    // no cursors for orders other than `recent`
+    assert!(page["nextCursor"].is_null());
+    assert!(page["prevCursor"].is_null());
+}
+
+async fn list_articles_with_cursor(ctx: TestContext) {
+    let user = fake::create_activated_user(&ctx).await;
+    let author = user.username.as_str();
+    fake::gen_articles(&ctx.backend_url, &user.token, 5, None).await;
+
+    // garbage is rejected
+    let url = ctx.backend_url.join("/api/articles").unwrap();
+    let resp = ctx
+        .http_client
+        .get(url.clone())
+        .query(&[("cursor", "garbage")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (expected, _) = list_articles(&ctx, &[("author", author)]).await;
+    let expected: Vec<_> = expected.iter().map(|a| a["slug"].clone()).collect();
+    assert_eq!(expected.len(), 5);
+
+    // walk all the pages forward ...
+    let page = list_page(&ctx, &[("author", author), ("limit", "2")]).await;
+    assert_eq!(page["articlesCount"], 5);
+    assert!(page["prevCursor"].is_null());
+    let mut slugs = slugs_of(&page);
+    let mut pages = vec![page];
+    while let Some(cursor) = pages.last().unwrap()["nextCursor"].as_str() {
+        let page = list_page(
+            &ctx,
+            &[("author", author), ("limit", "2"), ("cursor", cursor)],
+        )
+        .await;
+        assert_eq!(page["articlesCount"], 5);
+        assert!(page["prevCursor"].is_string());
+        slugs.extend(slugs_of(&page));
+        pages.push(page);
+    }
+    // ... and make sure nothing has been skipped or returned twice
+    assert_eq!(pages.len(), 3);
+    assert_eq!(slugs, expected);
+
+    // a valid cursor cannot be combined with offset either
+    let cursor = pages[0]["nextCursor"].as_str().unwrap();
+    let resp = ctx
+        .http_client
+        .get(url.clone())
+        .query(&[("author", author), ("cursor", cursor), ("offset", "1")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let errors = resp.json::<Value>().await.unwrap();
+    assert!(errors["errors"]["offset"].is_array());
+
+    // new article is not shifting the pages when navigating with a cursor
+    fake::gen_articles(&ctx.backend_url, &user.token, 1, None).await;
+    let cursor = pages[0]["nextCursor"].as_str().unwrap();
+    let page = list_page(
+        &ctx,
+        &[("author", author), ("limit", "2"), ("cursor", cursor)],
+    )
+    .await;
+    assert_eq!(slugs_of(&page), slugs_of(&pages[1]));
+
+    // now let's go back from the last page
+    let cursor = pages[2]["prevCursor"].as_str().unwrap();
+    let page = list_page(
+        &ctx,
+        &[("author", author), ("limit", "2"), ("cursor", cursor)],
+    )
+    .await;
+    assert_eq!(slugs_of(&page), slugs_of(&pages[1]));
+    let cursor = page["prevCursor"].as_str().unwrap();
+    let page = list_page(
+        &ctx,
+        &[("author", author), ("limit", "2"), ("cursor", cursor)],
+    )
+    .await;
+    assert_eq!(slugs_of(&page), slugs_of(&pages[0]));
+    // the article we've just published precedes this page
+    assert!(page["prevCursor"].is_string());
+    assert!(page["nextCursor"].is_string());
+}
+
+async fn list_articles_with_following_flag(ctx: TestContext) {
+    let followed = fake::create_activated_user(&ctx).await;
+    let other = fake::create_activated_user(&ctx).await;
+    let reader = fake::create_activated_user(&ctx).await;
+    let tag = format!("following-{}", &reader.username);
+    fake::create_article(&ctx, &followed.token, "Followed", "body", &[&tag]).await;
+    fake::create_article(&ctx, &other.token, "Not followed", "body", &[&tag]).await;
+
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/profiles/{}/follow", followed.username))
+                .unwrap(),
+        )
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let list = |token: Option<&str>| {
+        let mut req = ctx
+            .http_client
+            .get(ctx.backend_url.join("/api/articles").unwrap())
+            .query(&[("tag", tag.as_str())]);
+        if let Some(token) = token {
+            req = req.bearer_auth(token);
+        }
+        async move {
+            let resp = req.send().await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let payload: Value = resp.json().await.unwrap();
+            let mut following: Vec<_> = payload["articles"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|a| {
+                    (
+                        a["author"]["username"].as_str().unwrap().to_owned(),
+                        a["author"]["following"].as_bool().unwrap(),
+                    )
+                })
+                .collect();
+            following.sort();
+            following
+        }
+    };
+
+    // the reader is following one of the authors ...
+    let mut expected = vec![
+        (followed.username.clone(), true),
+        (other.username.clone(), false),
+    ];
+    expected.sort();
+    assert_eq!(list(Some(&reader.token)).await, expected);
+
+    // ... while for anonymous users it's always `false`
+    let mut expected = vec![
+        (followed.username.clone(), false),
+        (other.username.clone(), false),
+    ];
+    expected.sort();
+    assert_eq!(list(None).await, expected);
+}
+
+async fn trending_articles(ctx: TestContext) {
+    let user = fake::create_activated_user(&ctx).await;
+    let tag = format!("trending-{}", &user.username);
+    let a1 = fake::create_article(&ctx, &user.token, "Trending One", "body", &[&tag]).await;
+    let a2 = fake::create_article(&ctx, &user.token, "Trending Two", "body", &[&tag]).await;
+    fake::create_article(&ctx, &user.token, "Trending Three", "body", &[&tag]).await;
+
+    // scores are computed by a background workflow, and so we are
+    // talking to db directly here to simulate a refresh
+    sqlx::query(
+        r#"
+        INSERT INTO trending_articles (period, article_id, score)
+        SELECT period, article_id, score
+        FROM (VALUES ('day', $1, 1.0), ('day', $2, 5.0), ('week', $1, 10.0)) t (period, slug, score)
+            JOIN articles USING (slug)
+        "#,
+    )
+    .bind(a1["slug"].as_str().unwrap())
+    .bind(a2["slug"].as_str().unwrap())
+    .execute(&ctx.db_pool)
+    .await
+    .unwrap();
+
+    let url = ctx.backend_url.join("/api/articles/trending").unwrap();
+    let trending = |window: &'static str| {
+        let (url, tag) = (url.clone(), tag.clone());
+        let client = ctx.http_client.clone();
+        async move {
+            let resp = client
+                .get(url)
+                .query(&[("window", window), ("tag", tag.as_str())])
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            resp.json::<Value>().await.unwrap()
+        }
+    };
+
+    let page = trending("day").await;
+    assert_eq!(page["articlesCount"], 2);
+    assert_eq!(slugs_of(&page), [a2["slug"].clone(), a1["slug"].clone()]);
+    let page = trending("week").await;
+    assert_eq!(page["articlesCount"], 1);
+    assert_eq!(slugs_of(&page), [a1["slug"].clone()]);
+    let page = trending("month").await;
+    assert_eq!(page["articlesCount"], 0);
+
+    // unknown window
+    let resp = ctx
+        .http_client
+        .get(url)
+        .query(&[("window", "year")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+async fn search_articles(ctx: TestContext) {
+    let user1 = fake::create_activated_user(&ctx).await;
+    let user2 = fake::create_activated_user(&ctx).await;
+    fake::create_article(
+        &ctx,
+        &user1.token,
+        "Memory safety without garbage collection",
+        "Ownership and borrowing is how Rust achieves it.",
+        &["rust"],
+    )
+    .await;
+    fake::create_article(
+        &ctx,
+        &user2.token,
+        "Tuning the Go runtime",
+        "Garbage collector pauses and memory footprint, and also safety.",
+        &["go"],
+    )
+    .await;
+    fake::create_article(
+        &ctx,
+        &user2.token,
+        "Async in practice",
+        "Futures, executors, and memory <script>alert(1)</script> safety.",
+        &["rust", "async"],
+    )
+    .await;
+
+    // search query is required
+    let url = ctx.backend_url.join("/api/articles/search").unwrap();
+    let resp = ctx.http_client.get(url.clone()).send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // all three articles mention memory safety, but the one having these
+    // words in its title is the most relevant one
+    let (articles, count) = search(&ctx, &[("q", "memory safety")]).await;
+    assert_eq!(count, 3);
+    assert_eq!(
+        articles[0]["slug"],
+        "memory-safety-without-garbage-collection"
+    );
+    for article in &articles {
+        assert!(article["snippet"].as_str().unwrap().contains("<mark>"));
+    }
+
+    // excluding words and combining search with filters
+    let (articles, count) = search(&ctx, &[("q", "memory safety -garbage")]).await;
+    assert_eq!(count, 1);
+    assert_eq!(articles[0]["slug"], "async-in-practice");
+    // NB: the markup in the article's body is escaped
+    assert!(
+        !articles[0]["snippet"]
+            .as_str()
+            .unwrap()
+            .contains("<script>")
+    );
+    let (articles, count) = search(
+        &ctx,
+        &[("q", "memory"), ("author", user2.username.as_str())],
+    )
+    .await;
+    assert_eq!(count, 2);
+    assert!(
+        articles
+            .iter()
+            .all(|a| a["author"]["username"] == user2.username)
+    );
+    let (articles, count) = search(&ctx, &[("q", "memory"), ("tag", "rust"), ("limit", "1")]).await;
+    assert_eq!(count, 2);
+    assert_eq!(articles.len(), 1);
+
+    // nothing found
+    let (articles, count) = search(&ctx, &[("q", "kubernetes")]).await;
+    assert_eq!(count, 0);
+    assert!(articles.is_empty());
+}
+
 /// List articles given the query.
 ///
 /// A local helper, that will send a list articles request with the given query
@@ -207,7 +613,58 @@ where
     (articles, count)
 }
 
+/// List articles given the query and return the entire payload.
+async fn list_page<T>(ctx: &TestContext, query: &T) -> Value
+where
+    T: Serialize + ?Sized,
+{
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/articles").unwrap())
+        .query(query)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    resp.json().await.unwrap()
+}
+
+fn slugs_of(page: &Value) -> Vec<Value> {
+    page["articles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["slug"].clone())
+        .collect()
+}
+
+/// Search articles given the query.
+///
+/// Same as `list_articles` helper, but for the search endpoint.
+async fn search<T>(ctx: &TestContext, query: &T) -> (Vec<Value>, u64)
+where
+    T: Serialize + ?Sized,
+{
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/articles/search").unwrap())
+        .query(query)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    let count = payload["articlesCount"].as_u64().unwrap();
+    let articles = payload["articles"].as_array().unwrap().to_owned();
+    (articles, count)
+}
+
 mod tests {
     crate::async_test!(list_articles_query_issues);
     crate::async_test!(list_articles_with_limit_and_offset);
+    crate::async_test!(list_articles_with_cursor);
+    crate::async_test!(list_articles_with_filters_and_sort);
+    crate::async_test!(list_articles_with_following_flag);
+    crate::async_test!(search_articles);
+    crate::async_test!(trending_articles);
 }
@@ -0,0 +1,146 @@
+use crate::utils::{TestContext, fake};
+use futures_util::StreamExt as _;
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest as _;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn join(ctx: &TestContext, slug: &str, token: &str) -> Result<Socket, tungstenite::Error> {
+    connect(ctx, slug, "Authorization", format!("Token {token}")).await
+}
+
+/// Join the way browsers do, i.e. with the token offered as a subprotocol.
+async fn join_from_browser(
+    ctx: &TestContext,
+    slug: &str,
+    token: &str,
+) -> Result<Socket, tungstenite::Error> {
+    let protocols = format!("access_token, {token}");
+    connect(ctx, slug, "Sec-WebSocket-Protocol", protocols).await
+}
+
+async fn connect(
+    ctx: &TestContext,
+    slug: &str,
+    header: &'static str,
+    value: String,
+) -> Result<Socket, tungstenite::Error> {
+    let mut url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{slug}/live"))
+        .unwrap();
+    url.set_scheme("ws").unwrap();
+    let mut req = url.as_str().into_client_request().unwrap();
+    req.headers_mut().insert(header, value.parse().unwrap());
+    // NB: the client fails the connection unless the server has accepted
+    // one of the offered subprotocols (if any)
+    tokio_tungstenite::connect_async(req)
+        .await
+        .map(|(socket, _)| socket)
+}
+
+/// Read messages from the socket until the event with the given `name`,
+/// returning its payload.
+async fn next_event(socket: &mut Socket, name: &str) -> Value {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let message = socket.next().await.unwrap().unwrap();
+            let Message::Text(text) = message else {
+                continue;
+            };
+            let event: Value = serde_json::from_str(&text).unwrap();
+            if event["name"] == name {
+                return event["data"].clone();
+            }
+        }
+    })
+    .await
+    .expect("event to have been received")
+}
+
+async fn live_article_discussion(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let reader = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &author.token, 1, None).await;
+
+    // ---------- only authenticated users can join existing articles ----------
+    let err = join(&ctx, &slugs[0], "invalid-token").await.unwrap_err();
+    let tungstenite::Error::Http(resp) = err else {
+        panic!("expected HTTP error, got: {err:?}");
+    };
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let err = join_from_browser(&ctx, &slugs[0], "invalid-token")
+        .await
+        .unwrap_err();
+    let tungstenite::Error::Http(resp) = err else {
+        panic!("expected HTTP error, got: {err:?}");
+    };
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let err = join(&ctx, "no-such-article", &reader.token)
+        .await
+        .unwrap_err();
+    let tungstenite::Error::Http(resp) = err else {
+        panic!("expected HTTP error, got: {err:?}");
+    };
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // ---------- users see each other joining ----------
+    let mut author_socket = join(&ctx, &slugs[0], &author.token).await.unwrap();
+    assert_eq!(
+        next_event(&mut author_socket, "presence").await["readers"],
+        1
+    );
+    let mut reader_socket = join(&ctx, &slugs[0], &reader.token).await.unwrap();
+    assert_eq!(
+        next_event(&mut reader_socket, "presence").await["readers"],
+        2
+    );
+    assert_eq!(
+        next_event(&mut author_socket, "presence").await["readers"],
+        2
+    );
+
+    // ---------- the same user in another tab is the same reader ----------
+    let mut reader_tab = join_from_browser(&ctx, &slugs[0], &reader.token)
+        .await
+        .unwrap();
+    assert_eq!(next_event(&mut reader_tab, "presence").await["readers"], 2);
+    reader_tab.close(None).await.unwrap();
+
+    // ---------- new comments are delivered to everyone ----------
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}/comments", &slugs[0]))
+                .unwrap(),
+        )
+        .json(&json!({"comment": {"body": "Anyone here?"}}))
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    for socket in [&mut author_socket, &mut reader_socket] {
+        let data = next_event(socket, "comment").await;
+        assert_eq!(data["article"], slugs[0]);
+        assert_eq!(data["comment"]["body"], "Anyone here?");
+        assert_eq!(data["comment"]["author"]["username"], reader.username);
+    }
+
+    // ---------- users see each other leaving ----------
+    reader_socket.close(None).await.unwrap();
+    assert_eq!(
+        next_event(&mut author_socket, "presence").await["readers"],
+        1
+    );
+}
+
+mod tests {
+    crate::async_test!(live_article_discussion);
+}
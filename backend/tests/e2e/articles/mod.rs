@@ -1,3 +1,10 @@
+mod authors;
+mod bookmarks;
 mod comments;
 mod crud;
 mod list;
+mod live;
+mod related;
+mod series;
+mod stats;
+mod tags;
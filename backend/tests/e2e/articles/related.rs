@@ -0,0 +1,114 @@
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+use crate::utils::{TestContext, fake};
+
+async fn recommend_related_articles(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let reader = fake::create_activated_user(&ctx).await;
+    // tags are shared across tests, hence making them unique
+    let tag = |name: &str| format!("{name}-{}", author.username).to_lowercase();
+    let (shared, common, fresh) = (tag("shared"), tag("common"), tag("fresh"));
+    let title = |title: &str| format!("{title} by {}", author.username);
+    let source = fake::create_article(
+        &ctx,
+        &author.token,
+        &title("Origin"),
+        "body",
+        &[&shared, &common],
+    )
+    .await;
+    let source = source["slug"].as_str().unwrap();
+    // sharing both of the tags ...
+    let closest = fake::create_article(
+        &ctx,
+        &author.token,
+        &title("Closest"),
+        "body",
+        &[&shared, &common],
+    )
+    .await;
+    // ... and sharing one of them
+    let close =
+        fake::create_article(&ctx, &author.token, &title("Close"), "body", &[&common]).await;
+
+    let related = |token: Option<&str>| {
+        let mut req = ctx
+            .http_client
+            .get(
+                ctx.backend_url
+                    .join(&format!("/api/articles/{source}/related"))
+                    .unwrap(),
+            )
+            .query(&[("limit", "2")]);
+        if let Some(token) = token {
+            req = req.bearer_auth(token);
+        }
+        async move {
+            let resp = req.send().await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let payload: Value = resp.json().await.unwrap();
+            payload["articles"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|a| a["slug"].as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        }
+    };
+    let slug = |article: &Value| article["slug"].as_str().unwrap().to_owned();
+    assert_eq!(related(None).await, [slug(&closest), slug(&close)]);
+
+    // the reader's own articles are never recommended, ...
+    assert!(related(Some(&author.token)).await.is_empty());
+    // ... and neither are the ones they already favorited
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}/favorite", slug(&closest)))
+                .unwrap(),
+        )
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(related(Some(&reader.token)).await[0], slug(&close));
+
+    // recommendations are cached, ...
+    let freshest =
+        fake::create_article(&ctx, &author.token, &title("Freshest"), "body", &[&fresh]).await;
+    assert_eq!(related(None).await, [slug(&closest), slug(&close)]);
+    // ... until the article gets updated
+    let resp = ctx
+        .http_client
+        .put(
+            ctx.backend_url
+                .join(&format!("/api/articles/{source}"))
+                .unwrap(),
+        )
+        .bearer_auth(&author.token)
+        .json(&json!({"article": {"tagList": [fresh]}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(related(None).await[0], slug(&freshest));
+
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join("/api/articles/no-such-article-ever/related")
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+mod tests {
+    crate::async_test!(recommend_related_articles);
+}
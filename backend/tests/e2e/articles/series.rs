@@ -0,0 +1,175 @@
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+use crate::utils::{TestContext, fake};
+
+async fn create_read_update_and_delete_series(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let stranger = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &author.token, 3, None).await;
+    let foreign = fake::gen_articles(&ctx.backend_url, &stranger.token, 1, None).await;
+    let series_url = ctx.backend_url.join("/api/series").unwrap();
+
+    // series can only be created by authenticated users ...
+    let resp = ctx
+        .http_client
+        .post(series_url.clone())
+        .json(&json!({"series": {"title": "Dragons", "articles": slugs}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    // ... and only include their own existing articles, ...
+    for articles in [
+        json!([slugs[0], foreign[0]]),
+        json!([slugs[0], "whatever"]),
+        json!([slugs[0], slugs[0]]),
+    ] {
+        let resp = ctx
+            .http_client
+            .post(series_url.clone())
+            .json(&json!({"series": {"title": "Dragons", "articles": articles}}))
+            .bearer_auth(&author.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let errors: Value = resp.json().await.unwrap();
+        assert!(errors["errors"]["articles"].is_array());
+    }
+
+    // happy path
+    let resp = ctx
+        .http_client
+        .post(series_url.clone())
+        .json(&json!({
+            "series": {
+                "title": "Dragons",
+                "description": "Everything about dragons",
+                "articles": [slugs[2], slugs[0]]
+            }
+        }))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let payload: Value = resp.json().await.unwrap();
+    let series = &payload["series"];
+    assert_eq!(series["slug"], "dragons");
+    assert_eq!(series["description"], "Everything about dragons");
+    assert_eq!(series["author"]["username"], author.username);
+    assert_eq!(series["articles"][0]["slug"], slugs[2].as_str());
+    assert_eq!(series["articles"][1]["slug"], slugs[0].as_str());
+
+    // an article can be part of one series at most
+    let resp = ctx
+        .http_client
+        .post(series_url.clone())
+        .json(&json!({"series": {"title": "More dragons", "articles": [slugs[0]]}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // articles now know their place in the series
+    let article = read_article(&ctx, &slugs[2]).await;
+    assert_eq!(article["series"]["slug"], "dragons");
+    assert_eq!(article["series"]["position"], 1);
+    assert_eq!(article["series"]["articlesCount"], 2);
+    assert!(article["series"]["previous"].is_null());
+    assert_eq!(article["series"]["next"]["slug"], slugs[0].as_str());
+    let article = read_article(&ctx, &slugs[0]).await;
+    assert_eq!(article["series"]["position"], 2);
+    assert_eq!(article["series"]["previous"]["slug"], slugs[2].as_str());
+    assert!(article["series"]["next"].is_null());
+    let article = read_article(&ctx, &slugs[1]).await;
+    assert!(article.get("series").is_none());
+
+    // and can be filtered by series
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/articles").unwrap())
+        .query(&[("series", "dragons")])
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["articlesCount"], 2);
+
+    // only the author can update the series ...
+    let dragons_url = ctx.backend_url.join("/api/series/dragons").unwrap();
+    let resp = ctx
+        .http_client
+        .put(dragons_url.clone())
+        .json(&json!({"series": {"title": "Wyverns"}}))
+        .bearer_auth(&stranger.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    // ... e.g. rename it and reorder the articles
+    let resp = ctx
+        .http_client
+        .put(dragons_url.clone())
+        .json(&json!({"series": {"title": "Wyverns", "articles": [slugs[0], slugs[1], slugs[2]]}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["series"]["slug"], "wyverns");
+    assert_eq!(payload["series"]["description"], "Everything about dragons");
+    assert_eq!(payload["series"]["articles"].as_array().unwrap().len(), 3);
+    let article = read_article(&ctx, &slugs[1]).await;
+    assert_eq!(article["series"]["position"], 2);
+
+    // the old slug is gone
+    let resp = ctx.http_client.get(dragons_url).send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // deleting the series keeps the articles
+    let wyverns_url = ctx.backend_url.join("/api/series/wyverns").unwrap();
+    let resp = ctx
+        .http_client
+        .delete(wyverns_url.clone())
+        .bearer_auth(&stranger.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = ctx
+        .http_client
+        .delete(wyverns_url.clone())
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let resp = ctx.http_client.get(wyverns_url).send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let article = read_article(&ctx, &slugs[1]).await;
+    assert!(article.get("series").is_none());
+}
+
+async fn read_article(ctx: &TestContext, slug: &str) -> Value {
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join(&format!("/api/articles/{slug}"))
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let mut payload: Value = resp.json().await.unwrap();
+    payload["article"].take()
+}
+
+mod tests {
+    crate::async_test!(create_read_update_and_delete_series);
+}
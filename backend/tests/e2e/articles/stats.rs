@@ -0,0 +1,239 @@
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+use crate::utils::{TestContext, fake};
+
+async fn article_stats_and_author_dashboard(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let reader = fake::create_activated_user(&ctx).await;
+    let article =
+        fake::create_article(&ctx, &author.token, "Popular one", "body", &["stats"]).await;
+    let slug = article["slug"].as_str().unwrap();
+    let stats_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{slug}/stats"))
+        .unwrap();
+
+    // stats are only available to the author ...
+    let resp = ctx.http_client.get(stats_url.clone()).send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = ctx
+        .http_client
+        .get(stats_url.clone())
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    // ... of an existing article
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join("/api/articles/whatever/stats")
+                .unwrap(),
+        )
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // the reader opens the article twice (which is counted once) ...
+    let article_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{slug}"))
+        .unwrap();
+    for _ in 0..2 {
+        let resp = ctx
+            .http_client
+            .get(article_url.clone())
+            .bearer_auth(&reader.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    // ... as does an anonymous visitor, ...
+    let resp = ctx
+        .http_client
+        .get(article_url.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    // ... while the author's own views are not counted
+    let resp = ctx
+        .http_client
+        .get(article_url)
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // the reader makes it to the end of the article, ...
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{slug}/read"))
+                .unwrap(),
+        )
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    // ... likes it, ...
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{slug}/favorite"))
+                .unwrap(),
+        )
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    // ... and leaves a comment
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{slug}/comments"))
+                .unwrap(),
+        )
+        .bearer_auth(&reader.token)
+        .json(&json!({"comment": {"body": "Thank you!"}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // views are flushed to the database in background, and so we
+    // might need to wait a bit for them to show up
+    let mut stats = Value::Null;
+    for _ in 0..20 {
+        let resp = ctx
+            .http_client
+            .get(stats_url.clone())
+            .query(&[("days", 7)])
+            .bearer_auth(&author.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        stats = resp.json::<Value>().await.unwrap()["stats"].take();
+        if stats["views"] == 2 && stats["reads"] == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    assert_eq!(stats["views"], 2);
+    assert_eq!(stats["reads"], 1);
+    assert_eq!(stats["favoritesCount"], 1);
+    assert_eq!(stats["commentsCount"], 1);
+    let daily = stats["daily"].as_array().unwrap();
+    assert_eq!(daily.len(), 7);
+    let today = &daily[6];
+    assert_eq!(today["views"], 2);
+    assert_eq!(today["reads"], 1);
+    assert_eq!(today["favorites"], 1);
+    assert_eq!(today["comments"], 1);
+    assert!(daily[..6].iter().all(|day| day["views"] == 0));
+
+    // accepted co-authors can see the stats too
+    let editor = fake::create_activated_user(&ctx).await;
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{slug}/authors"))
+                .unwrap(),
+        )
+        .json(&json!({"invitation": {"username": editor.username}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let resp = ctx
+        .http_client
+        .get(stats_url.clone())
+        .bearer_auth(&editor.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{slug}/authors/accept"))
+                .unwrap(),
+        )
+        .bearer_auth(&editor.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = ctx
+        .http_client
+        .get(stats_url.clone())
+        .bearer_auth(&editor.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // number of days is limited
+    let resp = ctx
+        .http_client
+        .get(stats_url)
+        .query(&[("days", 366)])
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // the author's dashboard is reflecting the same
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/user/stats").unwrap())
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let stats = resp.json::<Value>().await.unwrap()["stats"].take();
+    assert_eq!(stats["views"], 2);
+    assert_eq!(stats["favoritesCount"], 1);
+    assert_eq!(stats["daily"].as_array().unwrap().len(), 30); // default
+    let articles = stats["articles"].as_array().unwrap();
+    assert_eq!(articles.len(), 1);
+    assert_eq!(articles[0]["slug"], slug);
+    assert_eq!(articles[0]["reads"], 1);
+    assert_eq!(articles[0]["commentsCount"], 1);
+
+    // while the reader has not authored anything yet
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/user/stats").unwrap())
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let stats = resp.json::<Value>().await.unwrap()["stats"].take();
+    assert_eq!(stats["views"], 0);
+    assert!(stats["articles"].as_array().unwrap().is_empty());
+}
+
+mod tests {
+    crate::async_test!(article_stats_and_author_dashboard);
+}
@@ -0,0 +1,375 @@
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+use crate::utils::{TestContext, fake};
+
+async fn normalize_tags_and_manage_aliases(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let admin = fake::create_activated_user(&ctx).await;
+    sqlx::query("UPDATE users SET is_admin = TRUE WHERE username = $1")
+        .bind(&admin.username)
+        .execute(&ctx.db_pool)
+        .await
+        .unwrap();
+    // tags are shared across tests, hence making them unique
+    let tag = format!("tag-{}", author.username).to_lowercase();
+    let alias = format!("alias-{}", author.username).to_lowercase();
+
+    // tags are trimmed, case-folded, and deduplicated
+    let messy = format!("  {}  ", tag.to_uppercase());
+    let article = fake::create_article(
+        &ctx,
+        &author.token,
+        &format!("Normalized tags by {}", author.username),
+        "body",
+        &[&messy, &tag, &alias],
+    )
+    .await;
+    assert_eq!(article["tagList"], json!([alias, tag]));
+    let slug = article["slug"].as_str().unwrap();
+
+    // ... and too long ones, or those which could not be filtered on, are rejected
+    for bad_tag in ["x".repeat(51), "rust, go".to_owned()] {
+        let resp = ctx
+            .http_client
+            .post(ctx.backend_url.join("/api/articles").unwrap())
+            .bearer_auth(&author.token)
+            .json(&json!({ "article": {
+                "title": format!("Bad tags by {}", author.username),
+                "description": "description",
+                "body": "body",
+                "tagList": [bad_tag],
+            }}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    // filters get normalized as well
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/articles").unwrap())
+        .query(&[("tag", messy.as_str())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["articlesCount"], 1);
+
+    // only admins can manage tags
+    let aliases_url = ctx
+        .backend_url
+        .join(&format!("/api/tags/{tag}/aliases"))
+        .unwrap();
+    let resp = ctx
+        .http_client
+        .post(aliases_url.clone())
+        .bearer_auth(&author.token)
+        .json(&json!({"alias": {"name": alias}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // adding an alias which is a tag in use merges the two tags
+    let resp = ctx
+        .http_client
+        .post(aliases_url.clone())
+        .bearer_auth(&admin.token)
+        .json(&json!({"alias": {"name": alias}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["tag"]["name"], tag.as_str());
+    assert_eq!(payload["tag"]["aliases"], json!([alias]));
+    assert_eq!(payload["tag"]["articlesCount"], 1);
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join(&format!("/api/articles/{slug}"))
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["article"]["tagList"], json!([tag]));
+
+    // from now on, the alias is replaced with the tag
+    let article = fake::create_article(
+        &ctx,
+        &author.token,
+        &format!("Aliased tags by {}", author.username),
+        "body",
+        &[&alias],
+    )
+    .await;
+    assert_eq!(article["tagList"], json!([tag]));
+
+    // tags can be described ...
+    let resp = ctx
+        .http_client
+        .put(ctx.backend_url.join(&format!("/api/tags/{tag}")).unwrap())
+        .bearer_auth(&admin.token)
+        .json(&json!({"tag": {"description": "Tag for testing"}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // ... and the metadata is listed alongside the tags
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/tags/").unwrap())
+        .query(&[("limit", "1000")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    let details = payload["details"].as_array().unwrap();
+    let position = details.iter().position(|t| t["name"] == tag.as_str());
+    let details = &details[position.unwrap()];
+    assert_eq!(payload["tags"][position.unwrap()], tag.as_str());
+    assert_eq!(details["description"], "Tag for testing");
+    assert_eq!(details["articlesCount"], 2);
+    assert!(payload["tagsCount"].as_u64().unwrap() >= 1);
+
+    // aliases can be removed
+    let alias_url = ctx
+        .backend_url
+        .join(&format!("/api/tags/{tag}/aliases/{alias}"))
+        .unwrap();
+    let resp = ctx
+        .http_client
+        .delete(alias_url.clone())
+        .bearer_auth(&admin.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let resp = ctx
+        .http_client
+        .delete(alias_url)
+        .bearer_auth(&admin.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+async fn follow_tags_and_read_personal_feed(ctx: TestContext) {
+    let reader = fake::create_activated_user(&ctx).await;
+    let followed = fake::create_activated_user(&ctx).await;
+    let other = fake::create_activated_user(&ctx).await;
+    let tag = format!("feed-{}", reader.username).to_lowercase();
+    // the feed is scoped with this tag, since articles are shared across tests
+    let scope = format!("scope-{}", reader.username).to_lowercase();
+    let title = |title: &str| format!("{title} for {}", reader.username);
+    // by the followed author and having the followed tag ...
+    let both = fake::create_article(
+        &ctx,
+        &followed.token,
+        &title("Both"),
+        "body",
+        &[&tag, &scope],
+    )
+    .await;
+    // ... only by the followed author, ...
+    let by_author =
+        fake::create_article(&ctx, &followed.token, &title("Author"), "body", &[&scope]).await;
+    // ... only having the followed tag, ...
+    let tagged =
+        fake::create_article(&ctx, &other.token, &title("Tag"), "body", &[&tag, &scope]).await;
+    // ... and neither
+    fake::create_article(&ctx, &other.token, &title("Neither"), "body", &[&scope]).await;
+
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/profiles/{}/follow", followed.username))
+                .unwrap(),
+        )
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let follow_url = ctx
+        .backend_url
+        .join(&format!("/api/tags/{}/follow", tag.to_uppercase()))
+        .unwrap();
+    let resp = ctx
+        .http_client
+        .post(follow_url.clone())
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["tag"]["name"], tag.as_str());
+    assert_eq!(payload["tag"]["following"], true);
+
+    // unknown tags cannot be followed
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join("/api/tags/no-such-tag-ever/follow")
+                .unwrap(),
+        )
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // followed tags are part of the current user's details
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/user").unwrap())
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["user"]["followedTags"], json!([tag]));
+
+    let feed = |mode: &'static str| {
+        let req = ctx
+            .http_client
+            .get(ctx.backend_url.join("/api/articles/feed").unwrap())
+            .query(&[("mode", mode), ("tag", scope.as_str())])
+            .bearer_auth(&reader.token);
+        async move {
+            let resp = req.send().await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let payload: Value = resp.json().await.unwrap();
+            let mut slugs: Vec<_> = payload["articles"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|a| a["slug"].as_str().unwrap().to_owned())
+                .collect();
+            slugs.sort();
+            assert_eq!(payload["articlesCount"], slugs.len());
+            slugs
+        }
+    };
+    let slugs = |articles: &[&Value]| {
+        let mut slugs: Vec<_> = articles
+            .iter()
+            .map(|a| a["slug"].as_str().unwrap().to_owned())
+            .collect();
+        slugs.sort();
+        slugs
+    };
+    assert_eq!(feed("authors").await, slugs(&[&both, &by_author]));
+    assert_eq!(feed("tags").await, slugs(&[&both, &tagged]));
+    // articles matching both the author and the tag are not repeated
+    assert_eq!(feed("all").await, slugs(&[&both, &by_author, &tagged]));
+
+    let resp = ctx
+        .http_client
+        .delete(follow_url)
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["tag"]["following"], false);
+    assert_eq!(feed("tags").await, Vec::<String>::new());
+}
+
+async fn autocomplete_and_related_tags(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    // tags are shared across tests, hence making them unique
+    let prefix = format!("ac-{}-", author.username).to_lowercase();
+    let (popular, rare, related) = (
+        format!("{prefix}popular"),
+        format!("{prefix}rare"),
+        format!("{prefix}related"),
+    );
+    for (n, tags) in [
+        vec![&popular, &related],
+        vec![&popular, &related],
+        vec![&popular, &rare],
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let tags: Vec<&str> = tags.into_iter().map(String::as_str).collect();
+        let title = format!("Autocomplete {n} by {}", author.username);
+        fake::create_article(&ctx, &author.token, &title, "body", &tags).await;
+    }
+
+    // tags starting with the prefix, the most popular ones first
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/tags/").unwrap())
+        .query(&[("prefix", prefix.to_uppercase().as_str()), ("limit", "2")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["tags"], json!([popular, related]));
+    assert_eq!(payload["tagsCount"], 3);
+
+    // wildcards are matched literally
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/tags/").unwrap())
+        .query(&[("prefix", "%")])
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(payload["tagsCount"], 0);
+
+    // tags used together with the given one, the most frequent ones first
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join(&format!("/api/tags/{popular}/related"))
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload: Value = resp.json().await.unwrap();
+    assert_eq!(
+        payload["tags"],
+        json!([
+            {"name": related, "articlesCount": 2},
+            {"name": rare, "articlesCount": 1},
+        ])
+    );
+    let resp = ctx
+        .http_client
+        .get(
+            ctx.backend_url
+                .join("/api/tags/no-such-tag-ever/related")
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+mod tests {
+    crate::async_test!(normalize_tags_and_manage_aliases);
+    crate::async_test!(follow_tags_and_read_personal_feed);
+    crate::async_test!(autocomplete_and_related_tags);
+}
@@ -10,4 +10,6 @@ mod articles;
 #[cfg(feature = "api-test")]
 mod health;
 #[cfg(feature = "api-test")]
+mod stream;
+#[cfg(feature = "api-test")]
 mod users;
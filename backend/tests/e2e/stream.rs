@@ -0,0 +1,138 @@
+use crate::utils::{TestContext, fake};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+/// Read events from the stream until the one with the given `name`, returning
+/// its identifier and payload, and skipping heartbeats and other events.
+async fn next_event(resp: &mut reqwest::Response, buf: &mut String, name: &str) -> (String, Value) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            while let Some(end) = buf.find("\n\n") {
+                let message: String = buf.drain(..end + 2).collect();
+                let (mut id, mut event, mut data) = (None, None, None);
+                for line in message.lines() {
+                    let (field, value) = line.split_once(':').unwrap_or((line, ""));
+                    let value = value.strip_prefix(' ').unwrap_or(value);
+                    match field {
+                        "id" => id = Some(value.to_owned()),
+                        "event" => event = Some(value.to_owned()),
+                        "data" => data = Some(serde_json::from_str(value).unwrap()),
+                        _ => {}
+                    }
+                }
+                if event.as_deref() == Some(name) {
+                    return (id.unwrap(), data.unwrap());
+                }
+            }
+            let chunk = resp.chunk().await.unwrap().expect("stream to be open");
+            buf.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+    })
+    .await
+    .expect("event to have been received")
+}
+
+async fn stream_events(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let reader = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &author.token, 1, None).await;
+    let stream_url = ctx.backend_url.join("/api/stream").unwrap();
+    let open = |token: &str, query: &[(&str, &str)], last_event_id: Option<&str>| {
+        let mut req = ctx
+            .http_client
+            .get(stream_url.clone())
+            .query(query)
+            .bearer_auth(token);
+        if let Some(last_event_id) = last_event_id {
+            req = req.header("Last-Event-ID", last_event_id);
+        }
+        req.send()
+    };
+
+    // ---------- stream is for authenticated users only ----------
+    let resp = ctx
+        .http_client
+        .get(stream_url.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = open(&reader.token, &[("article", "no-such-article")], None)
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let resp = open(&reader.token, &[], Some("not-an-id")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // ---------- the author is notified about the new follower ----------
+    let mut author_stream = open(&author.token, &[], None).await.unwrap();
+    assert_eq!(author_stream.status(), StatusCode::OK);
+    assert_eq!(author_stream.headers()["content-type"], "text/event-stream");
+    let mut reader_stream = open(&reader.token, &[("article", &slugs[0])], None)
+        .await
+        .unwrap();
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/profiles/{}/follow", author.username))
+                .unwrap(),
+        )
+        .bearer_auth(&reader.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let mut author_buf = String::new();
+    let (_, data) = next_event(&mut author_stream, &mut author_buf, "notification").await;
+    assert_eq!(data["notification"]["kind"], "follow");
+    assert_eq!(
+        data["notification"]["actors"][0]["username"],
+        reader.username
+    );
+    assert_eq!(data["unreadCount"], 1);
+
+    // ---------- the follower is streamed the author's new article ... ----------
+    let new_slugs = fake::gen_articles(&ctx.backend_url, &author.token, 1, None).await;
+    let mut reader_buf = String::new();
+    let (article_event_id, data) = next_event(&mut reader_stream, &mut reader_buf, "article").await;
+    assert_eq!(data["article"]["slug"], new_slugs[0]);
+
+    // ---------- ... and new comments under the article being viewed ----------
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/articles/{}/comments", &slugs[0]))
+                .unwrap(),
+        )
+        .json(&json!({"comment": {"body": "Thanks for following!"}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let (comment_event_id, data) = next_event(&mut reader_stream, &mut reader_buf, "comment").await;
+    assert_eq!(data["article"], slugs[0]);
+    assert_eq!(data["comment"]["body"], "Thanks for following!");
+    assert_eq!(data["comment"]["author"]["username"], author.username);
+    drop(reader_stream);
+
+    // ---------- missed events are replayed on reconnect ----------
+    let mut reader_stream = open(
+        &reader.token,
+        &[("article", &slugs[0])],
+        Some(&article_event_id),
+    )
+    .await
+    .unwrap();
+    let mut reader_buf = String::new();
+    let (id, data) = next_event(&mut reader_stream, &mut reader_buf, "comment").await;
+    assert_eq!(id, comment_event_id);
+    assert_eq!(data["comment"]["body"], "Thanks for following!");
+}
+
+mod tests {
+    crate::async_test!(stream_events);
+}
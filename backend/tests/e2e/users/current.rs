@@ -41,6 +41,13 @@ async fn update_user_issues(ctx: TestContext) {
             }),
             "username cannot be empty",
         ),
+        (
+            json!({
+                "username": "rob,pike",
+                "captcha": "test",
+            }),
+            "username cannot contain commas",
+        ),
         (
             json!({
                 "password": "123",
@@ -1,4 +1,6 @@
 mod current;
 mod login;
+mod notifications;
+mod preferences;
 mod profiles;
 mod register;
@@ -0,0 +1,247 @@
+use crate::utils::{TestContext, fake};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+async fn receive_and_read_notifications(ctx: TestContext) {
+    let author = fake::create_activated_user(&ctx).await;
+    let fan1 = fake::create_activated_user(&ctx).await;
+    let fan2 = fake::create_activated_user(&ctx).await;
+    let slugs = fake::gen_articles(&ctx.backend_url, &author.token, 1, None).await;
+    let notifications_url = ctx.backend_url.join("/api/user/notifications").unwrap();
+    let list = |token: &str, query: &[(&str, &str)]| {
+        let req = ctx
+            .http_client
+            .get(notifications_url.clone())
+            .query(query)
+            .bearer_auth(token);
+        async move {
+            let resp = req.send().await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            resp.json::<Value>().await.unwrap()
+        }
+    };
+
+    // ---------- fans follow the author, favorite and comment their article ----------
+    for fan in [&fan1, &fan2] {
+        for path in [
+            format!("/api/profiles/{}/follow", author.username),
+            format!("/api/articles/{}/favorite", &slugs[0]),
+        ] {
+            let resp = ctx
+                .http_client
+                .post(ctx.backend_url.join(&path).unwrap())
+                .bearer_auth(&fan.token)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+    }
+    let comments_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{}/comments", &slugs[0]))
+        .unwrap();
+    let resp = ctx
+        .http_client
+        .post(comments_url.clone())
+        .json(&json!({"comment": {"body": "Great read!"}}))
+        .bearer_auth(&fan1.token)
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    let comment_id = payload["comment"]["id"].as_str().unwrap().to_owned();
+
+    // ---------- the author replies (mentioning the other fan) ----------
+    let resp = ctx
+        .http_client
+        .post(comments_url.clone())
+        .json(&json!({"comment": {
+            "body": format!("Thanks! And thank you @{}", fan2.username),
+            "parentId": comment_id,
+        }}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    let payload: Value = resp.json().await.unwrap();
+    let reply_id = payload["comment"]["id"].as_str().unwrap().to_owned();
+
+    // ---------- similar notifications are aggregated ----------
+    let payload = list(&author.token, &[]).await;
+    assert_eq!(payload["unreadCount"], 3);
+    assert_eq!(payload["nextCursor"], Value::Null);
+    let notifications = payload["notifications"].as_array().unwrap();
+    let kinds: Vec<_> = notifications.iter().map(|n| &n["kind"]).collect();
+    assert_eq!(kinds, ["comment", "favorite", "follow"]);
+    assert_eq!(notifications[0]["actorsCount"], 1);
+    assert_eq!(notifications[0]["actors"][0]["username"], fan1.username);
+    assert_eq!(notifications[0]["article"]["slug"], slugs[0]);
+    assert_eq!(notifications[1]["actorsCount"], 2);
+    // the latest actor goes first
+    assert_eq!(notifications[1]["actors"][0]["username"], fan2.username);
+    assert_eq!(notifications[1]["actors"][1]["username"], fan1.username);
+    assert_eq!(notifications[2]["actorsCount"], 2);
+    assert_eq!(notifications[2]["article"], Value::Null);
+    assert!(notifications.iter().all(|n| n["read"] == false));
+
+    // ---------- repeating an action does not bump the notification ----------
+    let favorite_url = ctx
+        .backend_url
+        .join(&format!("/api/articles/{}/favorite", &slugs[0]))
+        .unwrap();
+    for req in [
+        ctx.http_client.delete(favorite_url.clone()),
+        ctx.http_client.post(favorite_url),
+    ] {
+        let resp = req.bearer_auth(&fan1.token).send().await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    let payload = list(&author.token, &[]).await;
+    assert_eq!(payload["notifications"][1]["kind"], "favorite");
+    assert_eq!(
+        payload["notifications"][1]["notifiedAt"],
+        notifications[1]["notifiedAt"]
+    );
+    assert_eq!(payload["notifications"][1]["actorsCount"], 2);
+
+    // ---------- the fans are notified about the reply and mention ----------
+    let payload = list(&fan1.token, &[]).await;
+    assert_eq!(payload["notifications"][0]["kind"], "reply");
+    assert_eq!(
+        payload["notifications"][0]["commentId"],
+        comment_id.as_str()
+    );
+    let payload = list(&fan2.token, &[]).await;
+    assert_eq!(payload["notifications"][0]["kind"], "mention");
+    assert_eq!(payload["notifications"][0]["commentId"], reply_id.as_str());
+
+    // ---------- users are not notified about mentions by whom they blocked ----------
+    let block_url = ctx
+        .backend_url
+        .join(&format!("/api/profiles/{}/block", author.username))
+        .unwrap();
+    let resp = ctx
+        .http_client
+        .post(block_url)
+        .bearer_auth(&fan2.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let resp = ctx
+        .http_client
+        .post(comments_url.clone())
+        .json(&json!({"comment": {"body": format!("Again, thank you @{}", fan2.username)}}))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload = list(&fan2.token, &[]).await;
+    assert_eq!(payload["notifications"].as_array().unwrap().len(), 1);
+    assert_eq!(payload["notifications"][0]["commentId"], reply_id.as_str());
+
+    // ---------- notifications are paginated ----------
+    let payload = list(&author.token, &[("limit", "2")]).await;
+    assert_eq!(payload["notifications"].as_array().unwrap().len(), 2);
+    let cursor = payload["nextCursor"].as_str().unwrap();
+    let payload = list(&author.token, &[("limit", "2"), ("cursor", cursor)]).await;
+    assert_eq!(payload["notifications"].as_array().unwrap().len(), 1);
+    assert_eq!(payload["notifications"][0]["kind"], "follow");
+    assert_eq!(payload["nextCursor"], Value::Null);
+
+    // ---------- notifications can be marked as read one by one ... ----------
+    let payload = list(&author.token, &[]).await;
+    let follow_id = payload["notifications"][2]["id"].as_str().unwrap();
+    let read_url = |notification_id: &str| {
+        ctx.backend_url
+            .join(&format!("/api/user/notifications/{notification_id}/read"))
+            .unwrap()
+    };
+    for (user, status) in [
+        (&fan1, StatusCode::NOT_FOUND),
+        (&author, StatusCode::NO_CONTENT),
+        (&author, StatusCode::NO_CONTENT),
+    ] {
+        let resp = ctx
+            .http_client
+            .post(read_url(follow_id))
+            .bearer_auth(&user.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), status);
+    }
+    let resp = ctx
+        .http_client
+        .post(read_url("not-a-uuid"))
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let payload = list(&author.token, &[("unread", "true")]).await;
+    assert_eq!(payload["unreadCount"], 2);
+    let kinds: Vec<_> = payload["notifications"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| &n["kind"])
+        .collect();
+    assert_eq!(kinds, ["comment", "favorite"]);
+
+    // ---------- ... and once read, are no longer aggregated ----------
+    let resp = ctx
+        .http_client
+        .delete(
+            ctx.backend_url
+                .join(&format!("/api/profiles/{}/follow", author.username))
+                .unwrap(),
+        )
+        .bearer_auth(&fan1.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/profiles/{}/follow", author.username))
+                .unwrap(),
+        )
+        .bearer_auth(&fan1.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let payload = list(&author.token, &[]).await;
+    assert_eq!(payload["unreadCount"], 3);
+    assert_eq!(payload["notifications"][0]["kind"], "follow");
+    assert_eq!(payload["notifications"][0]["actorsCount"], 1);
+    assert_eq!(payload["notifications"][0]["read"], false);
+    assert_eq!(payload["notifications"][3]["read"], true);
+
+    // ---------- all notifications can be marked as read at once ----------
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join("/api/user/notifications/read")
+                .unwrap(),
+        )
+        .bearer_auth(&author.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let payload = list(&author.token, &[]).await;
+    assert_eq!(payload["unreadCount"], 0);
+    let payload = list(&author.token, &[("unread", "true")]).await;
+    assert_eq!(payload["notifications"], json!([]));
+}
+
+mod tests {
+    crate::async_test!(receive_and_read_notifications);
+}
@@ -0,0 +1,185 @@
+use crate::utils::{TestContext, fake};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use std::time::Duration;
+use url::Url;
+
+async fn manage_email_preferences(ctx: TestContext) {
+    let user = fake::create_activated_user(&ctx).await;
+    let preferences_url = ctx.backend_url.join("/api/user/email-preferences").unwrap();
+
+    // ---------- preferences are for authenticated users only ----------
+    let resp = ctx
+        .http_client
+        .get(preferences_url.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // ---------- nothing is emailed by default ----------
+    let resp = ctx
+        .http_client
+        .get(preferences_url.clone())
+        .bearer_auth(&user.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let preferences = resp.json::<Value>().await.unwrap()["preferences"].take();
+    for topic in [
+        "follow", "favorite", "comment", "reply", "mention", "article",
+    ] {
+        assert_eq!(preferences[topic], "off");
+    }
+
+    // ---------- only the provided topics are updated ----------
+    let resp = ctx
+        .http_client
+        .put(preferences_url.clone())
+        .json(&json!({"preferences": {"mention": "immediate", "follow": "daily"}}))
+        .bearer_auth(&user.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let preferences = resp.json::<Value>().await.unwrap()["preferences"].take();
+    assert_eq!(preferences["mention"], "immediate");
+    assert_eq!(preferences["follow"], "daily");
+    assert_eq!(preferences["article"], "off");
+    let resp = ctx
+        .http_client
+        .put(preferences_url.clone())
+        .json(&json!({"preferences": {"article": "weekly"}}))
+        .bearer_auth(&user.token)
+        .send()
+        .await
+        .unwrap();
+    let preferences = resp.json::<Value>().await.unwrap()["preferences"].take();
+    assert_eq!(preferences["article"], "weekly");
+    assert_eq!(preferences["mention"], "immediate");
+
+    // ---------- unknown delivery options are rejected ----------
+    let resp = ctx
+        .http_client
+        .put(preferences_url.clone())
+        .json(&json!({"preferences": {"article": "hourly"}}))
+        .bearer_auth(&user.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // ---------- emails link to the back-end to unsubscribe ----------
+    let resp = ctx
+        .http_client
+        .put(preferences_url.clone())
+        .json(&json!({"preferences": {"follow": "immediate"}}))
+        .bearer_auth(&user.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let fan = fake::create_activated_user(&ctx).await;
+    let resp = ctx
+        .http_client
+        .post(
+            ctx.backend_url
+                .join(&format!("/api/profiles/{}/follow", user.username))
+                .unwrap(),
+        )
+        .bearer_auth(&fan.token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    // the email is sent off the request path, so we might need to wait a bit
+    let mut email = None;
+    for _ in 0..20 {
+        email = ctx
+            .mailer_server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|request| request.body_json::<Value>().unwrap())
+            .find(|payload| payload["to"][0] == user.email.as_str());
+        if email.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    let email = email.expect("follow notification to have been emailed");
+    let unsubscribe_url: Url = email["headers"]["List-Unsubscribe"]
+        .as_str()
+        .unwrap()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .parse()
+        .unwrap();
+    assert_eq!(unsubscribe_url.origin(), ctx.backend_url.origin());
+    // the very same link is in the email's body for the humans to follow
+    let links: Vec<_> = linkify::LinkFinder::new()
+        .links(email["html"].as_str().unwrap())
+        .filter_map(|link| Url::parse(link.as_str()).ok())
+        .collect();
+    assert!(links.contains(&unsubscribe_url));
+    // visiting the link does not unsubscribe ...
+    let resp = ctx
+        .http_client
+        .get(unsubscribe_url.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = ctx
+        .http_client
+        .get(preferences_url.clone())
+        .bearer_auth(&user.token)
+        .send()
+        .await
+        .unwrap();
+    let preferences = resp.json::<Value>().await.unwrap()["preferences"].take();
+    assert_eq!(preferences["follow"], "immediate");
+    // ... while one-click unsubscription does
+    let resp = ctx
+        .http_client
+        .post(unsubscribe_url.clone())
+        .form(&[("List-Unsubscribe", "One-Click")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = ctx
+        .http_client
+        .get(preferences_url.clone())
+        .bearer_auth(&user.token)
+        .send()
+        .await
+        .unwrap();
+    let preferences = resp.json::<Value>().await.unwrap()["preferences"].take();
+    assert_eq!(preferences["follow"], "off");
+    assert_eq!(preferences["mention"], "immediate");
+
+    // ---------- unsubscribe links require a valid token ----------
+    let resp = ctx
+        .http_client
+        .post(ctx.backend_url.join("/api/users/unsubscribe").unwrap())
+        .query(&[("token", user.token.as_str())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let resp = ctx
+        .http_client
+        .get(ctx.backend_url.join("/api/users/unsubscribe").unwrap())
+        .query(&[("token", "not-a-token")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+mod tests {
+    crate::async_test!(manage_email_preferences);
+}
@@ -11,7 +11,6 @@ use deadpool_redis::Runtime;
 use realworld_axum_react::{Config, MailerTransport};
 use secrecy::SecretString;
 use sqlx::PgPool;
-use std::time::Duration;
 use testcontainers_modules::postgres;
 use testcontainers_modules::postgres::Postgres;
 use testcontainers_modules::testcontainers::core::IntoContainerPort as _;
@@ -29,8 +28,6 @@ use wiremock::{
 #[cfg(feature = "browser-test")]
 use tower_http::services::ServeDir;
 
-const TESTRUN_SETUP_TIMEOUT: Duration = Duration::from_secs(5);
-
 pub struct TestContext {
     #[allow(unused)]
     pub backend_url: Url,
@@ -87,32 +84,36 @@ pub fn extract_otp_from_html(html: &str) -> String {
         .expect("expected at least one link with an OTP query parameter")
 }
 
-async fn serve_on_available_port(app: Router) -> (JoinHandle<()>, Url) {
-    // prepare a channel to receive the assigned port from
-    let (tx, rx) = tokio::sync::oneshot::channel();
-    // launch app on any available port (OS will assign one for us)
-    let handle = tokio::spawn(async move {
-        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 0));
-        let listener = tokio::net::TcpListener::bind(&addr)
-            .await
-            .expect("port to be available");
-        let assigned_addr = listener.local_addr().unwrap();
-        tx.send(assigned_addr.port()).unwrap();
-        axum::serve(listener, app.into_make_service())
-            .await
-            .expect("turned app to service");
-    });
-    // wait for the app's port
-    let port = tokio::time::timeout(TESTRUN_SETUP_TIMEOUT, rx)
+async fn bind_available_port() -> (tokio::net::TcpListener, Url) {
+    // bind to any available port (OS will assign one for us)
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = tokio::net::TcpListener::bind(&addr)
         .await
-        .expect("test setup to not have timed out")
-        .expect("port to have been received from the channel");
+        .expect("port to be available");
+    let port = listener.local_addr().unwrap().port();
     // we now know the app's address
     let url = format!("http://localhost:{}", port)
         .parse()
         .expect("valid url");
 
-    (handle, url)
+    (listener, url)
+}
+
+fn serve(listener: tokio::net::TcpListener, app: Router) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
+        .expect("turned app to service");
+    })
+}
+
+#[cfg(feature = "browser-test")]
+async fn serve_on_available_port(app: Router) -> (JoinHandle<()>, Url) {
+    let (listener, url) = bind_available_port().await;
+    (serve(listener, app), url)
 }
 
 pub(crate) async fn setup(test_name: &'static str) -> TestRunContext {
@@ -171,6 +172,10 @@ pub(crate) async fn setup(test_name: &'static str) -> TestRunContext {
     #[cfg(feature = "browser-test")]
     allowed_origins.push(frontend_url.to_string());
 
+    // bind back-end's listener upfront, since the app needs to know its own
+    // public address (e.g. to build unsubscribe links in the emails)
+    let (be_listener, be_url) = bind_available_port().await;
+
     // create a mock mailer server, i.e. our local instance of Resend,
     // which just intercepts requests and allows us to inspect them
     let mailer_server = MockServer::start().await;
@@ -193,7 +198,11 @@ pub(crate) async fn setup(test_name: &'static str) -> TestRunContext {
         // https://developers.cloudflare.com/turnstile/troubleshooting/testing/#dummy-sitekeys-and-secret-keys
         captcha_secret: SecretString::from("1x0000000000000000000000000000000AA"),
         docs_ui_path: Some("/scalar".to_string()),
+        // flushing article views quicker than in production, so that
+        // we can check the stats without waiting for too long
+        analytics_flush_interval: Some(1),
         frontend_url: frontend_url.clone(),
+        backend_url: be_url.clone(),
         allowed_origins,
         mailer_transport: MailerTransport::Http,
         mailer_token: SecretString::from("re_"),
@@ -217,12 +226,12 @@ pub(crate) async fn setup(test_name: &'static str) -> TestRunContext {
     };
 
     // launch back-end application
-    let (be_handle, be_url) = serve_on_available_port(
+    let be_handle = serve(
+        be_listener,
         realworld_axum_react::api(config)
             .await
             .expect("built app and ran migrations just fine"),
-    )
-    .await;
+    );
 
     // create fantoccini client that the test function will be
     // using to navigate to get the application in the browser
@@ -523,4 +532,34 @@ pub(crate) mod fake {
         }
         set.join_all().await
     }
+
+    /// Create article with the given details and return the created article.
+    ///
+    /// Unlike `gen_articles`, this helper is useful when we need to control
+    /// the article's contents, e.g. when testing search.
+    #[cfg(feature = "api-test")]
+    pub async fn create_article(
+        ctx: &TestContext,
+        token: &str,
+        title: &str,
+        body: &str,
+        tags: &[&str],
+    ) -> Value {
+        let description: String = Sentence(10..20).fake();
+        let resp = ctx
+            .http_client
+            .post(ctx.backend_url.join("/api/articles").unwrap())
+            .bearer_auth(token)
+            .json(&json!({ "article": {
+                "title": title,
+                "description": description,
+                "body": body,
+                "tagList": tags,
+            }}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        resp.json::<Value>().await.unwrap()["article"].take()
+    }
 }